@@ -0,0 +1,524 @@
+//! Bot-vs-bot arena for measuring relative bot strength.
+//!
+//! [`run_arena`] plays every pair of the selected bots against each other on
+//! each board size, alternating who moves first. The resulting
+//! [`ArenaReport`] holds per-matchup win rates with 95% Wilson confidence
+//! intervals and an Elo ladder fitted to all games, and can be written as
+//! JSON or CSV.
+
+use crate::{Coordinates, GameStatus, GameY, GameYError, Movement, PlayerId, YBot, YBotRegistry};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// z-score of a two-sided 95% confidence interval.
+const Z_95: f64 = 1.96;
+/// Average rating of the Elo ladder.
+const ELO_MEAN: f64 = 1500.0;
+/// Iterations used to fit the Elo ladder.
+const ELO_FIT_ITERATIONS: usize = 1000;
+
+/// Settings of an arena run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaConfig {
+    /// Names of the bots to pit against each other; every pair plays.
+    pub bots: Vec<String>,
+    /// Games per pair of bots and board size.
+    pub games: u32,
+    /// Board sizes to play on.
+    pub sizes: Vec<u32>,
+    /// Random stones placed before the bots take over, to vary the games.
+    pub random_openings: u32,
+    /// Seed for the random openings. It only fixes the openings: the bots
+    /// draw their own randomness, so games with random bots still vary.
+    pub seed: Option<u64>,
+    /// Time each bot may spend on a move; `None` uses the bots' own limits.
+    pub time_limit: Option<Duration>,
+}
+
+impl Default for ArenaConfig {
+    fn default() -> Self {
+        Self {
+            bots: Vec::new(),
+            games: 20,
+            sizes: vec![7],
+            random_openings: 0,
+            seed: None,
+            time_limit: None,
+        }
+    }
+}
+
+/// Results of one pair of bots on one board size.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MatchupResult {
+    pub bot_a: String,
+    pub bot_b: String,
+    pub size: u32,
+    pub games: u32,
+    pub bot_a_wins: u32,
+    pub bot_b_wins: u32,
+    /// Share of the games won by `bot_a`.
+    pub bot_a_win_rate: f64,
+    /// Lower bound of the 95% Wilson interval of `bot_a_win_rate`.
+    pub ci_low: f64,
+    /// Upper bound of the 95% Wilson interval of `bot_a_win_rate`.
+    pub ci_high: f64,
+}
+
+/// One entry of the Elo ladder.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EloRating {
+    pub bot: String,
+    pub rating: f64,
+    pub games: u32,
+    pub wins: u32,
+}
+
+/// Full results of an arena run.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ArenaReport {
+    pub seed: Option<u64>,
+    pub games_per_matchup: u32,
+    pub sizes: Vec<u32>,
+    pub matchups: Vec<MatchupResult>,
+    /// Bots sorted from strongest to weakest.
+    pub ladder: Vec<EloRating>,
+}
+
+impl ArenaReport {
+    /// Serializes the report as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, GameYError> {
+        serde_json::to_string_pretty(self).map_err(|e| GameYError::ArenaError {
+            message: e.to_string(),
+        })
+    }
+
+    /// Serializes the matchups as CSV, one row per matchup, with the Elo
+    /// rating of both bots.
+    pub fn to_csv(&self) -> String {
+        let ratings: HashMap<&str, f64> = self
+            .ladder
+            .iter()
+            .map(|entry| (entry.bot.as_str(), entry.rating))
+            .collect();
+        let mut csv = String::from(
+            "bot_a,bot_b,size,games,bot_a_wins,bot_b_wins,bot_a_win_rate,ci_low,ci_high,bot_a_elo,bot_b_elo\n",
+        );
+        for m in &self.matchups {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{:.4},{:.4},{:.4},{:.0},{:.0}",
+                m.bot_a,
+                m.bot_b,
+                m.size,
+                m.games,
+                m.bot_a_wins,
+                m.bot_b_wins,
+                m.bot_a_win_rate,
+                m.ci_low,
+                m.ci_high,
+                ratings.get(m.bot_a.as_str()).copied().unwrap_or(ELO_MEAN),
+                ratings.get(m.bot_b.as_str()).copied().unwrap_or(ELO_MEAN),
+            );
+        }
+        csv
+    }
+
+    /// Renders a short human-readable summary.
+    pub fn summary(&self) -> String {
+        let mut text = String::new();
+        for m in &self.matchups {
+            let _ = writeln!(
+                text,
+                "size {:>2}: {} vs {}: {}-{} ({:.1}% [{:.1}%, {:.1}%])",
+                m.size,
+                m.bot_a,
+                m.bot_b,
+                m.bot_a_wins,
+                m.bot_b_wins,
+                m.bot_a_win_rate * 100.0,
+                m.ci_low * 100.0,
+                m.ci_high * 100.0,
+            );
+        }
+        let _ = writeln!(text, "Elo ladder:");
+        for (rank, entry) in self.ladder.iter().enumerate() {
+            let _ = writeln!(
+                text,
+                "{:>2}. {:<20} {:>6.0} ({}/{} wins)",
+                rank + 1,
+                entry.bot,
+                entry.rating,
+                entry.wins,
+                entry.games
+            );
+        }
+        text
+    }
+}
+
+/// Plays the configured games and builds the report.
+///
+/// Each pair of bots plays `games` games per board size, alternating who moves
+/// first. Fails if a bot is unknown, fewer than two bots are given, or no
+/// board size is given.
+pub fn run_arena(registry: &YBotRegistry, config: &ArenaConfig) -> Result<ArenaReport, GameYError> {
+    let bots = resolve_bots(registry, config)?;
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_rng(&mut rand::rng()),
+    };
+
+    let mut matchups = Vec::new();
+    for &size in &config.sizes {
+        for (i, bot_a) in bots.iter().enumerate() {
+            for bot_b in &bots[i + 1..] {
+                matchups.push(play_matchup(
+                    bot_a.as_ref(),
+                    bot_b.as_ref(),
+                    size,
+                    config,
+                    &mut rng,
+                ));
+            }
+        }
+    }
+
+    let ladder = fit_elo(&config.bots, &matchups);
+    Ok(ArenaReport {
+        seed: config.seed,
+        games_per_matchup: config.games,
+        sizes: config.sizes.clone(),
+        matchups,
+        ladder,
+    })
+}
+
+fn resolve_bots(
+    registry: &YBotRegistry,
+    config: &ArenaConfig,
+) -> Result<Vec<Arc<dyn YBot>>, GameYError> {
+    if config.bots.len() < 2 {
+        return Err(GameYError::ArenaError {
+            message: "at least two bots are needed".to_string(),
+        });
+    }
+    if (1..config.bots.len()).any(|i| config.bots[..i].contains(&config.bots[i])) {
+        return Err(GameYError::ArenaError {
+            message: "each bot may only be listed once".to_string(),
+        });
+    }
+    if config.sizes.is_empty() || config.sizes.contains(&0) {
+        return Err(GameYError::ArenaError {
+            message: "board sizes must be at least 1".to_string(),
+        });
+    }
+
+    config
+        .bots
+        .iter()
+        .map(|name| {
+            registry.find(name).ok_or_else(|| GameYError::ArenaError {
+                message: format!("unknown bot '{}'", name),
+            })
+        })
+        .collect()
+}
+
+fn play_matchup(
+    bot_a: &dyn YBot,
+    bot_b: &dyn YBot,
+    size: u32,
+    config: &ArenaConfig,
+    rng: &mut StdRng,
+) -> MatchupResult {
+    let mut bot_a_wins = 0;
+    for game_index in 0..config.games {
+        let a_moves_first = game_index % 2 == 0;
+        let players = if a_moves_first {
+            [bot_a, bot_b]
+        } else {
+            [bot_b, bot_a]
+        };
+        let winner = play_game(players, size, config, rng);
+        if (winner.id() == 0) == a_moves_first {
+            bot_a_wins += 1;
+        }
+    }
+
+    let (ci_low, ci_high) = wilson_interval(bot_a_wins, config.games);
+    MatchupResult {
+        bot_a: bot_a.name().to_string(),
+        bot_b: bot_b.name().to_string(),
+        size,
+        games: config.games,
+        bot_a_wins,
+        bot_b_wins: config.games - bot_a_wins,
+        bot_a_win_rate: win_rate(bot_a_wins, config.games),
+        ci_low,
+        ci_high,
+    }
+}
+
+/// Plays one game and returns the winner. A bot that fails to produce a legal
+/// move loses the game.
+fn play_game(
+    players: [&dyn YBot; 2],
+    size: u32,
+    config: &ArenaConfig,
+    rng: &mut StdRng,
+) -> PlayerId {
+    let mut game = GameY::new(size);
+    play_random_opening(&mut game, config.random_openings, rng);
+
+    loop {
+        let player = match game.status() {
+            GameStatus::Finished { winner } => return *winner,
+            GameStatus::Ongoing { next_player } => *next_player,
+        };
+        let bot = players[player.id() as usize];
+        let chosen = match config.time_limit {
+            Some(limit) => bot.choose_move_with_time_limit(&game, limit),
+            None => bot.choose_move(&game),
+        };
+        let legal = chosen.is_some_and(|coords| {
+            game.add_move(Movement::Placement { player, coords })
+                .is_ok()
+        });
+        if !legal {
+            return crate::other_player(player);
+        }
+    }
+}
+
+fn play_random_opening(game: &mut GameY, stones: u32, rng: &mut StdRng) {
+    for _ in 0..stones {
+        let (Some(player), false) = (game.next_player(), game.available_cells().is_empty()) else {
+            return;
+        };
+        let cells = game.available_cells();
+        let cell = cells[rng.random_range(0..cells.len())];
+        let coords = Coordinates::from_index(cell, game.board_size());
+        let _ = game.add_move(Movement::Placement { player, coords });
+    }
+}
+
+fn win_rate(wins: u32, games: u32) -> f64 {
+    if games == 0 {
+        0.0
+    } else {
+        f64::from(wins) / f64::from(games)
+    }
+}
+
+/// 95% Wilson score interval for `wins` out of `games`.
+pub fn wilson_interval(wins: u32, games: u32) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let n = f64::from(games);
+    let p = f64::from(wins) / n;
+    let z2 = Z_95 * Z_95;
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let margin = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+/// Fits Elo ratings to the matchup results with the Bradley-Terry model.
+///
+/// Every pair that played gets one virtual drawn game so that perfect scores
+/// still give finite ratings. Ratings are shifted so that their mean is 1500.
+pub fn fit_elo(bots: &[String], matchups: &[MatchupResult]) -> Vec<EloRating> {
+    let index: HashMap<&str, usize> = bots
+        .iter()
+        .enumerate()
+        .map(|(i, bot)| (bot.as_str(), i))
+        .collect();
+    let count = bots.len();
+    let mut wins = vec![vec![0.0_f64; count]; count];
+    let mut games_won = vec![0u32; count];
+    let mut games_played = vec![0u32; count];
+    let mut prior_added = HashSet::new();
+
+    for m in matchups {
+        let (Some(&a), Some(&b)) = (index.get(m.bot_a.as_str()), index.get(m.bot_b.as_str()))
+        else {
+            continue;
+        };
+        wins[a][b] += f64::from(m.bot_a_wins);
+        wins[b][a] += f64::from(m.bot_b_wins);
+        games_won[a] += m.bot_a_wins;
+        games_won[b] += m.bot_b_wins;
+        games_played[a] += m.games;
+        games_played[b] += m.games;
+        if prior_added.insert((a.min(b), a.max(b))) {
+            wins[a][b] += 0.5;
+            wins[b][a] += 0.5;
+        }
+    }
+    let mut strength = vec![1.0_f64; count];
+    for _ in 0..ELO_FIT_ITERATIONS {
+        for i in 0..count {
+            let total_wins: f64 = wins[i].iter().sum();
+            let weighted_games: f64 = (0..count)
+                .filter(|&j| j != i)
+                .map(|j| (wins[i][j] + wins[j][i]) / (strength[i] + strength[j]))
+                .sum();
+            if total_wins > 0.0 && weighted_games > 0.0 {
+                strength[i] = total_wins / weighted_games;
+            }
+        }
+    }
+
+    let raw: Vec<f64> = strength.iter().map(|s| 400.0 * s.log10()).collect();
+    let offset = ELO_MEAN - raw.iter().sum::<f64>() / count.max(1) as f64;
+    let mut ladder: Vec<EloRating> = bots
+        .iter()
+        .enumerate()
+        .map(|(i, bot)| EloRating {
+            bot: bot.clone(),
+            rating: raw[i] + offset,
+            games: games_played[i],
+            wins: games_won[i],
+        })
+        .collect();
+    ladder.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+    ladder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GreedyBot, RandomBot};
+
+    fn registry() -> YBotRegistry {
+        YBotRegistry::new()
+            .with_bot(Arc::new(RandomBot))
+            .with_bot(Arc::new(GreedyBot))
+    }
+
+    fn matchup(bot_a: &str, bot_b: &str, bot_a_wins: u32, games: u32) -> MatchupResult {
+        let (ci_low, ci_high) = wilson_interval(bot_a_wins, games);
+        MatchupResult {
+            bot_a: bot_a.to_string(),
+            bot_b: bot_b.to_string(),
+            size: 5,
+            games,
+            bot_a_wins,
+            bot_b_wins: games - bot_a_wins,
+            bot_a_win_rate: win_rate(bot_a_wins, games),
+            ci_low,
+            ci_high,
+        }
+    }
+
+    #[test]
+    fn test_wilson_interval() {
+        let (low, high) = wilson_interval(50, 100);
+        assert!((low - 0.4038).abs() < 1e-3);
+        assert!((high - 0.5962).abs() < 1e-3);
+
+        let (low, high) = wilson_interval(10, 10);
+        assert!(low > 0.65 && low < 0.75);
+        assert_eq!(high, 1.0);
+
+        assert_eq!(wilson_interval(0, 0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_fit_elo_orders_bots_and_centres_ratings() {
+        let bots = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let matchups = vec![
+            matchup("a", "b", 15, 20),
+            matchup("a", "c", 20, 20),
+            matchup("b", "c", 14, 20),
+        ];
+
+        let ladder = fit_elo(&bots, &matchups);
+
+        let names: Vec<&str> = ladder.iter().map(|e| e.bot.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        let mean = ladder.iter().map(|e| e.rating).sum::<f64>() / 3.0;
+        assert!((mean - ELO_MEAN).abs() < 1e-6);
+        assert!(ladder.iter().all(|e| e.rating.is_finite()));
+        assert_eq!(ladder[0].wins, 35);
+        assert_eq!(ladder[0].games, 40);
+    }
+
+    #[test]
+    fn test_even_results_give_equal_ratings() {
+        let bots = vec!["a".to_string(), "b".to_string()];
+        let ladder = fit_elo(&bots, &[matchup("a", "b", 5, 10)]);
+        assert!((ladder[0].rating - ladder[1].rating).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_run_arena_plays_alternating_games() {
+        let config = ArenaConfig {
+            bots: vec!["random_bot".to_string(), "greedy_bot".to_string()],
+            games: 4,
+            sizes: vec![3, 4],
+            random_openings: 1,
+            seed: Some(7),
+            time_limit: None,
+        };
+
+        let report = run_arena(&registry(), &config).unwrap();
+
+        assert_eq!(report.matchups.len(), 2);
+        assert_eq!(report.seed, Some(7));
+        for m in &report.matchups {
+            assert_eq!(m.bot_a_wins + m.bot_b_wins, 4);
+            assert!(m.ci_low <= m.bot_a_win_rate && m.bot_a_win_rate <= m.ci_high);
+        }
+        assert_eq!(report.ladder.len(), 2);
+        assert_eq!(report.ladder[0].games, 8);
+    }
+
+    #[test]
+    fn test_run_arena_rejects_unknown_bot_and_bad_config() {
+        let unknown = ArenaConfig {
+            bots: vec!["random_bot".to_string(), "nope".to_string()],
+            ..ArenaConfig::default()
+        };
+        assert!(matches!(
+            run_arena(&registry(), &unknown),
+            Err(GameYError::ArenaError { message }) if message.contains("nope")
+        ));
+
+        let single = ArenaConfig {
+            bots: vec!["random_bot".to_string()],
+            ..ArenaConfig::default()
+        };
+        assert!(run_arena(&registry(), &single).is_err());
+    }
+
+    #[test]
+    fn test_report_csv_and_json() {
+        let bots = vec!["a".to_string(), "b".to_string()];
+        let matchups = vec![matchup("a", "b", 3, 4)];
+        let report = ArenaReport {
+            seed: None,
+            games_per_matchup: 4,
+            sizes: vec![5],
+            ladder: fit_elo(&bots, &matchups),
+            matchups,
+        };
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("bot_a,bot_b,size,games"));
+        assert!(lines.next().unwrap().starts_with("a,b,5,4,3,1,0.7500,"));
+
+        let parsed: ArenaReport = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(parsed.matchups[0].bot_a_wins, 3);
+        assert_eq!(parsed.ladder.len(), 2);
+        assert!(report.summary().contains("Elo ladder:"));
+    }
+}
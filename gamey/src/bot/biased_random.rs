@@ -71,7 +71,7 @@ impl BiasedRandomBot {
 
         // If the cell is very centralized (min and max close together),
         // reduce its weight slightly
-        let center_penalty = if max_coord - min_coord < (board_size as u32) / 4 {
+        let center_penalty = if max_coord - min_coord < board_size / 4 {
             0.7
         } else {
             1.0
@@ -275,7 +275,7 @@ mod tests {
         let w1 = BiasedRandomBot::cell_weight(&Coordinates::new(0, 0, 4), board_size);
         let w2 = BiasedRandomBot::cell_weight(&Coordinates::new(1, 1, 3), board_size);
         assert!(
-            (w1 - w2).abs() > std::f64::EPSILON,
+            (w1 - w2).abs() > f64::EPSILON,
             "weights should vary between cells"
         );
     }
@@ -287,10 +287,10 @@ mod tests {
         let game = GameY::new(5);
         let mut edge_moves = 0;
         for _ in 0..100 {
-            if let Some(coords) = bot.choose_move(&game) {
-                if coords.touches_side_a() || coords.touches_side_b() || coords.touches_side_c() {
-                    edge_moves += 1;
-                }
+            if let Some(coords) = bot.choose_move(&game)
+                && (coords.touches_side_a() || coords.touches_side_b() || coords.touches_side_c())
+            {
+                edge_moves += 1;
             }
         }
         // Expect at least half of the moves land on an edge; randomness could make
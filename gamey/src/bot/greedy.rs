@@ -4,7 +4,8 @@
 //! the board state after each possible move and selecting the one with the
 //! highest heuristic score.
 
-use crate::{Coordinates, GameY, YBot};
+use crate::{Coordinates, GameY, MinimaxBot, PlayerId, YBot};
+use std::collections::BTreeSet;
 
 /// A bot that chooses moves greedily based on heuristic evaluation.
 ///
@@ -13,43 +14,94 @@ use crate::{Coordinates, GameY, YBot};
 /// that maximizes this score.
 ///
 /// The heuristic considers:
-/// - Proximity to connecting all three sides
-/// - Number of sides touched by the player's pieces
-/// - Blocking opponent's progress
+/// - How many more stones each player needs to connect all three sides
+/// - Whether the opponent is left with a winning move
+/// - How many separate groups the player's stones form
 pub struct GreedyBot;
 
 impl GreedyBot {
+    /// Score of a won position; a lost one scores the negation.
+    const WIN_SCORE: f64 = 1000.0;
+    /// Score of a position where the opponent wins on their next move.
+    const THREAT_SCORE: f64 = -900.0;
+    /// Weight of each stone of difference in connection distance.
+    const DISTANCE_WEIGHT: f64 = 10.0;
+    /// Penalty for each separate group of the player's stones.
+    const GROUP_WEIGHT: f64 = 1.0;
+
     /// Evaluates the board state for a given player.
     ///
     /// Higher scores indicate better positions for the player.
-    /// The evaluation considers strategic factors like side connections.
-    fn evaluate_board(board: &GameY, player: crate::PlayerId) -> f64 {
-        let mut score = 0.0;
-
+    /// The evaluation compares how close each player is to connecting the
+    /// three sides, and rewards merging groups.
+    fn evaluate_board(board: &GameY, player: PlayerId) -> f64 {
         // Check if the game is already won
         if let crate::GameStatus::Finished { winner } = board.status() {
             if *winner == player {
-                return 1000.0; // Win
+                return Self::WIN_SCORE;
             } else {
-                return -1000.0; // Loss
+                return -Self::WIN_SCORE;
             }
         }
 
-        // Evaluate based on connected components
-        // This is a simplified heuristic - in a real implementation,
-        // you'd analyze the union-find structure more deeply
-        let available_cells = board.available_cells().len() as f64;
-        let total_cells = (board.board_size() * (board.board_size() + 1)) / 2;
-        let filled_cells = total_cells - available_cells as u32;
+        let opponent = crate::other_player(player);
+        let unreachable = board.total_cells() + 1;
+        let my_distance = Self::connection_distance(board, player).unwrap_or(unreachable);
+        let opponent_distance = Self::connection_distance(board, opponent).unwrap_or(unreachable);
+
+        if opponent_distance <= 1 && board.next_player() == Some(opponent) {
+            return Self::THREAT_SCORE;
+        }
 
-        // Prefer positions that control more of the board
-        score += filled_cells as f64 * 0.1;
+        let mut score =
+            (f64::from(opponent_distance) - f64::from(my_distance)) * Self::DISTANCE_WEIGHT;
+        score -= board.group_count(player) as f64 * Self::GROUP_WEIGHT;
 
-        // Add some randomness to avoid deterministic play
+        // Add some randomness to break ties between equally good moves
         score += rand::random::<f64>() * 0.01;
 
         score
     }
+
+    /// Returns how many more stones `player` needs to connect all three sides,
+    /// or None if the opponent has cut them off.
+    ///
+    /// For every cell, the cheapest routes from each side are added up; the
+    /// cell itself is counted once. Own stones cost nothing and empty cells
+    /// cost one. The routes come from [`MinimaxBot::side_distances`].
+    fn connection_distance(board: &GameY, player: PlayerId) -> Option<u32> {
+        let n = board.board_size();
+        let (mut my, mut opp) = (BTreeSet::new(), BTreeSet::new());
+        for idx in 0..board.total_cells() {
+            match board.cell_owner(&Coordinates::from_index(idx, n)) {
+                Some(owner) if owner == player => {
+                    my.insert(idx);
+                }
+                Some(_) => {
+                    opp.insert(idx);
+                }
+                None => {}
+            }
+        }
+        let occupied: BTreeSet<u32> = my.union(&opp).copied().collect();
+        let nbrs = MinimaxBot::build_neighbor_map(n);
+        // Edge masks of sides A, B and C.
+        let [side_a, side_b, side_c] = [0b001, 0b010, 0b100]
+            .map(|side| MinimaxBot::side_distances(side, &my, &opp, &occupied, n, &nbrs));
+
+        (0..board.total_cells())
+            .filter(|idx| !opp.contains(idx))
+            .filter_map(|idx| {
+                let i = idx as usize;
+                let routes = [side_a[i], side_b[i], side_c[i]];
+                if routes.contains(&u32::MAX) {
+                    return None;
+                }
+                let cost = u32::from(!my.contains(&idx));
+                Some(routes.iter().sum::<u32>() - 2 * cost)
+            })
+            .min()
+    }
 }
 
 impl YBot for GreedyBot {
@@ -212,4 +264,75 @@ mod tests {
         let chosen = bot.choose_move(&game).unwrap();
         assert_eq!(chosen, Coordinates::new(0, 2, 0));
     }
+
+    #[test]
+    fn test_connection_distance_counts_missing_stones() {
+        let mut game = GameY::new(4);
+        let player = crate::PlayerId::new(0);
+        assert_eq!(GreedyBot::connection_distance(&game, player), Some(4));
+
+        game.add_move(crate::Movement::Placement {
+            player,
+            coords: Coordinates::new(1, 1, 1),
+        })
+        .unwrap();
+        assert_eq!(GreedyBot::connection_distance(&game, player), Some(3));
+    }
+
+    #[test]
+    fn test_connection_distance_is_none_when_cut_off() {
+        let mut game = GameY::new(2);
+        for mv in [
+            crate::Movement::Placement {
+                player: crate::PlayerId::new(0),
+                coords: Coordinates::new(1, 0, 0),
+            },
+            crate::Movement::Placement {
+                player: crate::PlayerId::new(1),
+                coords: Coordinates::new(0, 1, 0),
+            },
+        ] {
+            game.add_move(mv).unwrap();
+        }
+        assert_eq!(
+            GreedyBot::connection_distance(&game, crate::PlayerId::new(1)),
+            Some(1)
+        );
+        assert_eq!(
+            GreedyBot::connection_distance(&game, crate::PlayerId::new(0)),
+            Some(1)
+        );
+
+        game.add_move(crate::Movement::Placement {
+            player: crate::PlayerId::new(0),
+            coords: Coordinates::new(0, 0, 1),
+        })
+        .unwrap();
+        assert_eq!(
+            GreedyBot::connection_distance(&game, crate::PlayerId::new(1)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_greedy_bot_blocks_opponent_winning_cell() {
+        let bot = GreedyBot;
+        let mut game = GameY::new(4);
+        // Player 0 runs along side a and only (1, 2, 0) completes the connection.
+        for (player, coords) in [
+            (0, Coordinates::new(0, 0, 3)),
+            (1, Coordinates::new(0, 3, 0)),
+            (0, Coordinates::new(0, 1, 2)),
+            (1, Coordinates::new(3, 0, 0)),
+            (0, Coordinates::new(0, 2, 1)),
+        ] {
+            game.add_move(crate::Movement::Placement {
+                player: crate::PlayerId::new(player),
+                coords,
+            })
+            .unwrap();
+        }
+
+        assert_eq!(bot.choose_move(&game), Some(Coordinates::new(1, 2, 0)));
+    }
 }
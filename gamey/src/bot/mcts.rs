@@ -0,0 +1,356 @@
+//! A Monte Carlo Tree Search bot implementation.
+//!
+//! This module provides [`MctsBot`], a bot that builds a search tree with the
+//! UCT selection policy and scores positions by random playouts. Y has no
+//! draws and a full board always has a winner, so every playout ends with a
+//! result.
+
+use crate::{Coordinates, GameStatus, GameY, Movement, PlayerId, YBot};
+use rand::Rng;
+use rand::seq::SliceRandom;
+use std::time::{Duration, Instant};
+
+/// Iterations run when none are configured.
+pub const DEFAULT_MCTS_ITERATIONS: u32 = 200_000;
+/// Time limit used when none is configured.
+pub const DEFAULT_MCTS_TIME_LIMIT: Duration = Duration::from_secs(3);
+/// Exploration constant of the UCT formula when none is configured.
+pub const DEFAULT_MCTS_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// A bot that chooses moves with Monte Carlo Tree Search.
+///
+/// Each iteration walks down the tree picking the child with the best UCT
+/// score, expands one untried move, plays the rest of the game at random on a
+/// cloned [`GameY`] and records the winner along the path. The search stops
+/// after `iterations` iterations or when the time limit runs out, whichever
+/// comes first, and plays the most visited move.
+///
+/// # Example
+///
+/// ```
+/// use gamey::{GameY, MctsBot, YBot};
+///
+/// let bot = MctsBot::default().with_iterations(200);
+/// let game = GameY::new(5);
+/// assert!(bot.choose_move(&game).is_some());
+/// ```
+pub struct MctsBot {
+    iterations: u32,
+    time_limit: Duration,
+    exploration: f64,
+}
+
+impl Default for MctsBot {
+    fn default() -> Self {
+        Self {
+            iterations: DEFAULT_MCTS_ITERATIONS,
+            time_limit: DEFAULT_MCTS_TIME_LIMIT,
+            exploration: DEFAULT_MCTS_EXPLORATION,
+        }
+    }
+}
+
+/// A node of the search tree, stored in a flat arena.
+struct Node {
+    /// Cell played to reach this node, `None` for the root.
+    cell: Option<u32>,
+    /// Player who played `cell`.
+    player: PlayerId,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Cells not expanded into children yet.
+    untried: Vec<u32>,
+    visits: u32,
+    /// Playouts won by `player` through this node.
+    wins: f64,
+}
+
+impl Node {
+    fn new(cell: Option<u32>, player: PlayerId, parent: Option<usize>, game: &GameY) -> Self {
+        let untried = if game.check_game_over() {
+            Vec::new()
+        } else {
+            let mut cells = game.available_cells().clone();
+            cells.shuffle(&mut rand::rng());
+            cells
+        };
+
+        Self {
+            cell,
+            player,
+            parent,
+            children: Vec::new(),
+            untried,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+
+    fn uct_score(&self, parent_visits: u32, exploration: f64) -> f64 {
+        let visits = f64::from(self.visits);
+        self.wins / visits + exploration * (f64::from(parent_visits).ln() / visits).sqrt()
+    }
+}
+
+impl MctsBot {
+    /// Creates a bot that runs at most `iterations` iterations per move.
+    pub fn new(iterations: u32) -> Self {
+        Self::default().with_iterations(iterations)
+    }
+
+    /// Sets the maximum number of iterations per move.
+    pub fn with_iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations.max(1);
+        self
+    }
+
+    /// Sets the time this bot may spend on one move when the caller does not
+    /// give a limit.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = time_limit;
+        self
+    }
+
+    /// Sets the exploration constant of the UCT formula. Larger values try
+    /// less visited moves more often.
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Returns the maximum number of iterations per move.
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    /// Returns the time this bot spends on one move by default.
+    pub fn time_limit(&self) -> Duration {
+        self.time_limit
+    }
+
+    fn place(game: &mut GameY, player: PlayerId, cell: u32) {
+        let coords = Coordinates::from_index(cell, game.board_size());
+        // Cells come from `available_cells` and players alternate, so the
+        // placement is always legal.
+        let _ = game.add_move(Movement::Placement { player, coords });
+    }
+
+    fn winning_move(board: &GameY, player: PlayerId) -> Option<u32> {
+        board.available_cells().iter().copied().find(|&cell| {
+            let mut test_board = board.clone();
+            Self::place(&mut test_board, player, cell);
+            matches!(test_board.status(), GameStatus::Finished { winner } if *winner == player)
+        })
+    }
+
+    /// Follows the UCT policy from the root until a node with untried moves
+    /// or a finished game, replaying the moves on `game`.
+    fn select(&self, tree: &[Node], game: &mut GameY) -> usize {
+        let mut node = 0;
+        while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+            let parent_visits = tree[node].visits;
+            node = tree[node]
+                .children
+                .iter()
+                .copied()
+                .max_by(|&a, &b| {
+                    tree[a]
+                        .uct_score(parent_visits, self.exploration)
+                        .total_cmp(&tree[b].uct_score(parent_visits, self.exploration))
+                })
+                .unwrap_or(node);
+
+            if let Some(cell) = tree[node].cell {
+                Self::place(game, tree[node].player, cell);
+            }
+        }
+        node
+    }
+
+    /// Adds a child for one untried move of `node` and plays it on `game`.
+    fn expand(tree: &mut Vec<Node>, node: usize, game: &mut GameY) -> usize {
+        let (Some(cell), Some(player)) = (tree[node].untried.pop(), game.next_player()) else {
+            return node;
+        };
+
+        Self::place(game, player, cell);
+        let child = tree.len();
+        tree.push(Node::new(Some(cell), player, Some(node), game));
+        tree[node].children.push(child);
+        child
+    }
+
+    /// Plays random moves until the game ends and returns the winner.
+    fn playout(game: &mut GameY, rng: &mut impl Rng) -> Option<PlayerId> {
+        let mut cells = game.available_cells().clone();
+        cells.shuffle(rng);
+
+        for cell in cells {
+            let GameStatus::Ongoing { next_player } = *game.status() else {
+                break;
+            };
+            Self::place(game, next_player, cell);
+        }
+
+        match game.status() {
+            GameStatus::Finished { winner } => Some(*winner),
+            GameStatus::Ongoing { .. } => None,
+        }
+    }
+
+    fn backpropagate(tree: &mut [Node], mut node: usize, winner: Option<PlayerId>) {
+        loop {
+            tree[node].visits += 1;
+            if winner == Some(tree[node].player) {
+                tree[node].wins += 1.0;
+            }
+            match tree[node].parent {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+    }
+
+    fn search(&self, board: &GameY, player: PlayerId, deadline: Instant) -> Option<Coordinates> {
+        let mut rng = rand::rng();
+        // The root holds the opponent as "player" so that its children, the
+        // moves of `player`, are scored from `player`'s point of view.
+        let mut tree = vec![Node::new(None, crate::other_player(player), None, board)];
+
+        for _ in 0..self.iterations {
+            if Instant::now() >= deadline {
+                break;
+            }
+
+            let mut game = board.clone();
+            let selected = self.select(&tree, &mut game);
+            let expanded = Self::expand(&mut tree, selected, &mut game);
+            let winner = Self::playout(&mut game, &mut rng);
+            Self::backpropagate(&mut tree, expanded, winner);
+        }
+
+        tree[0]
+            .children
+            .iter()
+            .max_by_key(|&&child| tree[child].visits)
+            .and_then(|&child| tree[child].cell)
+            .or_else(|| tree[0].untried.last().copied())
+            .map(|cell| Coordinates::from_index(cell, board.board_size()))
+    }
+}
+
+impl YBot for MctsBot {
+    fn name(&self) -> &str {
+        "mcts_bot"
+    }
+
+    fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_time_limit(board, self.time_limit)
+    }
+
+    fn choose_move_with_time_limit(
+        &self,
+        board: &GameY,
+        time_limit: Duration,
+    ) -> Option<Coordinates> {
+        let deadline = Instant::now() + time_limit;
+        let player = board.next_player()?;
+        if board.available_cells().is_empty() {
+            return None;
+        }
+
+        if let Some(cell) = Self::winning_move(board, player) {
+            return Some(Coordinates::from_index(cell, board.board_size()));
+        }
+
+        self.search(board, player, deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(game: &mut GameY, moves: &[(u32, (u32, u32, u32))]) {
+        for &(player, (x, y, z)) in moves {
+            game.add_move(Movement::Placement {
+                player: PlayerId::new(player),
+                coords: Coordinates::new(x, y, z),
+            })
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_mcts_bot_name() {
+        assert_eq!(MctsBot::default().name(), "mcts_bot");
+    }
+
+    #[test]
+    fn test_mcts_bot_limits_are_configurable() {
+        let bot = MctsBot::new(500).with_time_limit(Duration::from_millis(50));
+        assert_eq!(bot.iterations(), 500);
+        assert_eq!(bot.time_limit(), Duration::from_millis(50));
+        assert_eq!(MctsBot::new(0).iterations(), 1);
+    }
+
+    #[test]
+    fn test_mcts_bot_chooses_available_cell() {
+        let bot = MctsBot::new(300);
+        let mut game = GameY::new(4);
+        play(&mut game, &[(0, (1, 1, 1)), (1, (3, 0, 0))]);
+
+        let coords = bot.choose_move(&game).unwrap();
+
+        assert!(
+            game.available_cells()
+                .contains(&coords.to_index(game.board_size()))
+        );
+    }
+
+    #[test]
+    fn test_mcts_bot_returns_none_on_finished_game() {
+        let bot = MctsBot::new(100);
+        let mut game = GameY::new(2);
+        play(&mut game, &[(0, (1, 0, 0)), (1, (0, 1, 0)), (0, (0, 0, 1))]);
+
+        assert!(bot.choose_move(&game).is_none());
+    }
+
+    #[test]
+    fn test_mcts_bot_takes_winning_move() {
+        let bot = MctsBot::new(100);
+        let mut game = GameY::new(3);
+        play(
+            &mut game,
+            &[
+                (0, (0, 0, 2)),
+                (1, (2, 0, 0)),
+                (0, (0, 1, 1)),
+                (1, (1, 1, 0)),
+            ],
+        );
+
+        assert_eq!(bot.choose_move(&game), Some(Coordinates::new(0, 2, 0)));
+    }
+
+    #[test]
+    fn test_mcts_bot_respects_time_limit() {
+        let bot = MctsBot::default();
+        let game = GameY::new(11);
+
+        let started = Instant::now();
+        let coords = bot.choose_move_with_time_limit(&game, Duration::from_millis(100));
+
+        assert!(coords.is_some());
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_playout_always_has_a_winner() {
+        let mut game = GameY::new(6);
+        let winner = MctsBot::playout(&mut game, &mut rand::rng());
+        assert!(winner.is_some());
+        assert!(game.check_game_over());
+    }
+}
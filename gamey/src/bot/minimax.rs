@@ -1,14 +1,95 @@
 //! A minimax bot implementation — improved version.
+//!
+//! The search uses iterative deepening: depths 1, 2, … up to `max_depth` are
+//! searched in turn until the time limit runs out, and the best move of the
+//! last completed depth is played.
+//!
+//! [`MinimaxBot::analyze`] runs the same search but reports what it found: the
+//! best candidate moves with their scores, each player's shortest connections
+//! between the sides and the line the bot expects.
 use crate::{Coordinates, GameY, PlayerId, YBot};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::cmp::Reverse;
 use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Time limit used when none is configured.
+pub const DEFAULT_MINIMAX_TIME_LIMIT: Duration = Duration::from_secs(5);
 
 pub struct MinimaxBot {
     max_depth: u32,
+    time_limit: Duration,
+}
+
+/// A root move scored by [`MinimaxBot::analyze`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CandidateMove {
+    /// The cell to play.
+    pub coords: Coordinates,
+    /// Value of the move for the player to move: positive is good for them,
+    /// ±10000 is a forced win or loss within the searched depth.
+    pub score: f64,
+}
+
+/// The cheapest route a player has between two sides of the board.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SideConnection {
+    /// Number of empty cells the player still has to fill.
+    pub cost: u32,
+    /// The empty cells of the route.
+    pub cells: Vec<Coordinates>,
+}
+
+/// How close a player is to connecting the three sides.
+///
+/// A route is `None` when the opponent has already cut those two sides apart.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConnectionCosts {
+    pub player: PlayerId,
+    pub side_a_to_b: Option<SideConnection>,
+    pub side_b_to_c: Option<SideConnection>,
+    pub side_a_to_c: Option<SideConnection>,
+    /// The connectivity heuristic the search evaluates positions with.
+    pub score: f64,
+}
+
+/// Result of [`MinimaxBot::analyze`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PositionAnalysis {
+    /// The player to move, from whose side the candidates are scored.
+    pub player: PlayerId,
+    /// Last search depth completed in time, or 0 if the candidates only have
+    /// a static evaluation.
+    pub depth: u32,
+    /// The best root moves, best first.
+    pub candidates: Vec<CandidateMove>,
+    /// Connection costs of player 0 and player 1, in that order.
+    pub connection_costs: Vec<ConnectionCosts>,
+    /// The expected line of play, starting with the best candidate.
+    pub principal_variation: Vec<Coordinates>,
 }
 
 type PathHeap = BinaryHeap<Reverse<(u32, u32)>>;
-type TranspositionTable = HashMap<(BTreeSet<u32>, BTreeSet<u32>), (u32, f64)>;
+type TranspositionTable = HashMap<(BTreeSet<u32>, BTreeSet<u32>), TranspositionEntry>;
+
+/// How a stored value relates to the real value of the position. Alpha-beta
+/// only learns a bound when a node is cut off or every move fails low.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The real value is at least the stored one.
+    Lower,
+    /// The real value is at most the stored one.
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TranspositionEntry {
+    depth: u32,
+    value: f64,
+    bound: Bound,
+}
 
 struct PathSearch<'a> {
     my: &'a BTreeSet<u32>,
@@ -52,6 +133,25 @@ impl<'a> PathSearch<'a> {
         MinimaxBot::edge_mask(x, y, z) & edge != 0
     }
 
+    /// Runs the search from `from_edge` until every reachable cell is settled
+    /// and returns the cost of each cell, the cell itself included.
+    fn distances_from(&self, from_edge: u8) -> Vec<u32> {
+        let total = self.total_cells();
+        let mut dist = vec![u32::MAX; total as usize];
+        let mut prev = vec![u32::MAX; total as usize];
+        let mut heap = PathHeap::new();
+
+        self.initialize_sources(from_edge, &mut dist, &mut heap);
+        while let Some(Reverse((distance, idx))) = heap.pop() {
+            if dist[idx as usize] < distance {
+                continue;
+            }
+            self.relax_neighbors(idx, distance, &mut dist, &mut prev, &mut heap);
+        }
+
+        dist
+    }
+
     fn initialize_sources(&self, from_edge: u8, dist: &mut [u32], heap: &mut PathHeap) {
         for idx in 0..self.total_cells() {
             if self.is_wall(idx) || !self.touches_edge(idx, from_edge) {
@@ -165,9 +265,44 @@ impl SearchWindow {
     }
 }
 
+/// The last depth completed by [`MinimaxBot::deepen_root_search`].
+struct RootSearch {
+    depth: u32,
+    best_move: u32,
+    scores: Vec<(u32, f64)>,
+    transposition_table: TranspositionTable,
+}
+
+/// Deadline of one search. Once it has passed, the search unwinds and the
+/// values of the interrupted depth are discarded.
+struct SearchClock {
+    deadline: Instant,
+    interrupted: Cell<bool>,
+}
+
+impl SearchClock {
+    fn new(deadline: Instant) -> Self {
+        Self {
+            deadline,
+            interrupted: Cell::new(false),
+        }
+    }
+
+    fn is_expired(&self) -> bool {
+        if !self.interrupted.get() && Instant::now() >= self.deadline {
+            self.interrupted.set(true);
+        }
+        self.interrupted.get()
+    }
+
+    fn was_interrupted(&self) -> bool {
+        self.interrupted.get()
+    }
+}
+
 impl Default for MinimaxBot {
     fn default() -> Self {
-        Self { max_depth: 6 }
+        Self::new(6)
     }
 }
 
@@ -176,7 +311,22 @@ impl MinimaxBot {
     const RELEVANT_THREAT_COST_LIMIT: u32 = 4;
 
     pub fn new(max_depth: u32) -> Self {
-        Self { max_depth }
+        Self {
+            max_depth,
+            time_limit: DEFAULT_MINIMAX_TIME_LIMIT,
+        }
+    }
+
+    /// Sets the time this bot may spend on one move when the caller does not
+    /// give a limit.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = time_limit;
+        self
+    }
+
+    /// Returns the time this bot spends on one move by default.
+    pub fn time_limit(&self) -> Duration {
+        self.time_limit
     }
 
     // ── Geometry ──────────────────────────────────────────────────────────────
@@ -192,7 +342,7 @@ impl MinimaxBot {
         ((x == 0) as u8) | (((y == 0) as u8) << 1) | (((z == 0) as u8) << 2)
     }
 
-    fn build_neighbor_map(n: u32) -> HashMap<u32, Vec<u32>> {
+    pub(crate) fn build_neighbor_map(n: u32) -> HashMap<u32, Vec<u32>> {
         const D: [(i32, i32, i32); 6] = [
             (1, -1, 0),
             (-1, 1, 0),
@@ -248,6 +398,23 @@ impl MinimaxBot {
         Some((cost, search.reconstruct_path(&prev, end)))
     }
 
+    /// Returns the cost of reaching every cell from `from_edge`, the cell
+    /// itself included, or `u32::MAX` where the route is cut off.
+    ///
+    /// Uses the same costs as [`MinimaxBot::min_path_with_cells`], so
+    /// [`GreedyBot`](crate::GreedyBot) can combine the three sides into one
+    /// connection distance.
+    pub(crate) fn side_distances(
+        from_edge: u8,
+        my: &BTreeSet<u32>,
+        opp: &BTreeSet<u32>,
+        root_occupied: &BTreeSet<u32>,
+        n: u32,
+        nbrs: &HashMap<u32, Vec<u32>>,
+    ) -> Vec<u32> {
+        PathSearch::new(my, opp, root_occupied, n, nbrs).distances_from(from_edge)
+    }
+
     #[inline]
     fn min_path_cost(
         from_edge: u8,
@@ -483,24 +650,54 @@ impl MinimaxBot {
 
     // ── Alpha-beta minimax ────────────────────────────────────────────────────
 
+    /// Returns the stored value of `key` if it was searched at least `depth`
+    /// plies deep and is either exact or a bound that already falls outside
+    /// the `alpha`..`beta` window.
     fn cached_minimax_value(
         transposition_table: &TranspositionTable,
         key: &(BTreeSet<u32>, BTreeSet<u32>),
         depth: u32,
+        alpha: f64,
+        beta: f64,
     ) -> Option<f64> {
-        match transposition_table.get(key) {
-            Some(&(stored_depth, stored_value)) if stored_depth >= depth => Some(stored_value),
+        let entry = transposition_table.get(key)?;
+        if entry.depth < depth {
+            return None;
+        }
+
+        match entry.bound {
+            Bound::Exact => Some(entry.value),
+            Bound::Lower if entry.value >= beta => Some(entry.value),
+            Bound::Upper if entry.value <= alpha => Some(entry.value),
             _ => None,
         }
     }
 
+    /// Stores `value`, searched with the `alpha`..`beta` window, as exact if
+    /// it fell inside the window and as a bound otherwise.
     fn store_minimax_value(
         transposition_table: &mut TranspositionTable,
         key: (BTreeSet<u32>, BTreeSet<u32>),
         depth: u32,
         value: f64,
+        alpha: f64,
+        beta: f64,
     ) -> f64 {
-        transposition_table.insert(key, (depth, value));
+        let bound = if value <= alpha {
+            Bound::Upper
+        } else if value >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        transposition_table.insert(
+            key,
+            TranspositionEntry {
+                depth,
+                value,
+                bound,
+            },
+        );
         value
     }
 
@@ -520,6 +717,7 @@ impl MinimaxBot {
         if maximizing { (pc, oc) } else { (oc, pc) }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn ordered_minimax_moves(
         board: &GameY,
         maximizing: bool,
@@ -597,6 +795,7 @@ impl MinimaxBot {
         n: u32,
         nbrs: &HashMap<u32, Vec<u32>>,
         transposition_table: &mut TranspositionTable,
+        clock: &SearchClock,
     ) -> Option<f64> {
         let coords = Coordinates::from_index(cell, n);
         let current = Self::current_player(maximizing, player, opponent);
@@ -625,6 +824,7 @@ impl MinimaxBot {
             n,
             nbrs,
             transposition_table,
+            clock,
         );
         Self::rollback_search_move(maximizing, cell, pc, oc, occupied);
 
@@ -648,8 +848,13 @@ impl MinimaxBot {
         n: u32,
         nbrs: &HashMap<u32, Vec<u32>>,
         transposition_table: &mut TranspositionTable,
+        clock: &SearchClock,
     ) -> f64 {
         for cell in moves {
+            if clock.is_expired() {
+                break;
+            }
+
             let Some(eval) = self.evaluate_minimax_child(
                 board,
                 cell,
@@ -666,6 +871,7 @@ impl MinimaxBot {
                 n,
                 nbrs,
                 transposition_table,
+                clock,
             ) else {
                 continue;
             };
@@ -695,15 +901,30 @@ impl MinimaxBot {
         n: u32,
         nbrs: &HashMap<u32, Vec<u32>>,
         transposition_table: &mut TranspositionTable,
+        clock: &SearchClock,
     ) -> f64 {
+        if clock.is_expired() {
+            // The caller discards this depth, so the value does not matter.
+            return 0.0;
+        }
+
         let key = (pc.clone(), oc.clone());
-        if let Some(cached_value) = Self::cached_minimax_value(transposition_table, &key, depth) {
+        if let Some(cached_value) =
+            Self::cached_minimax_value(transposition_table, &key, depth, alpha, beta)
+        {
             return cached_value;
         }
 
         if Self::is_terminal_minimax_node(board, depth) {
             let eval = Self::evaluate(board, player, pc, oc, root_occupied, n, nbrs);
-            return Self::store_minimax_value(transposition_table, key, depth, eval);
+            return Self::store_minimax_value(
+                transposition_table,
+                key,
+                depth,
+                eval,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+            );
         }
 
         let moves = Self::ordered_minimax_moves(
@@ -732,9 +953,13 @@ impl MinimaxBot {
             n,
             nbrs,
             transposition_table,
+            clock,
         );
 
-        Self::store_minimax_value(transposition_table, key, depth, value)
+        if clock.was_interrupted() {
+            return value;
+        }
+        Self::store_minimax_value(transposition_table, key, depth, value, alpha, beta)
     }
 
     fn find_immediate_winning_move(
@@ -762,36 +987,55 @@ impl MinimaxBot {
         None
     }
 
+    /// Returns a copy of the board where `player` has passed, so the opponent's
+    /// immediate threats can be probed without breaking turn order.
+    fn board_with_turn_passed(board: &GameY, player: PlayerId) -> Option<GameY> {
+        let mut passed = board.clone();
+        passed
+            .add_move(crate::Movement::Action {
+                player,
+                action: crate::GameAction::PassTurn,
+            })
+            .ok()?;
+        Some(passed)
+    }
+
     fn root_occupied_from_available(available: &[u32], n: u32) -> BTreeSet<u32> {
         let total = n * (n + 1) / 2;
         let avail_set: HashSet<u32> = available.iter().copied().collect();
         (0..total).filter(|idx| !avail_set.contains(idx)).collect()
     }
 
+    /// Searches every root move to `depth` plies and returns the value of each,
+    /// in the order given, with the transposition table of the search. Returns
+    /// `None` if the clock ran out before the depth was completed.
+    ///
+    /// Each root move gets the full window, so its value is exact even when it
+    /// is worse than an earlier one.
     #[allow(clippy::too_many_arguments)]
-    fn choose_best_root_move(
+    fn score_root_moves(
         &self,
         board: &GameY,
+        moves: &[u32],
+        depth: u32,
         current_player: PlayerId,
         opponent: PlayerId,
         root_occupied: &BTreeSet<u32>,
         n: u32,
         nbrs: &HashMap<u32, Vec<u32>>,
-        transposition_table: &mut TranspositionTable,
-    ) -> Option<Coordinates> {
+        clock: &SearchClock,
+    ) -> Option<(Vec<(u32, f64)>, TranspositionTable)> {
+        let mut transposition_table: TranspositionTable = HashMap::new();
         let mut occupied = root_occupied.clone();
         let mut pc = BTreeSet::new();
         let mut oc = BTreeSet::new();
-        let moves =
-            Self::ordered_minimax_moves(board, true, &pc, &oc, &occupied, root_occupied, n, nbrs);
-        let mut best_move = None;
-        let mut best_value = f64::NEG_INFINITY;
+        let mut scores = Vec::with_capacity(moves.len());
 
-        for cell in moves {
+        for &cell in moves {
             let Some(value) = self.evaluate_minimax_child(
                 board,
                 cell,
-                self.max_depth,
+                depth,
                 true,
                 current_player,
                 opponent,
@@ -803,19 +1047,332 @@ impl MinimaxBot {
                 root_occupied,
                 n,
                 nbrs,
-                transposition_table,
+                &mut transposition_table,
+                clock,
             ) else {
                 continue;
             };
 
+            if clock.was_interrupted() {
+                return None;
+            }
+
+            scores.push((cell, value));
+        }
+
+        Some((scores, transposition_table))
+    }
+
+    /// Returns the first move with the highest value.
+    fn best_scored_move(scores: &[(u32, f64)]) -> Option<u32> {
+        let mut best_move = None;
+        let mut best_value = f64::NEG_INFINITY;
+
+        for &(cell, value) in scores {
             if value > best_value {
                 best_value = value;
-                best_move = Some(Coordinates::from_index(cell, n));
+                best_move = Some(cell);
             }
         }
 
         best_move
     }
+
+    /// Deepens the search one ply at a time until `max_depth` or the deadline,
+    /// and returns the last completed depth, or `None` if not even depth 1
+    /// completed.
+    ///
+    /// The best move of each depth is moved to the front of `moves`, so it is
+    /// searched first at the next depth.
+    #[allow(clippy::too_many_arguments)]
+    fn deepen_root_search(
+        &self,
+        board: &GameY,
+        moves: &mut [u32],
+        current_player: PlayerId,
+        opponent: PlayerId,
+        root_occupied: &BTreeSet<u32>,
+        n: u32,
+        nbrs: &HashMap<u32, Vec<u32>>,
+        clock: &SearchClock,
+    ) -> Option<RootSearch> {
+        let mut completed = None;
+
+        for depth in 1..=self.max_depth {
+            let Some((scores, transposition_table)) = self.score_root_moves(
+                board,
+                moves,
+                depth,
+                current_player,
+                opponent,
+                root_occupied,
+                n,
+                nbrs,
+                clock,
+            ) else {
+                break;
+            };
+            let Some(best_move) = Self::best_scored_move(&scores) else {
+                break;
+            };
+
+            if let Some(position) = moves.iter().position(|&candidate| candidate == best_move) {
+                moves[..=position].rotate_right(1);
+            }
+            completed = Some(RootSearch {
+                depth,
+                best_move,
+                scores,
+                transposition_table,
+            });
+        }
+
+        completed
+    }
+
+    /// Deepens the search until `max_depth` or the deadline, keeping the best
+    /// move of the last completed depth.
+    ///
+    /// If not even depth 1 completes, the best move by the cheap move ordering
+    /// is returned.
+    fn iterative_deepening(
+        &self,
+        board: &GameY,
+        current_player: PlayerId,
+        opponent: PlayerId,
+        n: u32,
+        clock: &SearchClock,
+    ) -> Option<Coordinates> {
+        let nbrs = Self::build_neighbor_map(n);
+        let root_occupied = Self::root_occupied_from_available(board.available_cells(), n);
+        let mut moves = Self::root_moves(board, &root_occupied, n, &nbrs);
+        let fallback = moves.first().copied();
+
+        self.deepen_root_search(
+            board,
+            &mut moves,
+            current_player,
+            opponent,
+            &root_occupied,
+            n,
+            &nbrs,
+            clock,
+        )
+        .map(|search| search.best_move)
+        .or(fallback)
+        .map(|cell| Coordinates::from_index(cell, n))
+    }
+
+    fn root_moves(
+        board: &GameY,
+        root_occupied: &BTreeSet<u32>,
+        n: u32,
+        nbrs: &HashMap<u32, Vec<u32>>,
+    ) -> Vec<u32> {
+        Self::ordered_minimax_moves(
+            board,
+            true,
+            &BTreeSet::new(),
+            &BTreeSet::new(),
+            root_occupied,
+            root_occupied,
+            n,
+            nbrs,
+        )
+    }
+
+    // ── Análisis de posiciones ────────────────────────────────────────────────
+
+    /// Analyses `board` from the side of the player to move, searching for at
+    /// most `time_limit`.
+    ///
+    /// Returns up to `top_k` candidate moves, the connection costs of both
+    /// players and the principal variation of the last completed depth, or
+    /// `None` if the game is over.
+    pub fn analyze(
+        &self,
+        board: &GameY,
+        top_k: usize,
+        time_limit: Duration,
+    ) -> Option<PositionAnalysis> {
+        let clock = SearchClock::new(Instant::now() + time_limit);
+        let player = board.next_player()?;
+        let opponent = crate::other_player(player);
+        let n = board.board_size();
+        let nbrs = Self::build_neighbor_map(n);
+        let root_occupied = Self::root_occupied_from_available(board.available_cells(), n);
+        let mut moves = Self::root_moves(board, &root_occupied, n, &nbrs);
+
+        let (depth, scores, principal_variation) = match self.deepen_root_search(
+            board,
+            &mut moves,
+            player,
+            opponent,
+            &root_occupied,
+            n,
+            &nbrs,
+            &clock,
+        ) {
+            Some(search) => {
+                let line = Self::principal_variation(board, &search);
+                (search.depth, search.scores, line)
+            }
+            None => {
+                let scores =
+                    Self::static_root_scores(board, &moves, player, &root_occupied, n, &nbrs);
+                let line = Self::best_scored_move(&scores).into_iter().collect();
+                (0, scores, line)
+            }
+        };
+
+        let mut candidates: Vec<CandidateMove> = scores
+            .into_iter()
+            .map(|(cell, score)| CandidateMove {
+                coords: Coordinates::from_index(cell, n),
+                score,
+            })
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        candidates.truncate(top_k);
+
+        Some(PositionAnalysis {
+            player,
+            depth,
+            candidates,
+            connection_costs: [PlayerId::new(0), PlayerId::new(1)]
+                .into_iter()
+                .map(|p| Self::connection_costs(board, p, &root_occupied, n, &nbrs))
+                .collect(),
+            principal_variation: principal_variation
+                .into_iter()
+                .map(|cell| Coordinates::from_index(cell, n))
+                .collect(),
+        })
+    }
+
+    /// Scores each root move by the evaluation of the position right after it,
+    /// for when the clock leaves no time for a search.
+    fn static_root_scores(
+        board: &GameY,
+        moves: &[u32],
+        player: PlayerId,
+        root_occupied: &BTreeSet<u32>,
+        n: u32,
+        nbrs: &HashMap<u32, Vec<u32>>,
+    ) -> Vec<(u32, f64)> {
+        let opp = BTreeSet::new();
+        moves
+            .iter()
+            .filter_map(|&cell| {
+                let mut next_board = board.clone();
+                next_board
+                    .add_move(crate::Movement::Placement {
+                        player,
+                        coords: Coordinates::from_index(cell, n),
+                    })
+                    .ok()?;
+                let my = BTreeSet::from([cell]);
+                let value = Self::evaluate(&next_board, player, &my, &opp, root_occupied, n, nbrs);
+                Some((cell, value))
+            })
+            .collect()
+    }
+
+    /// Follows the exact entries of the transposition table of `search` from
+    /// its best move, taking at each ply the reply that is best for the side
+    /// to move, until the searched depth or a position with no exact value.
+    ///
+    /// Replies that were cut off only have a bound, which can look better
+    /// than the real best reply, so they are never followed.
+    fn principal_variation(board: &GameY, search: &RootSearch) -> Vec<u32> {
+        let mut pc = BTreeSet::from([search.best_move]);
+        let mut oc = BTreeSet::new();
+        let mut line = vec![search.best_move];
+        let mut maximizing = false;
+
+        while (line.len() as u32) < search.depth {
+            let reply = board
+                .available_cells()
+                .iter()
+                .filter(|&&cell| !pc.contains(&cell) && !oc.contains(&cell))
+                .filter_map(|&cell| {
+                    let mut key = (pc.clone(), oc.clone());
+                    if maximizing {
+                        key.0.insert(cell);
+                    } else {
+                        key.1.insert(cell);
+                    }
+                    search
+                        .transposition_table
+                        .get(&key)
+                        .filter(|entry| entry.bound == Bound::Exact)
+                        .map(|entry| (cell, entry.value))
+                })
+                .reduce(|best, candidate| {
+                    let better = if maximizing {
+                        candidate.1 > best.1
+                    } else {
+                        candidate.1 < best.1
+                    };
+                    if better { candidate } else { best }
+                });
+            let Some((cell, _)) = reply else {
+                break;
+            };
+
+            if maximizing {
+                pc.insert(cell);
+            } else {
+                oc.insert(cell);
+            }
+            line.push(cell);
+            maximizing = !maximizing;
+        }
+
+        line
+    }
+
+    fn side_connection(
+        from_edge: u8,
+        to_edge: u8,
+        my: &BTreeSet<u32>,
+        opp: &BTreeSet<u32>,
+        root_occupied: &BTreeSet<u32>,
+        n: u32,
+        nbrs: &HashMap<u32, Vec<u32>>,
+    ) -> Option<SideConnection> {
+        Self::min_path_with_cells(from_edge, to_edge, my, opp, root_occupied, n, nbrs).map(
+            |(cost, cells)| SideConnection {
+                cost,
+                cells: cells
+                    .into_iter()
+                    .map(|cell| Coordinates::from_index(cell, n))
+                    .collect(),
+            },
+        )
+    }
+
+    /// Measures the routes of `player` with the stones on the board, unlike
+    /// the search, which only counts the stones it has placed itself.
+    fn connection_costs(
+        board: &GameY,
+        player: PlayerId,
+        root_occupied: &BTreeSet<u32>,
+        n: u32,
+        nbrs: &HashMap<u32, Vec<u32>>,
+    ) -> ConnectionCosts {
+        let (my, opp): (BTreeSet<u32>, BTreeSet<u32>) = root_occupied.iter().partition(|&&cell| {
+            board.cell_owner(&Coordinates::from_index(cell, n)) == Some(player)
+        });
+
+        ConnectionCosts {
+            player,
+            side_a_to_b: Self::side_connection(0b001, 0b010, &my, &opp, root_occupied, n, nbrs),
+            side_b_to_c: Self::side_connection(0b010, 0b100, &my, &opp, root_occupied, n, nbrs),
+            side_a_to_c: Self::side_connection(0b001, 0b100, &my, &opp, root_occupied, n, nbrs),
+            score: Self::connectivity(&my, &opp, root_occupied, n, nbrs),
+        }
+    }
 }
 
 impl YBot for MinimaxBot {
@@ -824,6 +1381,15 @@ impl YBot for MinimaxBot {
     }
 
     fn choose_move(&self, board: &GameY) -> Option<Coordinates> {
+        self.choose_move_with_time_limit(board, self.time_limit)
+    }
+
+    fn choose_move_with_time_limit(
+        &self,
+        board: &GameY,
+        time_limit: Duration,
+    ) -> Option<Coordinates> {
+        let clock = SearchClock::new(Instant::now() + time_limit);
         let available = board.available_cells();
         if available.is_empty() {
             return None;
@@ -832,8 +1398,6 @@ impl YBot for MinimaxBot {
         let current_player = board.next_player()?;
         let opponent = crate::other_player(current_player);
         let n = board.board_size();
-        let nbrs = Self::build_neighbor_map(n);
-        let mut transposition_table: TranspositionTable = HashMap::new();
 
         if let Some(winning_move) =
             Self::find_immediate_winning_move(board, available, current_player, n)
@@ -841,22 +1405,14 @@ impl YBot for MinimaxBot {
             return Some(winning_move);
         }
 
-        if let Some(blocking_move) =
-            Self::find_immediate_winning_move(board, available, opponent, n)
+        if let Some(opponent_to_move) = Self::board_with_turn_passed(board, current_player)
+            && let Some(blocking_move) =
+                Self::find_immediate_winning_move(&opponent_to_move, available, opponent, n)
         {
             return Some(blocking_move);
         }
 
-        let root_occupied = Self::root_occupied_from_available(available, n);
-        self.choose_best_root_move(
-            board,
-            current_player,
-            opponent,
-            &root_occupied,
-            n,
-            &nbrs,
-            &mut transposition_table,
-        )
+        self.iterative_deepening(board, current_player, opponent, n, &clock)
     }
 }
 
@@ -966,6 +1522,53 @@ mod tests {
         assert_eq!(bot.choose_move(&game).unwrap(), Coordinates::new(0, 2, 0));
     }
 
+    #[test]
+    fn test_minimax_bot_time_limit_is_configurable() {
+        assert_eq!(
+            MinimaxBot::default().time_limit(),
+            DEFAULT_MINIMAX_TIME_LIMIT
+        );
+        let bot = MinimaxBot::new(4).with_time_limit(Duration::from_millis(250));
+        assert_eq!(bot.time_limit(), Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_minimax_bot_stops_at_deadline_on_large_board() {
+        let bot = MinimaxBot::new(20);
+        let mut game = GameY::new(13);
+        for mv in [
+            crate::Movement::Placement {
+                player: crate::PlayerId::new(0),
+                coords: Coordinates::new(4, 4, 4),
+            },
+            crate::Movement::Placement {
+                player: crate::PlayerId::new(1),
+                coords: Coordinates::new(5, 3, 4),
+            },
+        ] {
+            game.add_move(mv).unwrap();
+        }
+
+        let started = Instant::now();
+        let coords = bot
+            .choose_move_with_time_limit(&game, Duration::from_millis(100))
+            .expect("expected a move");
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(
+            game.available_cells()
+                .contains(&coords.to_index(game.board_size()))
+        );
+    }
+
+    #[test]
+    fn test_minimax_bot_returns_move_even_without_time() {
+        let bot = MinimaxBot::default();
+        let game = GameY::new(9);
+        let coords = bot.choose_move_with_time_limit(&game, Duration::ZERO);
+        assert!(coords.is_some());
+    }
+
     #[test]
     fn test_min_path_with_cells_reconstructs_the_only_open_route() {
         let n = 2;
@@ -985,4 +1588,181 @@ mod tests {
         assert_eq!(cost, 1);
         assert_eq!(path, vec![required_free_cell]);
     }
+
+    fn game_with_winning_move_for_player_0() -> GameY {
+        let mut game = GameY::new(3);
+        for mv in [
+            crate::Movement::Placement {
+                player: crate::PlayerId::new(0),
+                coords: Coordinates::new(0, 0, 2),
+            },
+            crate::Movement::Placement {
+                player: crate::PlayerId::new(1),
+                coords: Coordinates::new(2, 0, 0),
+            },
+            crate::Movement::Placement {
+                player: crate::PlayerId::new(0),
+                coords: Coordinates::new(0, 1, 1),
+            },
+            crate::Movement::Placement {
+                player: crate::PlayerId::new(1),
+                coords: Coordinates::new(1, 1, 0),
+            },
+        ] {
+            game.add_move(mv).unwrap();
+        }
+        game
+    }
+
+    #[test]
+    fn test_transposition_table_reuses_bounds_only_outside_the_window() {
+        let mut table = TranspositionTable::new();
+        let lower = (BTreeSet::from([0]), BTreeSet::new());
+        let upper = (BTreeSet::from([1]), BTreeSet::new());
+        let exact = (BTreeSet::from([2]), BTreeSet::new());
+        MinimaxBot::store_minimax_value(&mut table, lower.clone(), 2, 5.0, 0.0, 5.0);
+        MinimaxBot::store_minimax_value(&mut table, upper.clone(), 2, -5.0, -5.0, 0.0);
+        MinimaxBot::store_minimax_value(&mut table, exact.clone(), 2, 1.0, 0.0, 5.0);
+
+        assert_eq!(table[&lower].bound, Bound::Lower);
+        assert_eq!(table[&upper].bound, Bound::Upper);
+        assert_eq!(table[&exact].bound, Bound::Exact);
+        assert_eq!(
+            MinimaxBot::cached_minimax_value(&table, &lower, 2, 0.0, 10.0),
+            None
+        );
+        assert_eq!(
+            MinimaxBot::cached_minimax_value(&table, &lower, 2, 0.0, 4.0),
+            Some(5.0)
+        );
+        assert_eq!(
+            MinimaxBot::cached_minimax_value(&table, &upper, 2, -10.0, 0.0),
+            None
+        );
+        assert_eq!(
+            MinimaxBot::cached_minimax_value(&table, &upper, 2, -4.0, 0.0),
+            Some(-5.0)
+        );
+        assert_eq!(
+            MinimaxBot::cached_minimax_value(&table, &exact, 2, -10.0, 10.0),
+            Some(1.0)
+        );
+        assert_eq!(
+            MinimaxBot::cached_minimax_value(&table, &exact, 3, -10.0, 10.0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_principal_variation_follows_only_exact_values() {
+        let game = GameY::new(3);
+        let mut transposition_table = TranspositionTable::new();
+        transposition_table.insert(
+            (BTreeSet::from([0]), BTreeSet::from([1])),
+            TranspositionEntry {
+                depth: 1,
+                value: -100.0,
+                bound: Bound::Upper,
+            },
+        );
+        transposition_table.insert(
+            (BTreeSet::from([0]), BTreeSet::from([2])),
+            TranspositionEntry {
+                depth: 1,
+                value: -1.0,
+                bound: Bound::Exact,
+            },
+        );
+        let search = RootSearch {
+            depth: 2,
+            best_move: 0,
+            scores: vec![(0, -1.0)],
+            transposition_table,
+        };
+
+        assert_eq!(MinimaxBot::principal_variation(&game, &search), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_analyze_ranks_the_winning_move_first() {
+        let game = game_with_winning_move_for_player_0();
+
+        let analysis = MinimaxBot::default()
+            .analyze(&game, 2, Duration::from_secs(5))
+            .expect("expected an analysis");
+
+        assert_eq!(analysis.player, crate::PlayerId::new(0));
+        assert!(analysis.depth >= 1);
+        assert_eq!(analysis.candidates.len(), 2);
+        assert_eq!(analysis.candidates[0].coords, Coordinates::new(0, 2, 0));
+        assert!(analysis.candidates[0].score >= analysis.candidates[1].score);
+        assert_eq!(
+            analysis.principal_variation.first(),
+            Some(&Coordinates::new(0, 2, 0))
+        );
+    }
+
+    #[test]
+    fn test_analyze_reports_connection_costs_of_both_players() {
+        let game = game_with_winning_move_for_player_0();
+
+        let analysis = MinimaxBot::default()
+            .analyze(&game, 1, Duration::from_secs(5))
+            .expect("expected an analysis");
+        let [first, second] = analysis.connection_costs.as_slice() else {
+            panic!("expected costs for two players");
+        };
+
+        assert_eq!(first.player, crate::PlayerId::new(0));
+        assert_eq!(first.side_a_to_b.as_ref().map(|c| c.cost), Some(0));
+        assert_eq!(
+            first.side_a_to_c,
+            Some(SideConnection {
+                cost: 1,
+                cells: vec![Coordinates::new(0, 2, 0)],
+            })
+        );
+        assert_eq!(second.player, crate::PlayerId::new(1));
+        assert_eq!(second.side_b_to_c.as_ref().map(|c| c.cost), Some(0));
+        assert_eq!(
+            second.side_a_to_b,
+            Some(SideConnection {
+                cost: 1,
+                cells: vec![Coordinates::new(0, 2, 0)],
+            })
+        );
+    }
+
+    #[test]
+    fn test_analyze_without_time_falls_back_to_static_scores() {
+        let game = GameY::new(5);
+
+        let analysis = MinimaxBot::default()
+            .analyze(&game, 3, Duration::ZERO)
+            .expect("expected an analysis");
+
+        assert_eq!(analysis.depth, 0);
+        assert_eq!(analysis.candidates.len(), 3);
+        assert_eq!(analysis.principal_variation.len(), 1);
+        assert_eq!(
+            analysis.principal_variation[0],
+            analysis.candidates[0].coords
+        );
+    }
+
+    #[test]
+    fn test_analyze_returns_none_when_the_game_is_over() {
+        let mut game = game_with_winning_move_for_player_0();
+        game.add_move(crate::Movement::Placement {
+            player: crate::PlayerId::new(0),
+            coords: Coordinates::new(0, 2, 0),
+        })
+        .unwrap();
+
+        assert!(
+            MinimaxBot::default()
+                .analyze(&game, 3, Duration::from_secs(1))
+                .is_none()
+        );
+    }
 }
@@ -9,15 +9,21 @@
 //! - [`BiasedRandomBot`] - A bot that prefers strategically important positions
 //! - [`GreedyBot`] - A bot that chooses moves greedily based on heuristic evaluation
 //! - [`MinimaxBot`] - A bot that uses the minimax algorithm
+//! - [`MctsBot`] - A bot that uses Monte Carlo Tree Search with random playouts
+//! - [`arena`] - Bot-vs-bot matches with win rates and an Elo ladder
 
+pub mod arena;
 pub mod biased_random;
 pub mod greedy;
+pub mod mcts;
 pub mod minimax;
 pub mod random;
 pub mod ybot;
 pub mod ybot_registry;
+pub use arena::*;
 pub use biased_random::*;
 pub use greedy::*;
+pub use mcts::*;
 pub use minimax::*;
 pub use random::*;
 pub use ybot::*;
@@ -1,4 +1,5 @@
 use crate::{Coordinates, GameY};
+use std::time::Duration;
 
 /// Trait representing a Y game bot (YBot)
 /// A YBot is an AI that can choose moves in the game of Y.
@@ -9,4 +10,16 @@ pub trait YBot: Send + Sync {
 
     /// Chooses a move based on the current game state.
     fn choose_move(&self, board: &GameY) -> Option<Coordinates>;
+
+    /// Chooses a move, spending at most about `time_limit` on the search.
+    ///
+    /// Bots whose moves are cheap to compute ignore the limit; the default
+    /// implementation calls [`YBot::choose_move`].
+    fn choose_move_with_time_limit(
+        &self,
+        board: &GameY,
+        _time_limit: Duration,
+    ) -> Option<Coordinates> {
+        self.choose_move(board)
+    }
 }
@@ -0,0 +1,166 @@
+//! Position analysis for hints and post-game review.
+//!
+//! Where the choose endpoint only returns the move a bot would play, the
+//! analysis endpoint runs [`MinimaxBot::analyze`] on the bot worker pool and
+//! returns the ranked candidate moves, both players' connection costs and the
+//! principal variation. The endpoint is only offered while `minimax_bot` is
+//! enabled on the server.
+
+use super::{
+    choose::BotTimeLimitQuery, error::ErrorResponse, state::AppState, version::check_api_version,
+};
+use crate::{GameY, MinimaxBot, PositionAnalysis, YEN};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Number of candidate moves returned when the request does not say.
+pub const DEFAULT_ANALYSIS_CANDIDATES: usize = 5;
+
+/// Largest number of candidate moves a request may ask for.
+pub const MAX_ANALYSIS_CANDIDATES: usize = 20;
+
+/// The bot whose search the analysis runs.
+const ANALYSIS_BOT_ID: &str = "minimax_bot";
+
+/// Path parameters extracted from the analysis endpoint URL.
+#[derive(Deserialize)]
+pub struct AnalysisParams {
+    /// The API version (e.g., "v1").
+    api_version: String,
+}
+
+/// Query parameters accepted by the analysis endpoint.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct AnalysisQuery {
+    /// Number of candidate moves to return, between 1 and
+    /// [`MAX_ANALYSIS_CANDIDATES`]. Defaults to [`DEFAULT_ANALYSIS_CANDIDATES`].
+    pub top_k: Option<usize>,
+    /// Time the search may take, in milliseconds, capped like the choose
+    /// endpoint's `time_limit_ms`.
+    pub time_limit_ms: Option<u64>,
+}
+
+impl AnalysisQuery {
+    /// Returns the requested number of candidates, clamped to the valid range.
+    fn top_k(&self) -> usize {
+        self.top_k
+            .unwrap_or(DEFAULT_ANALYSIS_CANDIDATES)
+            .clamp(1, MAX_ANALYSIS_CANDIDATES)
+    }
+
+    /// Returns the requested time limit, capped at the bot time limit.
+    fn time_limit(&self) -> Option<Duration> {
+        BotTimeLimitQuery {
+            time_limit_ms: self.time_limit_ms,
+        }
+        .time_limit()
+    }
+}
+
+/// Response returned by the analysis endpoint on success.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnalysisResponse {
+    /// The API version used for this request.
+    pub api_version: String,
+    /// The analysis of the position.
+    #[serde(flatten)]
+    pub analysis: PositionAnalysis,
+}
+
+/// Handler for the position analysis endpoint.
+///
+/// # Route
+/// `POST /{api_version}/ybot/analyze`
+///
+/// # Query Parameters
+/// - `top_k` (optional): number of candidate moves to return.
+/// - `time_limit_ms` (optional): time the search may take.
+///
+/// # Request Body
+/// A JSON object in YEN format representing the position to analyse.
+///
+/// # Response
+/// On success, returns an `AnalysisResponse` for the player to move.
+/// On failure, returns an `ErrorResponse` with details about what went wrong,
+/// including when `minimax_bot` is not enabled.
+#[axum::debug_handler]
+pub async fn analyze(
+    State(state): State<AppState>,
+    Path(params): Path<AnalysisParams>,
+    Query(query): Query<AnalysisQuery>,
+    Json(yen): Json<YEN>,
+) -> Result<Json<AnalysisResponse>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+    let game_y = GameY::try_from(yen).map_err(|err| {
+        ErrorResponse::error(
+            &format!("Invalid YEN format: {}", err),
+            Some(params.api_version.clone()),
+            None,
+        )
+    })?;
+    if state.bots().find(ANALYSIS_BOT_ID).is_none() {
+        let available_bots = state.bots().names().join(", ");
+        return Err(ErrorResponse::error(
+            &format!(
+                "Bot not found: {}, available bots: [{}]",
+                ANALYSIS_BOT_ID, available_bots
+            ),
+            Some(params.api_version),
+            Some(ANALYSIS_BOT_ID.to_string()),
+        ));
+    }
+    let bot = MinimaxBot::default();
+    let time_limit = query.time_limit().unwrap_or_else(|| bot.time_limit());
+    let top_k = query.top_k();
+
+    let (analysis, _, _) = state
+        .bot_workers()
+        .run(move || bot.analyze(&game_y, top_k, time_limit))
+        .await
+        .map_err(|e| {
+            let mut response =
+                ErrorResponse::error(&e.to_string(), Some(params.api_version.clone()), None);
+            response.status = StatusCode::INTERNAL_SERVER_ERROR;
+            response
+        })?;
+    let analysis = analysis.ok_or_else(|| {
+        ErrorResponse::error(
+            "The game is already finished",
+            Some(params.api_version.clone()),
+            None,
+        )
+    })?;
+
+    Ok(Json(AnalysisResponse {
+        api_version: params.api_version,
+        analysis,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_k_defaults_and_is_clamped() {
+        assert_eq!(
+            AnalysisQuery::default().top_k(),
+            DEFAULT_ANALYSIS_CANDIDATES
+        );
+        let query = AnalysisQuery {
+            top_k: Some(0),
+            time_limit_ms: None,
+        };
+        assert_eq!(query.top_k(), 1);
+        let query = AnalysisQuery {
+            top_k: Some(1000),
+            time_limit_ms: None,
+        };
+        assert_eq!(query.top_k(), MAX_ANALYSIS_CANDIDATES);
+    }
+}
@@ -0,0 +1,171 @@
+//! Bounded pool for bot move computation.
+//!
+//! A bot may search for up to [`MAX_BOT_TIME_LIMIT`](super::choose::MAX_BOT_TIME_LIMIT)
+//! before answering. Moves are therefore computed on tokio's blocking threads
+//! rather than on the async workers that serve requests, and at most
+//! `max_concurrent_bot_moves` of them run at once. Further moves wait for a free
+//! slot. Handlers release the game lock before asking a bot for a move, so a
+//! slow search only delays the game it belongs to.
+
+use super::state::AppState;
+use crate::{Coordinates, GameY, GameYError, YBot};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
+
+/// Runs bot moves on blocking threads, a bounded number at a time.
+pub struct BotWorkerPool {
+    permits: Arc<Semaphore>,
+    capacity: usize,
+    /// Moves waiting for a free slot.
+    queued: AtomicUsize,
+}
+
+/// Decrements the queued-moves gauge when a move leaves the queue, also when
+/// the waiting request is dropped.
+struct QueuedGuard<'a>(&'a AtomicUsize);
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl BotWorkerPool {
+    /// Creates a pool running at most `max_concurrent` moves at once.
+    pub fn new(max_concurrent: usize) -> Self {
+        let capacity = max_concurrent.max(1);
+        Self {
+            permits: Arc::new(Semaphore::new(capacity)),
+            capacity,
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of moves waiting for a free slot.
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of moves being computed.
+    pub fn running(&self) -> usize {
+        self.capacity - self.permits.available_permits()
+    }
+
+    /// Runs `job` on a blocking thread once a slot is free.
+    ///
+    /// Returns the result together with the time spent waiting for the slot
+    /// and the time spent running.
+    ///
+    /// # Errors
+    /// Returns `GameYError::ServerError` if the job panics.
+    pub async fn run<T, F>(&self, job: F) -> Result<(T, Duration, Duration), GameYError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let enqueued_at = Instant::now();
+        let permit = {
+            self.queued.fetch_add(1, Ordering::Relaxed);
+            let _queued = QueuedGuard(&self.queued);
+            Arc::clone(&self.permits)
+                .acquire_owned()
+                .await
+                .expect("bot worker semaphore is never closed")
+        };
+        let queue_wait = enqueued_at.elapsed();
+
+        let started_at = Instant::now();
+        let result = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            job()
+        })
+        .await
+        .map_err(|e| GameYError::ServerError {
+            message: format!("Bot worker failed: {}", e),
+        })?;
+        Ok((result, queue_wait, started_at.elapsed()))
+    }
+}
+
+/// Asks `bot` for a move on the worker pool of `state`, within `time_limit`
+/// if one is given, and records the queue wait and think time.
+pub(super) async fn choose_bot_move(
+    state: &AppState,
+    bot_id: &str,
+    bot: Arc<dyn YBot>,
+    game: GameY,
+    time_limit: Option<Duration>,
+) -> Result<Option<Coordinates>, GameYError> {
+    let (coords, queue_wait, think_time) = state
+        .bot_workers()
+        .run(move || match time_limit {
+            Some(limit) => bot.choose_move_with_time_limit(&game, limit),
+            None => bot.choose_move(&game),
+        })
+        .await?;
+    state
+        .metrics()
+        .observe_bot_move(bot_id, queue_wait, think_time);
+    Ok(coords)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_returns_job_result() {
+        let pool = BotWorkerPool::new(2);
+
+        let (result, _, _) = pool.run(|| 6 * 7).await.unwrap();
+
+        assert_eq!(result, 42);
+        assert_eq!(pool.running(), 0);
+        assert_eq!(pool.queued(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_beyond_capacity_wait_for_a_slot() {
+        let pool = Arc::new(BotWorkerPool::new(1));
+        let (release, blocked) = std::sync::mpsc::channel::<()>();
+
+        let first = {
+            let pool = Arc::clone(&pool);
+            tokio::spawn(async move { pool.run(move || blocked.recv().unwrap()).await })
+        };
+        while pool.running() == 0 {
+            tokio::task::yield_now().await;
+        }
+        let second = {
+            let pool = Arc::clone(&pool);
+            tokio::spawn(async move { pool.run(|| ()).await })
+        };
+        while pool.queued() == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(pool.running(), 1);
+
+        release.send(()).unwrap();
+        first.await.unwrap().unwrap();
+        let (_, queue_wait, _) = second.await.unwrap().unwrap();
+
+        assert!(queue_wait > Duration::ZERO);
+        assert_eq!(pool.queued(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_job_is_a_server_error() {
+        let pool = BotWorkerPool::new(1);
+
+        let result = pool.run(|| panic!("bot crashed")).await;
+
+        assert!(matches!(result, Err(GameYError::ServerError { .. })));
+        assert_eq!(pool.running(), 0);
+    }
+}
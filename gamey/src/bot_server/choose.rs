@@ -1,10 +1,18 @@
-use super::{error::ErrorResponse, state::AppState, version::check_api_version};
+use super::{
+    bot_workers::choose_bot_move, error::ErrorResponse, state::AppState, version::check_api_version,
+};
 use crate::{Coordinates, GameY, YEN};
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::StatusCode,
 };
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Longest time a request may let a bot think, well inside the 60 s online
+/// turn timer.
+pub const MAX_BOT_TIME_LIMIT: Duration = Duration::from_secs(30);
 
 /// Path parameters extracted from the choose endpoint URL.
 #[derive(Deserialize)]
@@ -15,6 +23,22 @@ pub struct ChooseParams {
     bot_id: String,
 }
 
+/// Query parameters accepted by the endpoints where a bot chooses a move.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct BotTimeLimitQuery {
+    /// Time the bot may spend on its move, in milliseconds. Requests above
+    /// [`MAX_BOT_TIME_LIMIT`] are capped; without it the bot's own limit is used.
+    pub time_limit_ms: Option<u64>,
+}
+
+impl BotTimeLimitQuery {
+    /// Returns the requested time limit, capped at [`MAX_BOT_TIME_LIMIT`].
+    pub(super) fn time_limit(&self) -> Option<Duration> {
+        self.time_limit_ms
+            .map(|ms| Duration::from_millis(ms).min(MAX_BOT_TIME_LIMIT))
+    }
+}
+
 /// Response returned by the choose endpoint on success.
 ///
 /// Contains the bot's chosen move coordinates along with context
@@ -37,6 +61,9 @@ pub struct MoveResponse {
 /// # Route
 /// `POST /{api_version}/ybot/choose/{bot_id}`
 ///
+/// # Query Parameters
+/// - `time_limit_ms` (optional): time the bot may spend on the move.
+///
 /// # Request Body
 /// A JSON object in YEN format representing the current game state.
 ///
@@ -47,6 +74,7 @@ pub struct MoveResponse {
 pub async fn choose(
     State(state): State<AppState>,
     Path(params): Path<ChooseParams>,
+    Query(time_limit): Query<BotTimeLimitQuery>,
     Json(yen): Json<YEN>,
 ) -> Result<Json<MoveResponse>, ErrorResponse> {
     check_api_version(&params.api_version)?;
@@ -74,7 +102,18 @@ pub async fn choose(
             ));
         }
     };
-    let coords = match bot.choose_move(&game_y) {
+    let chosen = choose_bot_move(&state, &params.bot_id, bot, game_y, time_limit.time_limit())
+        .await
+        .map_err(|e| {
+            let mut response = ErrorResponse::error(
+                &e.to_string(),
+                Some(params.api_version.clone()),
+                Some(params.bot_id.clone()),
+            );
+            response.status = StatusCode::INTERNAL_SERVER_ERROR;
+            response
+        })?;
+    let coords = match chosen {
         Some(coords) => coords,
         None => {
             // Handle the case where the bot has no valid moves
@@ -97,6 +136,16 @@ pub async fn choose(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_time_limit_is_capped() {
+        let query = BotTimeLimitQuery {
+            time_limit_ms: Some(10 * 60 * 1000),
+        };
+
+        assert_eq!(query.time_limit(), Some(MAX_BOT_TIME_LIMIT));
+        assert_eq!(BotTimeLimitQuery::default().time_limit(), None);
+    }
+
     #[test]
     fn test_move_response_creation() {
         let response = MoveResponse {
@@ -0,0 +1,338 @@
+//! Per-player game clocks.
+//!
+//! A [`TimeControl`] is chosen when a game is created or requested through
+//! matchmaking. The session then keeps a [`GameClock`] that runs for the player
+//! to move and is pressed after every turn. A player whose time runs out loses
+//! the game with [`GameCompletionReason::Timeout`](super::state::GameCompletionReason::Timeout).
+
+use crate::PlayerId;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Longest main time a game may be created with.
+const MAX_BASE_MS: u64 = 24 * 60 * 60 * 1000;
+/// Longest increment or byo-yomi period.
+const MAX_EXTRA_MS: u64 = 60 * 60 * 1000;
+/// Most byo-yomi periods a game may be created with.
+const MAX_PERIODS: u32 = 100;
+
+/// How much time each player gets.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TimeControl {
+    /// `base_ms` for the whole game, plus `increment_ms` after every turn.
+    Fischer { base_ms: u64, increment_ms: u64 },
+    /// `base_ms` of main time, then `periods` periods of `period_ms`. A turn
+    /// that takes longer than one period uses it up; finishing a turn within a
+    /// period keeps it.
+    ByoYomi {
+        base_ms: u64,
+        period_ms: u64,
+        periods: u32,
+    },
+}
+
+impl TimeControl {
+    /// Checks that the time control is within the supported limits.
+    pub fn validate(&self) -> Result<(), String> {
+        let (base_ms, extra_ms) = match *self {
+            TimeControl::Fischer {
+                base_ms,
+                increment_ms,
+            } => (base_ms, increment_ms),
+            TimeControl::ByoYomi {
+                base_ms,
+                period_ms,
+                periods,
+            } => {
+                if period_ms == 0 || periods == 0 || periods > MAX_PERIODS {
+                    return Err(format!(
+                        "byo-yomi needs a positive period and between 1 and {} periods",
+                        MAX_PERIODS
+                    ));
+                }
+                (base_ms, period_ms)
+            }
+        };
+
+        if base_ms == 0 || base_ms > MAX_BASE_MS {
+            return Err(format!(
+                "base time must be between 1 and {} ms",
+                MAX_BASE_MS
+            ));
+        }
+        if extra_ms > MAX_EXTRA_MS {
+            return Err(format!(
+                "increments and periods must be at most {} ms",
+                MAX_EXTRA_MS
+            ));
+        }
+        Ok(())
+    }
+
+    fn base(&self) -> Duration {
+        match *self {
+            TimeControl::Fischer { base_ms, .. } | TimeControl::ByoYomi { base_ms, .. } => {
+                Duration::from_millis(base_ms)
+            }
+        }
+    }
+
+    fn periods(&self) -> u32 {
+        match *self {
+            TimeControl::Fischer { .. } => 0,
+            TimeControl::ByoYomi { periods, .. } => periods,
+        }
+    }
+}
+
+/// Clock readings as returned by the API and stored with the session.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockState {
+    pub time_control: TimeControl,
+    /// Main time left for players 0 and 1, in milliseconds.
+    pub remaining_ms: [u64; 2],
+    /// Byo-yomi periods left for players 0 and 1. Always zero with Fischer.
+    pub periods_left: [u32; 2],
+    /// Player whose clock is running, if any.
+    pub running_player: Option<u32>,
+}
+
+/// The clocks of both players of a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameClock {
+    time_control: TimeControl,
+    remaining: [Duration; 2],
+    periods_left: [u32; 2],
+    /// Player whose clock is running and when it started.
+    running: Option<(PlayerId, Instant)>,
+}
+
+impl GameClock {
+    /// Creates full clocks and starts the one of `first_player`.
+    pub fn new(time_control: TimeControl, first_player: Option<PlayerId>, now: Instant) -> Self {
+        Self {
+            time_control,
+            remaining: [time_control.base(); 2],
+            periods_left: [time_control.periods(); 2],
+            running: first_player.map(|player| (player, now)),
+        }
+    }
+
+    /// Restores clocks from a stored state, restarting the running clock at
+    /// `now`.
+    pub fn from_state(state: &ClockState, now: Instant) -> Self {
+        Self {
+            time_control: state.time_control,
+            remaining: state.remaining_ms.map(Duration::from_millis),
+            periods_left: state.periods_left,
+            running: state
+                .running_player
+                .map(|player| (PlayerId::new(player), now)),
+        }
+    }
+
+    /// Returns the time control of the game.
+    pub fn time_control(&self) -> TimeControl {
+        self.time_control
+    }
+
+    /// Returns the player whose time has run out, if any.
+    pub fn flagged_player(&self, now: Instant) -> Option<PlayerId> {
+        let (player, started_at) = self.running?;
+        let elapsed = now.saturating_duration_since(started_at);
+        self.charge(player, elapsed).is_none().then_some(player)
+    }
+
+    /// Ends the turn of the running player and starts the clock of
+    /// `next_player`, or stops the clocks if there is none.
+    ///
+    /// The player who just moved gets the Fischer increment. A player whose
+    /// time ran out is left with no time.
+    pub fn press(&mut self, next_player: Option<PlayerId>, now: Instant) {
+        if let Some((player, started_at)) = self.running {
+            let idx = Self::index(player);
+            let elapsed = now.saturating_duration_since(started_at);
+            let (remaining, periods_left) = self.charge(player, elapsed).unwrap_or_default();
+            self.remaining[idx] = remaining;
+            self.periods_left[idx] = periods_left;
+
+            if let TimeControl::Fischer { increment_ms, .. } = self.time_control
+                && next_player.is_some()
+                && next_player != Some(player)
+            {
+                self.remaining[idx] += Duration::from_millis(increment_ms);
+            }
+        }
+        self.running = next_player.map(|player| (player, now));
+    }
+
+    /// Returns the clock readings at `now`, counting the running turn.
+    pub fn state(&self, now: Instant) -> ClockState {
+        let mut remaining = self.remaining;
+        let mut periods_left = self.periods_left;
+        if let Some((player, started_at)) = self.running {
+            let idx = Self::index(player);
+            let elapsed = now.saturating_duration_since(started_at);
+            (remaining[idx], periods_left[idx]) = self.charge(player, elapsed).unwrap_or_default();
+        }
+
+        ClockState {
+            time_control: self.time_control,
+            remaining_ms: remaining.map(|time| time.as_millis().min(u128::from(u64::MAX)) as u64),
+            periods_left,
+            running_player: self.running.map(|(player, _)| player.id()),
+        }
+    }
+
+    /// Main time and periods left for `player` after spending `elapsed` on a
+    /// turn, or `None` if that exhausts their time.
+    fn charge(&self, player: PlayerId, elapsed: Duration) -> Option<(Duration, u32)> {
+        let idx = Self::index(player);
+        let remaining = self.remaining[idx];
+        if elapsed < remaining {
+            return Some((remaining - elapsed, self.periods_left[idx]));
+        }
+
+        let TimeControl::ByoYomi { period_ms, .. } = self.time_control else {
+            return None;
+        };
+        let overtime = (elapsed - remaining).as_millis();
+        let used_periods = overtime / u128::from(period_ms);
+        let periods_left = u128::from(self.periods_left[idx]).checked_sub(used_periods)?;
+        (periods_left > 0).then_some((Duration::ZERO, periods_left as u32))
+    }
+
+    fn index(player: PlayerId) -> usize {
+        (player.id() as usize).min(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fischer(base_ms: u64, increment_ms: u64) -> TimeControl {
+        TimeControl::Fischer {
+            base_ms,
+            increment_ms,
+        }
+    }
+
+    #[test]
+    fn test_fischer_clock_charges_mover_and_adds_increment() {
+        let start = Instant::now();
+        let mut clock = GameClock::new(fischer(10_000, 2_000), Some(PlayerId::new(0)), start);
+
+        clock.press(Some(PlayerId::new(1)), start + Duration::from_secs(3));
+        let state = clock.state(start + Duration::from_secs(4));
+
+        assert_eq!(state.remaining_ms, [9_000, 9_000]);
+        assert_eq!(state.running_player, Some(1));
+    }
+
+    #[test]
+    fn test_fischer_clock_flags_running_player() {
+        let start = Instant::now();
+        let clock = GameClock::new(fischer(5_000, 0), Some(PlayerId::new(0)), start);
+
+        assert_eq!(
+            clock.flagged_player(start + Duration::from_millis(4_999)),
+            None
+        );
+        assert_eq!(
+            clock.flagged_player(start + Duration::from_secs(5)),
+            Some(PlayerId::new(0))
+        );
+        assert_eq!(
+            clock.state(start + Duration::from_secs(6)).remaining_ms,
+            [0, 5_000]
+        );
+    }
+
+    #[test]
+    fn test_byo_yomi_uses_periods_only_for_slow_turns() {
+        let start = Instant::now();
+        let control = TimeControl::ByoYomi {
+            base_ms: 1_000,
+            period_ms: 5_000,
+            periods: 3,
+        };
+        let mut clock = GameClock::new(control, Some(PlayerId::new(0)), start);
+
+        // 1 s of main time plus 4 s inside the first period keeps all periods.
+        clock.press(Some(PlayerId::new(1)), start + Duration::from_secs(5));
+        clock.press(Some(PlayerId::new(0)), start + Duration::from_secs(5));
+        assert_eq!(
+            clock.state(start + Duration::from_secs(5)).periods_left,
+            [3, 3]
+        );
+        assert_eq!(
+            clock.state(start + Duration::from_secs(5)).remaining_ms[0],
+            0
+        );
+
+        // An 11 s turn in overtime uses two periods.
+        clock.press(Some(PlayerId::new(1)), start + Duration::from_secs(16));
+        assert_eq!(
+            clock.state(start + Duration::from_secs(16)).periods_left,
+            [1, 3]
+        );
+
+        clock.press(Some(PlayerId::new(0)), start + Duration::from_secs(16));
+        assert_eq!(
+            clock.flagged_player(start + Duration::from_secs(21)),
+            Some(PlayerId::new(0))
+        );
+    }
+
+    #[test]
+    fn test_press_without_next_player_stops_the_clock() {
+        let start = Instant::now();
+        let mut clock = GameClock::new(fischer(10_000, 1_000), Some(PlayerId::new(0)), start);
+
+        clock.press(None, start + Duration::from_secs(2));
+
+        let later = clock.state(start + Duration::from_secs(60));
+        assert_eq!(later.remaining_ms, [8_000, 10_000]);
+        assert_eq!(later.running_player, None);
+        assert_eq!(clock.flagged_player(start + Duration::from_secs(60)), None);
+    }
+
+    #[test]
+    fn test_clock_state_round_trip_restarts_running_clock() {
+        let start = Instant::now();
+        let clock = GameClock::new(fischer(10_000, 0), Some(PlayerId::new(1)), start);
+        let state = clock.state(start + Duration::from_secs(4));
+
+        let restored_at = start + Duration::from_secs(100);
+        let restored = GameClock::from_state(&state, restored_at);
+
+        assert_eq!(restored.state(restored_at), state);
+    }
+
+    #[test]
+    fn test_time_control_validation() {
+        assert!(fischer(60_000, 1_000).validate().is_ok());
+        assert!(fischer(0, 1_000).validate().is_err());
+        assert!(fischer(60_000, MAX_EXTRA_MS + 1).validate().is_err());
+        assert!(
+            TimeControl::ByoYomi {
+                base_ms: 60_000,
+                period_ms: 10_000,
+                periods: 0,
+            }
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_time_control_serialization_is_tagged() {
+        let json = serde_json::to_value(fischer(180_000, 2_000)).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"kind": "fischer", "base_ms": 180_000, "increment_ms": 2_000})
+        );
+    }
+}
@@ -0,0 +1,673 @@
+//! Runtime configuration of the bot server.
+//!
+//! A [`ServerConfig`] is built by [`ServerConfig::load`] from, in increasing
+//! order of precedence:
+//! 1. the built-in defaults,
+//! 2. a TOML file given with `--config` or `GAMEY_CONFIG`,
+//! 3. environment variables,
+//! 4. command-line flags.
+//!
+//! The result is validated before the server starts, so a typo in a
+//! deployment fails fast instead of silently running with a default.
+//!
+//! ```toml
+//! bind_address = "0.0.0.0"
+//! port = 3000
+//! storage_dir = "/data/games"
+//! bots = ["random_bot", "greedy_bot"]
+//! max_concurrent_bot_moves = 4
+//!
+//! [stats]
+//! url = "http://stats:3001"
+//! internal_token = "stats-internal-token"
+//! outbox_dir = "/data/stats-outbox"
+//! retry_initial_backoff_ms = 1000
+//! retry_max_backoff_secs = 300
+//!
+//! [online]
+//! player_inactivity_timeout_secs = 60
+//! turn_timeout_secs = 60
+//!
+//! [exhibition]
+//! move_delay_ms = 1000
+//! max_concurrent = 8
+//!
+//! [matchmaking]
+//! default_board_size = 7
+//! tick_ms = 300
+//!
+//! [matchmaking.tickets]
+//! heartbeat_timeout_secs = 15
+//! resolved_ticket_ttl_secs = 300
+//!
+//! [retention]
+//! finished_game_ttl_secs = 600
+//! idle_local_game_ttl_secs = 7200
+//! max_sessions = 10000
+//! sweep_interval_secs = 30
+//! ```
+
+use super::{matchmaking::TicketExpiryPolicy, retention::RetentionPolicy};
+use crate::{
+    BiasedRandomBot, CliArgs, GameYError, GreedyBot, MctsBot, MinimaxBot, RandomBot, YBotRegistry,
+};
+use serde::{Deserialize, Deserializer};
+use std::{
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+
+const DEFAULT_PORT: u16 = 3000;
+const DEFAULT_STATS_URL: &str = "http://stats:3001";
+const DEFAULT_STATS_INTERNAL_TOKEN: &str = "stats-internal-token";
+const DEFAULT_STATS_RETRY_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const DEFAULT_STATS_RETRY_MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_PLAYER_INACTIVITY_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_TURN_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_EXHIBITION_MOVE_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_CONCURRENT_EXHIBITIONS: usize = 8;
+const DEFAULT_BOARD_SIZE: u32 = 7;
+const DEFAULT_MATCHMAKING_TICK: Duration = Duration::from_millis(300);
+
+/// Looks up an environment variable by name.
+///
+/// The process environment is used in production; tests pass a fixed map.
+pub type EnvLookup<'a> = &'a dyn Fn(&str) -> Option<String>;
+
+/// Settings of the bot server.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// IP address the HTTP server listens on.
+    pub bind_address: IpAddr,
+    /// TCP port the HTTP server listens on.
+    pub port: u16,
+    /// Directory game sessions are stored in. Kept in memory only when unset.
+    pub storage_dir: Option<PathBuf>,
+    /// Bots that can be played against, by name.
+    pub bots: Vec<String>,
+    /// Most bot moves computed at the same time. Defaults to the number of
+    /// CPUs.
+    pub max_concurrent_bot_moves: usize,
+    pub stats: StatsConfig,
+    pub online: OnlineGameConfig,
+    pub exhibition: ExhibitionConfig,
+    pub matchmaking: MatchmakingConfig,
+    pub retention: RetentionPolicy,
+}
+
+/// Where finished games are reported.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatsConfig {
+    /// Base URL of the stats service.
+    pub url: String,
+    /// Token sent in the `x-service-token` header.
+    pub internal_token: String,
+    /// Directory reports waiting for delivery are stored in. Kept in memory
+    /// only when unset.
+    pub outbox_dir: Option<PathBuf>,
+    /// Delay before retrying a report that could not be delivered.
+    #[serde(
+        rename = "retry_initial_backoff_ms",
+        deserialize_with = "deserialize_millis"
+    )]
+    pub retry_initial_backoff: Duration,
+    /// Longest delay between two attempts to deliver a report.
+    #[serde(
+        rename = "retry_max_backoff_secs",
+        deserialize_with = "deserialize_secs"
+    )]
+    pub retry_max_backoff: Duration,
+}
+
+/// Timeouts of online (matchmaking) games.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct OnlineGameConfig {
+    /// Time a player may go without contacting the server before forfeiting.
+    #[serde(
+        rename = "player_inactivity_timeout_secs",
+        deserialize_with = "deserialize_secs"
+    )]
+    pub player_inactivity_timeout: Duration,
+    /// Time after which the turn of a player without a clock is passed.
+    #[serde(rename = "turn_timeout_secs", deserialize_with = "deserialize_secs")]
+    pub turn_timeout: Duration,
+}
+
+/// Pace and number of bot_vs_bot exhibition games.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExhibitionConfig {
+    /// Pause before each move, so people watching can follow the game.
+    #[serde(rename = "move_delay_ms", deserialize_with = "deserialize_millis")]
+    pub move_delay: Duration,
+    /// Exhibitions that may run at once; creating one more is rejected.
+    pub max_concurrent: usize,
+}
+
+/// Matchmaking queue settings.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchmakingConfig {
+    /// Board size of tickets that do not ask for one.
+    pub default_board_size: u32,
+    /// Time between two runs of the matchmaking worker.
+    #[serde(rename = "tick_ms", deserialize_with = "deserialize_millis")]
+    pub tick: Duration,
+    pub tickets: TicketExpiryPolicy,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        let mut bots = all_bots().names();
+        bots.sort();
+        Self {
+            bind_address: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            port: DEFAULT_PORT,
+            storage_dir: None,
+            bots,
+            max_concurrent_bot_moves: std::thread::available_parallelism()
+                .map_or(1, |cpus| cpus.get()),
+            stats: StatsConfig::default(),
+            online: OnlineGameConfig::default(),
+            exhibition: ExhibitionConfig::default(),
+            matchmaking: MatchmakingConfig::default(),
+            retention: RetentionPolicy::default(),
+        }
+    }
+}
+
+impl Default for StatsConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_STATS_URL.to_string(),
+            internal_token: DEFAULT_STATS_INTERNAL_TOKEN.to_string(),
+            outbox_dir: None,
+            retry_initial_backoff: DEFAULT_STATS_RETRY_INITIAL_BACKOFF,
+            retry_max_backoff: DEFAULT_STATS_RETRY_MAX_BACKOFF,
+        }
+    }
+}
+
+impl Default for OnlineGameConfig {
+    fn default() -> Self {
+        Self {
+            player_inactivity_timeout: DEFAULT_PLAYER_INACTIVITY_TIMEOUT,
+            turn_timeout: DEFAULT_TURN_TIMEOUT,
+        }
+    }
+}
+
+impl Default for ExhibitionConfig {
+    fn default() -> Self {
+        Self {
+            move_delay: DEFAULT_EXHIBITION_MOVE_DELAY,
+            max_concurrent: DEFAULT_MAX_CONCURRENT_EXHIBITIONS,
+        }
+    }
+}
+
+impl Default for MatchmakingConfig {
+    fn default() -> Self {
+        Self {
+            default_board_size: DEFAULT_BOARD_SIZE,
+            tick: DEFAULT_MATCHMAKING_TICK,
+            tickets: TicketExpiryPolicy::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Builds the configuration from the config file, the process environment
+    /// and `args`, and validates it.
+    ///
+    /// # Errors
+    /// Returns `GameYError::ConfigError` if the file cannot be read or parsed,
+    /// a variable holds a malformed value, or the result is invalid.
+    pub fn load(args: &CliArgs) -> Result<Self, GameYError> {
+        Self::load_with_env(args, &|name| std::env::var(name).ok())
+    }
+
+    /// Same as [`ServerConfig::load`], reading variables through `env`.
+    pub fn load_with_env(args: &CliArgs, env: EnvLookup<'_>) -> Result<Self, GameYError> {
+        let path = args
+            .config
+            .clone()
+            .or_else(|| env("GAMEY_CONFIG").map(PathBuf::from));
+        let mut config = match path {
+            Some(path) => Self::from_toml_file(&path)?,
+            None => Self::default(),
+        };
+        config.apply_env(env)?;
+        config.apply_args(args);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parses a TOML document. Missing keys keep their defaults.
+    pub fn from_toml_str(toml: &str) -> Result<Self, GameYError> {
+        toml::from_str(toml).map_err(|e| config_error(e.message()))
+    }
+
+    /// Reads and parses a TOML file.
+    pub fn from_toml_file(path: &Path) -> Result<Self, GameYError> {
+        let toml = std::fs::read_to_string(path)
+            .map_err(|e| config_error(format!("could not read {}: {}", path.display(), e)))?;
+        Self::from_toml_str(&toml)
+            .map_err(|e| config_error(format!("in {}: {}", path.display(), e)))
+    }
+
+    /// Overrides settings with the environment variables that are set.
+    ///
+    /// - `GAMEY_BIND_ADDRESS`, `GAMEY_PORT`, `GAMEY_STORAGE_DIR`
+    /// - `GAMEY_BOTS` (comma-separated)
+    /// - `GAMEY_MAX_CONCURRENT_BOT_MOVES`
+    /// - `STATS_SERVICE_URL`, `STATS_INTERNAL_TOKEN`, `STATS_OUTBOX_DIR`
+    /// - `STATS_RETRY_INITIAL_BACKOFF_MS`, `STATS_RETRY_MAX_BACKOFF_SECS`
+    /// - `GAMEY_PLAYER_INACTIVITY_TIMEOUT_SECS`, `GAMEY_TURN_TIMEOUT_SECS`
+    /// - `GAMEY_EXHIBITION_MOVE_DELAY_MS`, `GAMEY_EXHIBITION_MAX_CONCURRENT`
+    /// - `GAMEY_MATCHMAKING_DEFAULT_BOARD_SIZE`, `GAMEY_MATCHMAKING_TICK_MS`
+    /// - the variables read by [`TicketExpiryPolicy::apply_env`] and
+    ///   [`RetentionPolicy::apply_env`]
+    pub fn apply_env(&mut self, env: EnvLookup<'_>) -> Result<(), GameYError> {
+        if let Some(bind_address) = env_value(env, "GAMEY_BIND_ADDRESS")? {
+            self.bind_address = bind_address;
+        }
+        if let Some(port) = env_value(env, "GAMEY_PORT")? {
+            self.port = port;
+        }
+        if let Some(storage_dir) = env("GAMEY_STORAGE_DIR") {
+            self.storage_dir = Some(PathBuf::from(storage_dir));
+        }
+        if let Some(bots) = env("GAMEY_BOTS") {
+            self.bots = split_list(&bots);
+        }
+        if let Some(max_moves) = env_value(env, "GAMEY_MAX_CONCURRENT_BOT_MOVES")? {
+            self.max_concurrent_bot_moves = max_moves;
+        }
+        if let Some(url) = env("STATS_SERVICE_URL") {
+            self.stats.url = url;
+        }
+        if let Some(internal_token) = env("STATS_INTERNAL_TOKEN") {
+            self.stats.internal_token = internal_token;
+        }
+        if let Some(outbox_dir) = env("STATS_OUTBOX_DIR") {
+            self.stats.outbox_dir = Some(PathBuf::from(outbox_dir));
+        }
+        if let Some(backoff_ms) = env_value(env, "STATS_RETRY_INITIAL_BACKOFF_MS")? {
+            self.stats.retry_initial_backoff = Duration::from_millis(backoff_ms);
+        }
+        if let Some(backoff) = env_secs(env, "STATS_RETRY_MAX_BACKOFF_SECS")? {
+            self.stats.retry_max_backoff = backoff;
+        }
+        if let Some(timeout) = env_secs(env, "GAMEY_PLAYER_INACTIVITY_TIMEOUT_SECS")? {
+            self.online.player_inactivity_timeout = timeout;
+        }
+        if let Some(timeout) = env_secs(env, "GAMEY_TURN_TIMEOUT_SECS")? {
+            self.online.turn_timeout = timeout;
+        }
+        if let Some(delay_ms) = env_value(env, "GAMEY_EXHIBITION_MOVE_DELAY_MS")? {
+            self.exhibition.move_delay = Duration::from_millis(delay_ms);
+        }
+        if let Some(max_exhibitions) = env_value(env, "GAMEY_EXHIBITION_MAX_CONCURRENT")? {
+            self.exhibition.max_concurrent = max_exhibitions;
+        }
+        if let Some(size) = env_value(env, "GAMEY_MATCHMAKING_DEFAULT_BOARD_SIZE")? {
+            self.matchmaking.default_board_size = size;
+        }
+        if let Some(tick_ms) = env_value(env, "GAMEY_MATCHMAKING_TICK_MS")? {
+            self.matchmaking.tick = Duration::from_millis(tick_ms);
+        }
+        self.matchmaking.tickets.apply_env(env)?;
+        self.retention.apply_env(env)?;
+        Ok(())
+    }
+
+    /// Overrides settings with the flags given on the command line.
+    pub fn apply_args(&mut self, args: &CliArgs) {
+        if let Some(port) = args.port {
+            self.port = port;
+        }
+        if let Some(bind_address) = args.bind_address {
+            self.bind_address = bind_address;
+        }
+        if let Some(storage_dir) = &args.storage_dir {
+            self.storage_dir = Some(storage_dir.clone());
+        }
+        if !args.server_bots.is_empty() {
+            self.bots = args.server_bots.clone();
+        }
+        if let Some(stats_url) = &args.stats_url {
+            self.stats.url = stats_url.clone();
+        }
+    }
+
+    /// Checks that every setting is usable.
+    ///
+    /// # Errors
+    /// Returns `GameYError::ConfigError` describing the first invalid setting.
+    pub fn validate(&self) -> Result<(), GameYError> {
+        if self.bots.is_empty() {
+            return Err(config_error("at least one bot must be enabled"));
+        }
+        let available = all_bots();
+        for (idx, bot) in self.bots.iter().enumerate() {
+            if available.find(bot).is_none() {
+                let mut names = available.names();
+                names.sort();
+                return Err(config_error(format!(
+                    "unknown bot '{}', available bots: [{}]",
+                    bot,
+                    names.join(", ")
+                )));
+            }
+            if self.bots[..idx].contains(bot) {
+                return Err(config_error(format!("bot '{}' is listed twice", bot)));
+            }
+        }
+
+        if self.max_concurrent_bot_moves == 0 {
+            return Err(config_error("max_concurrent_bot_moves must be >= 1"));
+        }
+        if self.exhibition.max_concurrent == 0 {
+            return Err(config_error("exhibition max_concurrent must be >= 1"));
+        }
+
+        if !(self.stats.url.starts_with("http://") || self.stats.url.starts_with("https://")) {
+            return Err(config_error(format!(
+                "stats url must start with http:// or https://, got '{}'",
+                self.stats.url
+            )));
+        }
+        if self.stats.retry_max_backoff < self.stats.retry_initial_backoff {
+            return Err(config_error(
+                "stats retry_max_backoff_secs must not be shorter than retry_initial_backoff_ms",
+            ));
+        }
+        if self.matchmaking.default_board_size == 0 {
+            return Err(config_error("matchmaking default_board_size must be >= 1"));
+        }
+        if self.retention.max_sessions == 0 {
+            return Err(config_error("retention max_sessions must be >= 1"));
+        }
+
+        let positive_durations = [
+            (
+                "online player_inactivity_timeout_secs",
+                self.online.player_inactivity_timeout,
+            ),
+            (
+                "stats retry_initial_backoff_ms",
+                self.stats.retry_initial_backoff,
+            ),
+            ("online turn_timeout_secs", self.online.turn_timeout),
+            ("matchmaking tick_ms", self.matchmaking.tick),
+            (
+                "matchmaking tickets heartbeat_timeout_secs",
+                self.matchmaking.tickets.heartbeat_timeout,
+            ),
+            (
+                "retention sweep_interval_secs",
+                self.retention.sweep_interval,
+            ),
+        ];
+        if let Some((name, _)) = positive_durations
+            .iter()
+            .find(|(_, duration)| duration.is_zero())
+        {
+            return Err(config_error(format!("{} must be greater than zero", name)));
+        }
+
+        Ok(())
+    }
+
+    /// Returns a registry with the enabled bots.
+    ///
+    /// # Errors
+    /// Returns `GameYError::ConfigError` if a bot name is unknown.
+    pub fn bot_registry(&self) -> Result<YBotRegistry, GameYError> {
+        let available = all_bots();
+        self.bots
+            .iter()
+            .try_fold(YBotRegistry::new(), |registry, name| {
+                available
+                    .find(name)
+                    .map(|bot| registry.with_bot(bot))
+                    .ok_or_else(|| config_error(format!("unknown bot '{}'", name)))
+            })
+    }
+}
+
+/// Returns a registry with every bot the server can offer.
+pub fn all_bots() -> YBotRegistry {
+    YBotRegistry::new()
+        .with_bot(Arc::new(RandomBot))
+        .with_bot(Arc::new(BiasedRandomBot))
+        .with_bot(Arc::new(GreedyBot))
+        .with_bot(Arc::new(MinimaxBot::default()))
+        .with_bot(Arc::new(MctsBot::default()))
+}
+
+/// Parses the variable `name`, if set.
+pub(super) fn env_value<T>(env: EnvLookup<'_>, name: &str) -> Result<Option<T>, GameYError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    env(name)
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|e| config_error(format!("invalid {}='{}': {}", name, value, e)))
+        })
+        .transpose()
+}
+
+/// Parses the variable `name` as a number of seconds, if set.
+pub(super) fn env_secs(env: EnvLookup<'_>, name: &str) -> Result<Option<Duration>, GameYError> {
+    Ok(env_value::<u64>(env, name)?.map(Duration::from_secs))
+}
+
+pub(super) fn deserialize_secs<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_secs)
+}
+
+pub(super) fn deserialize_millis<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_millis)
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn config_error(message: impl Into<String>) -> GameYError {
+    GameYError::ConfigError {
+        message: message.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use std::collections::HashMap;
+
+    fn args(flags: &[&str]) -> CliArgs {
+        let mut argv = vec!["gamey", "--mode", "server"];
+        argv.extend_from_slice(flags);
+        CliArgs::try_parse_from(argv).unwrap()
+    }
+
+    fn load(flags: &[&str], vars: &[(&str, &str)]) -> Result<ServerConfig, GameYError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        ServerConfig::load_with_env(&args(flags), &|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        let config = load(&[], &[]).unwrap();
+
+        assert_eq!(config, ServerConfig::default());
+        assert_eq!(config.bind_address.to_string(), "0.0.0.0");
+        assert_eq!(config.bots.len(), 5);
+    }
+
+    #[test]
+    fn test_toml_keeps_defaults_for_missing_keys() {
+        let config = ServerConfig::from_toml_str(
+            r#"
+            bots = ["greedy_bot"]
+
+            [online]
+            turn_timeout_secs = 30
+
+            [matchmaking.tickets]
+            heartbeat_timeout_secs = 5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.bots, vec!["greedy_bot"]);
+        assert_eq!(config.online.turn_timeout, Duration::from_secs(30));
+        assert_eq!(
+            config.online.player_inactivity_timeout,
+            DEFAULT_PLAYER_INACTIVITY_TIMEOUT
+        );
+        assert_eq!(
+            config.matchmaking.tickets.heartbeat_timeout,
+            Duration::from_secs(5)
+        );
+        assert_eq!(config.exhibition, ExhibitionConfig::default());
+        assert_eq!(config.retention, RetentionPolicy::default());
+    }
+
+    #[test]
+    fn test_toml_rejects_unknown_keys() {
+        let result = ServerConfig::from_toml_str("[online]\nturn_timeout = 30\n");
+
+        assert!(matches!(result, Err(GameYError::ConfigError { .. })));
+    }
+
+    #[test]
+    fn test_file_env_and_flags_are_layered_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gamey.toml");
+        std::fs::write(
+            &path,
+            "bind_address = \"127.0.0.1\"\n[stats]\nurl = \"http://file:1\"\n[matchmaking]\ntick_ms = 100\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        let config = load(
+            &[
+                "--config",
+                path,
+                "--stats-url",
+                "http://flag:3",
+                "-p",
+                "4000",
+            ],
+            &[
+                ("STATS_SERVICE_URL", "http://env:2"),
+                ("GAMEY_MATCHMAKING_TICK_MS", "50"),
+                ("GAMEY_BOTS", "random_bot, mcts_bot"),
+                ("GAMEY_EXHIBITION_MOVE_DELAY_MS", "250"),
+                ("GAMEY_EXHIBITION_MAX_CONCURRENT", "2"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.bind_address.to_string(), "127.0.0.1");
+        assert_eq!(config.matchmaking.tick, Duration::from_millis(50));
+        assert_eq!(config.stats.url, "http://flag:3");
+        assert_eq!(config.bots, vec!["random_bot", "mcts_bot"]);
+        assert_eq!(config.exhibition.move_delay, Duration::from_millis(250));
+        assert_eq!(config.exhibition.max_concurrent, 2);
+        assert_eq!(config.port, 4000);
+    }
+
+    #[test]
+    fn test_port_is_read_from_file_env_and_flags() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("gamey.toml");
+        std::fs::write(&path, "port = 4100\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        assert_eq!(load(&[], &[]).unwrap().port, DEFAULT_PORT);
+        assert_eq!(load(&["--config", path], &[]).unwrap().port, 4100);
+        assert_eq!(
+            load(&["--config", path], &[("GAMEY_PORT", "4200")])
+                .unwrap()
+                .port,
+            4200
+        );
+        assert_eq!(
+            load(
+                &["--config", path, "--port", "4300"],
+                &[("GAMEY_PORT", "4200")]
+            )
+            .unwrap()
+            .port,
+            4300
+        );
+    }
+
+    #[test]
+    fn test_malformed_env_value_is_an_error() {
+        let result = load(&[], &[("GAMEY_TURN_TIMEOUT_SECS", "soon")]);
+
+        let Err(GameYError::ConfigError { message }) = result else {
+            panic!("expected a config error");
+        };
+        assert!(message.contains("GAMEY_TURN_TIMEOUT_SECS"));
+    }
+
+    #[test]
+    fn test_validation_rejects_unknown_bots_and_zero_timeouts() {
+        assert!(load(&["--server-bots", "random_bot,chess_bot"], &[]).is_err());
+        assert!(load(&[], &[("GAMEY_BOTS", "random_bot,random_bot")]).is_err());
+        assert!(load(&[], &[("GAMEY_PLAYER_INACTIVITY_TIMEOUT_SECS", "0")]).is_err());
+        assert!(load(&[], &[("GAMEY_MAX_CONCURRENT_BOT_MOVES", "0")]).is_err());
+        assert!(load(&[], &[("GAMEY_EXHIBITION_MAX_CONCURRENT", "0")]).is_err());
+        assert!(load(&[], &[("STATS_SERVICE_URL", "stats:3001")]).is_err());
+        assert!(
+            load(
+                &[],
+                &[
+                    ("STATS_RETRY_INITIAL_BACKOFF_MS", "10000"),
+                    ("STATS_RETRY_MAX_BACKOFF_SECS", "5"),
+                ],
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_bot_registry_contains_only_enabled_bots() {
+        let config = load(&["--server-bots", "greedy_bot"], &[]).unwrap();
+
+        let registry = config.bot_registry().unwrap();
+
+        assert_eq!(registry.names(), vec!["greedy_bot"]);
+    }
+}
@@ -0,0 +1,105 @@
+//! Change notifications behind the server-sent event streams.
+//!
+//! Handlers call [`EventHub::notify`] after they change a game or a
+//! matchmaking ticket. Each open stream holds a receiver from
+//! [`EventHub::subscribe`] and, when woken, reads the current state and sends
+//! it to its client. Notifications carry no payload: several changes in a row
+//! collapse into one wake-up, and the client always gets the latest state.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use tokio::sync::watch;
+
+/// Change notification channels indexed by game or ticket id.
+#[derive(Debug, Default)]
+pub struct EventHub {
+    channels: Mutex<HashMap<String, watch::Sender<u64>>>,
+}
+
+impl EventHub {
+    /// Creates a hub without channels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a receiver that is marked as changed every time `key` is
+    /// notified.
+    pub fn subscribe(&self, key: &str) -> watch::Receiver<u64> {
+        self.channels()
+            .entry(key.to_string())
+            .or_insert_with(|| watch::channel(0).0)
+            .subscribe()
+    }
+
+    /// Wakes every stream subscribed to `key`.
+    ///
+    /// Channels whose streams have all been closed are dropped here.
+    pub fn notify(&self, key: &str) {
+        let mut channels = self.channels();
+        let Some(sender) = channels.get(key) else {
+            return;
+        };
+
+        if sender.receiver_count() == 0 {
+            channels.remove(key);
+        } else {
+            sender.send_modify(|version| *version = version.wrapping_add(1));
+        }
+    }
+
+    /// Drops the channel of `key`, which ends the streams subscribed to it.
+    pub fn close(&self, key: &str) {
+        self.channels().remove(key);
+    }
+
+    /// Returns the number of open streams subscribed to `key`.
+    pub fn subscriber_count(&self, key: &str) -> usize {
+        self.channels()
+            .get(key)
+            .map_or(0, watch::Sender::receiver_count)
+    }
+
+    fn channels(&self) -> MutexGuard<'_, HashMap<String, watch::Sender<u64>>> {
+        self.channels.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_notify_wakes_subscribers_of_the_same_key_only() {
+        let hub = EventHub::new();
+        let mut game = hub.subscribe("game-1");
+        let other = hub.subscribe("game-2");
+
+        hub.notify("game-1");
+
+        assert!(game.has_changed().unwrap());
+        game.changed().await.unwrap();
+        assert!(!other.has_changed().unwrap());
+    }
+
+    #[test]
+    fn test_notify_drops_channels_without_subscribers() {
+        let hub = EventHub::new();
+        let receiver = hub.subscribe("game-1");
+        assert_eq!(hub.subscriber_count("game-1"), 1);
+
+        drop(receiver);
+        hub.notify("game-1");
+
+        assert!(hub.channels().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_close_ends_subscribed_streams() {
+        let hub = EventHub::new();
+        let mut receiver = hub.subscribe("ticket-1");
+
+        hub.close("ticket-1");
+
+        assert!(receiver.changed().await.is_err());
+    }
+}
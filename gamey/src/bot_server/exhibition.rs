@@ -0,0 +1,143 @@
+//! Bot-vs-bot exhibition games.
+//!
+//! A `bot_vs_bot` game has no players sending moves. Once it is created, or
+//! restored after a restart, [`start_exhibition`] plays it in the background:
+//! it waits `exhibition.move_delay`, asks the bot whose turn it is for a move on
+//! the bot worker pool and applies it, until the game is over. Clients follow the
+//! game through the usual game state, event and spectator endpoints.
+//!
+//! Each running exhibition holds an [`ExhibitionSlot`], and no more than
+//! `exhibition.max_concurrent` slots are handed out to new games.
+
+use super::{
+    choose::BotTimeLimitQuery,
+    games::{GameParams, play_bot_turn, prepare_bot_turn},
+    state::AppState,
+};
+use crate::PlayerId;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+use tracing::warn;
+
+/// Number of exhibitions being played.
+#[derive(Debug, Default)]
+pub struct ExhibitionSlots {
+    running: AtomicUsize,
+}
+
+impl ExhibitionSlots {
+    /// Creates a counter without running exhibitions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of exhibitions being played.
+    pub fn running(&self) -> usize {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Takes a slot if fewer than `max` exhibitions are running.
+    pub fn try_take(self: &Arc<Self>, max: usize) -> Option<ExhibitionSlot> {
+        self.running
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+                (running < max).then_some(running + 1)
+            })
+            .ok()?;
+        Some(ExhibitionSlot(Arc::clone(self)))
+    }
+
+    /// Takes a slot regardless of the limit, for exhibitions that were
+    /// already running before a restart.
+    pub fn take(self: &Arc<Self>) -> ExhibitionSlot {
+        self.running.fetch_add(1, Ordering::SeqCst);
+        ExhibitionSlot(Arc::clone(self))
+    }
+}
+
+/// Counts one exhibition as running until it is dropped.
+#[derive(Debug)]
+pub struct ExhibitionSlot(Arc<ExhibitionSlots>);
+
+impl Drop for ExhibitionSlot {
+    fn drop(&mut self) {
+        self.0.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Spawns the task that plays the exhibition `game_id` to the end, holding
+/// `slot` until it stops.
+///
+/// The task stops early if the game is evicted or a bot fails to move, in
+/// which case that bot forfeits the game.
+pub fn start_exhibition(
+    state: AppState,
+    api_version: String,
+    game_id: String,
+    slot: ExhibitionSlot,
+) {
+    tokio::spawn(async move {
+        let _slot = slot;
+        let params = GameParams {
+            api_version,
+            game_id,
+        };
+        let move_delay = state.config().exhibition.move_delay;
+        loop {
+            tokio::time::sleep(move_delay).await;
+
+            let bot_turn = {
+                let Some(mut session) = state.lock_game(&params.game_id).await else {
+                    return;
+                };
+                match prepare_bot_turn(&state, &mut session, &params.api_version) {
+                    Ok(Some(bot_turn)) => bot_turn,
+                    Ok(None) => return,
+                    Err(error) => {
+                        warn!("Stopping exhibition {}: {}", params.game_id, error.message);
+                        return;
+                    }
+                }
+            };
+
+            if let Err(error) = play_bot_turn(
+                &state,
+                &params,
+                bot_turn,
+                &BotTimeLimitQuery::default(),
+                PlayerId::new(0),
+                "Could not apply exhibition move",
+            )
+            .await
+            {
+                warn!("Stopping exhibition {}: {}", params.game_id, error.message);
+                return;
+            }
+            state.metrics().inc_moves_played();
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slots_are_capped_and_freed_on_drop() {
+        let slots = Arc::new(ExhibitionSlots::new());
+
+        let first = slots.try_take(2).expect("a slot should be free");
+        let second = slots.try_take(2).expect("a slot should be free");
+        assert!(slots.try_take(2).is_none());
+        let resumed = slots.take();
+        assert_eq!(slots.running(), 3);
+
+        drop(first);
+        drop(resumed);
+        assert_eq!(slots.running(), 1);
+        assert!(slots.try_take(2).is_some());
+        drop(second);
+        assert_eq!(slots.running(), 0);
+    }
+}
@@ -1,24 +1,39 @@
 use super::{
+    bot_workers::choose_bot_move,
+    choose::BotTimeLimitQuery,
+    clock::{ClockState, GameClock, TimeControl},
     error::ErrorResponse,
-    state::{AppState, GameCompletionReason, GameSession},
-    version::check_api_version,
+    exhibition::{ExhibitionSlot, start_exhibition},
+    spectators::SpectatorSeat,
+    state::{AppState, BotTurnStatus, GameCompletionReason, GameSession},
+    stats_outbox::{FinishedMatchPlayer, FinishedMatchRequest},
+    version::{SUPPORTED_VERSION, check_api_version},
 };
-use crate::{Coordinates, GameAction, GameStatus, GameY, Movement, PlayerId, YEN};
+use crate::{Coordinates, GameAction, GameStatus, GameY, Movement, PlayerId, YBot, YEN};
 use axum::{
     Json,
     extract::{Path, State, Query},
     http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
 };
+use futures_util::{Stream, stream};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
-    time::{Duration, Instant},
+    convert::Infallible,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
+use subtle::ConstantTimeEq;
+use tokio::sync::{OwnedMutexGuard, watch};
 use tracing::warn;
 
-const ONLINE_PLAYER_INACTIVITY_TIMEOUT: Duration = Duration::from_secs(60);
-const ONLINE_TURN_TIMEOUT: Duration = Duration::from_secs(60);
 const ONLINE_GAME_TIMEOUT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+/// How long a bot move that failed waits before it is tried again.
+const BOT_TURN_RETRY_DELAY: Duration = Duration::from_secs(5);
+/// How often an open event stream counts as presence for its player, well
+/// inside the inactivity timeout.
+const GAME_EVENTS_PRESENCE_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Supported game modes for the HTTP API.
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -27,19 +42,55 @@ pub enum GameMode {
     HumanVsHuman,
     #[default]
     HumanVsBot,
+    BotVsBot,
+}
+
+/// Side the human plays in a human-vs-bot game.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorChoice {
+    /// The human is player 0 and moves first.
+    #[default]
+    First,
+    /// The bot is player 0 and opens the game.
+    Second,
+    /// The side is drawn at random when the game is created.
+    Random,
 }
 
 /// Request payload for creating a new game.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CreateGameRequest {
-    /// Board size (triangle side length). Must be >= 1.
-    #[serde(default = "default_board_size")]
-    pub size: u32,
+    /// Board size (triangle side length). Must be >= 1. Defaults to 7, or to
+    /// the size of `initial_position`, which it must match when both are given.
+    #[serde(default)]
+    pub size: Option<u32>,
+    /// Optional position to start from instead of the empty board. The side
+    /// to move is taken from its `turn`.
+    #[serde(default)]
+    pub initial_position: Option<YEN>,
+    /// Optional placements played from the starting position before the game
+    /// is handed to the players, alternating sides. They cannot be undone.
+    #[serde(default)]
+    pub moves: Option<Vec<Coordinates>>,
     /// Game mode. Defaults to human_vs_bot.
     #[serde(default)]
     pub mode: GameMode,
-    /// Optional bot identifier. Used only in human_vs_bot mode.
+    /// Optional bot identifier. Used in human_vs_bot mode, and required in
+    /// bot_vs_bot mode for the bot moving first.
     pub bot_id: Option<String>,
+    /// Bot moving second. Required in bot_vs_bot mode, not allowed otherwise.
+    #[serde(default)]
+    pub opponent_bot_id: Option<String>,
+    /// Side the human plays. Used only in human_vs_bot mode, defaults to first.
+    #[serde(default)]
+    pub color: Option<ColorChoice>,
+    /// Whether the game may be watched by spectators. Defaults to true.
+    #[serde(default = "default_allow_spectators")]
+    pub allow_spectators: bool,
+    /// Optional per-player clock. Used only in human_vs_human mode.
+    #[serde(default)]
+    pub time_control: Option<TimeControl>,
 }
 
 /// Request payload for placing a move.
@@ -60,10 +111,20 @@ pub struct GameStateResponse {
     pub game_id: String,
     /// Current game mode.
     pub mode: GameMode,
-    /// Bot used for human-vs-bot games.
+    /// Bot used for human-vs-bot games, or the bot moving first in
+    /// bot-vs-bot games.
     pub bot_id: Option<String>,
+    /// Bot moving second in bot-vs-bot games.
+    #[serde(default)]
+    pub opponent_bot_id: Option<String>,
+    /// Player id controlled by the human in human-vs-bot games.
+    #[serde(default)]
+    pub human_player: Option<u32>,
     /// Current board state in YEN format.
     pub yen: YEN,
+    /// Position the game started from, when it was not the empty board.
+    #[serde(default)]
+    pub start_position: Option<YEN>,
     /// Whether the game has finished.
     pub game_over: bool,
     /// Next player id if game is ongoing.
@@ -80,17 +141,23 @@ pub struct GameStateResponse {
     pub opponent_inactivity_timeout_remaining_ms: Option<u64>,
     /// Remaining time before the current online turn is automatically passed.
     pub turn_timeout_remaining_ms: Option<u64>,
+    /// Number of spectators currently watching the game.
+    #[serde(default)]
+    pub spectator_count: usize,
+    /// Clock readings for games played with a time control.
+    #[serde(default)]
+    pub clock: Option<ClockState>,
 }
 
 #[derive(Deserialize)]
 pub struct ApiVersionParams {
-    api_version: String,
+    pub(super) api_version: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct GameParams {
-    api_version: String,
-    game_id: String,
+    pub(super) api_version: String,
+    pub(super) game_id: String,
 }
 
 /// Query parameters for GET /games endpoint.
@@ -112,25 +179,6 @@ pub fn start_inactive_online_game_monitor(state: AppState) {
     });
 }
 
-#[derive(Serialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-struct FinishedMatchPlayer {
-    user_id: String,
-    result: String,
-}
-
-#[derive(Serialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-struct FinishedMatchRequest {
-    game_id: String,
-    mode: Option<String>,
-    bot_id: Option<String>,
-    reason: Option<String>,
-    winner_id: Option<String>,
-    final_board: Option<YEN>,
-    players: Vec<FinishedMatchPlayer>,
-}
-
 /// Creates a new game and stores it in server memory.
 ///
 /// # Route
@@ -138,8 +186,22 @@ struct FinishedMatchRequest {
 ///
 /// # Request body
 /// - `size`: board size
-/// - `mode`: `human_vs_human` or `human_vs_bot`
-/// - `bot_id`: optional bot id (human_vs_bot only, defaults to `random_bot`)
+/// - `initial_position`: optional YEN to start from instead of the empty board
+/// - `moves`: optional placements played from the starting position before the
+///   game begins
+/// - `mode`: `human_vs_human`, `human_vs_bot` or `bot_vs_bot`
+/// - `bot_id`: bot id (defaults to `random_bot` in human_vs_bot, required in
+///   bot_vs_bot for the bot moving first)
+/// - `opponent_bot_id`: bot moving second (bot_vs_bot only, required)
+/// - `color`: `first`, `second` or `random` (human_vs_bot only, defaults to
+///   `first`); when the bot moves first it plays its opening move before the
+///   response is sent
+/// - `time_control`: optional Fischer or byo-yomi clock (human_vs_human only)
+///
+/// A bot_vs_bot game is played by the server in the background, see
+/// [`start_exhibition`]; the response shows the empty board. At most
+/// `exhibition.max_concurrent` of them run at once: past that the request
+/// fails with `429 Too Many Requests`.
 pub async fn create_game(
     State(state): State<AppState>,
     Path(params): Path<ApiVersionParams>,
@@ -148,7 +210,7 @@ pub async fn create_game(
 ) -> Result<Json<GameStateResponse>, ErrorResponse> {
     check_api_version(&params.api_version)?;
 
-    if request.size == 0 {
+    if request.size == Some(0) {
         return Err(error_response(
             "Board size must be >= 1",
             Some(params.api_version),
@@ -156,8 +218,39 @@ pub async fn create_game(
     }
 
     let bot_id = resolve_bot_id(&state, request.mode, request.bot_id, &params.api_version)?;
-    let player0_user_id = read_header_string(&headers, "x-user-id");
-    let player1_user_id = read_header_string(&headers, "x-opponent-user-id");
+    let opponent_bot_id = resolve_opponent_bot_id(
+        &state,
+        request.mode,
+        request.opponent_bot_id,
+        &params.api_version,
+    )?;
+    let human_player = resolve_human_player(request.mode, request.color, &params.api_version)?;
+    let time_control =
+        resolve_time_control(request.mode, request.time_control, &params.api_version)?;
+    let exhibition_slot = match request.mode {
+        GameMode::BotVsBot => Some(take_exhibition_slot(&state, &params.api_version)?),
+        GameMode::HumanVsHuman | GameMode::HumanVsBot => None,
+    };
+    // Nobody plays an exhibition, so it must not count as anyone's active game.
+    let (user_id, opponent_user_id) = match request.mode {
+        GameMode::BotVsBot => (None, None),
+        GameMode::HumanVsHuman | GameMode::HumanVsBot => (
+            read_header_string(&headers, "x-user-id"),
+            read_header_string(&headers, "x-opponent-user-id"),
+        ),
+    };
+    let (player0_user_id, player1_user_id) = if human_player == Some(PlayerId::new(1)) {
+        (opponent_user_id, user_id)
+    } else {
+        (user_id, opponent_user_id)
+    };
+    // The bot takes the seat the human does not play; in an exhibition the
+    // opponent bot moves second.
+    let bots = match human_player {
+        Some(human_player) if human_player == PlayerId::new(0) => [None, bot_id],
+        Some(_) => [bot_id, None],
+        None => [bot_id, opponent_bot_id],
+    };
 
     ensure_user_id_is_available_for_new_game(
         &state,
@@ -172,9 +265,17 @@ pub async fn create_game(
     )
     .await?;
 
-    let session = GameSession {
-        game: GameY::new(request.size),
-        bot_id: bot_id.clone(),
+    let game = build_starting_game(
+        request.size,
+        request.initial_position,
+        request.moves,
+        &params.api_version,
+    )?;
+    let clock = time_control
+        .map(|time_control| GameClock::new(time_control, game.next_player(), Instant::now()));
+    let mut session = GameSession {
+        game,
+        bots,
         created_at: Instant::now(),
         turn_started_at: None,
         player_tokens: None,
@@ -183,21 +284,57 @@ pub async fn create_game(
         player1_user_id,
         stats_reported: false,
         completion_reason: None,
+        last_activity_at: Instant::now(),
+        finished_at: None,
+        allow_spectators: request.allow_spectators,
+        clock,
+        bot_turn: BotTurnStatus::Idle,
     };
 
     let game_id = state.new_game_id();
-    let response = build_game_state_response(&params.api_version, &game_id, &session, None);
+    let response = build_game_state_response(&params.api_version, &game_id, &session, None, &state);
+    let bot_turn = match request.mode {
+        GameMode::BotVsBot => None,
+        GameMode::HumanVsHuman | GameMode::HumanVsBot => {
+            prepare_bot_turn(&state, &mut session, &params.api_version)?
+        }
+    };
 
-    let games = state.games();
-    games.write().await.insert(game_id.clone(), session.clone());
+    state.insert_game(game_id.clone(), session.clone()).await;
+    state.persist_game(&game_id, &session);
     register_active_game_for_session_users(&state, &game_id, &session).await;
     state.metrics().inc_games_created();
 
+    if let Some(slot) = exhibition_slot {
+        start_exhibition(state, params.api_version, game_id, slot);
+        return Ok(Json(response));
+    }
+    let (Some(bot_turn), Some(human_player)) = (bot_turn, human_player) else {
+        return Ok(Json(response));
+    };
+    let params = GameParams {
+        api_version: params.api_version,
+        game_id,
+    };
+    let response = play_bot_turn(
+        &state,
+        &params,
+        bot_turn,
+        &BotTimeLimitQuery::default(),
+        human_player,
+        "Could not apply bot opening move",
+    )
+    .await?;
+
     Ok(Json(response))
 }
 
 /// Returns the current state of a game.
 ///
+/// A `human_vs_bot` game still owed a bot move that nothing is computing, for
+/// instance after a restart or a failed attempt, has the bot start thinking
+/// again.
+///
 /// # Route
 /// `GET /{api_version}/games/{game_id}`
 pub async fn get_game(
@@ -209,77 +346,77 @@ pub async fn get_game(
     check_api_version(&params.api_version)?;
 
     // Check if request includes action=resign to handle resignation via GET.
-    if let Some(action) = query.action.as_deref() {
-        if action == "resign" {
-            let games = state.games();
-            let mut guard = games.write().await;
-
-            let pending_report: Option<FinishedMatchRequest>;
-            let user_ids_to_release_from_active_game_index: Option<Vec<String>>;
-
-            let response = {
-                let session = require_game_session_mut(&mut guard, &params)?;
-                ensure_game_not_finished(&session.game, &params.api_version)?;
-
-                let resigning_player = match &session.player_tokens {
-                    Some(_) => resolve_player_from_header_token(session, &headers, &params.api_version)?,
-                    None => match (&session.bot_id, session.game.next_player()) {
-                        (Some(_), _) => PlayerId::new(0),
-                        (None, Some(player)) => player,
-                        (None, None) => return Err(game_finished_error(&params.api_version)),
-                    },
-                };
-                record_online_player_presence(session, resigning_player);
-                session.completion_reason = Some(GameCompletionReason::Resignation);
-
-                session
-                    .game
-                    .add_move(Movement::Action {
-                        player: resigning_player,
-                        action: GameAction::Resign,
-                    })
-                    .map_err(|e| {
-                        error_response(
-                            &format!("Could not resign game: {}", e),
-                            Some(params.api_version.clone()),
-                        )
-                    })?;
-
-                reset_turn_timer(session);
+    if query.action.as_deref() == Some("resign") {
+        let mut guard = require_game_session(&state, &params).await?;
+
+        let pending_report: Option<FinishedMatchRequest>;
+        let user_ids_to_release_from_active_game_index: Option<Vec<String>>;
+
+        let response = {
+            let session = &mut *guard;
+            ensure_game_not_finished(&session.game, &params.api_version)?;
+            ensure_players_can_act(session, &params.api_version)?;
+
+            let resigning_player = match &session.player_tokens {
+                Some(_) => resolve_player_from_header_token(session, &headers, &params.api_version)?,
+                None => match (session.human_player(), session.game.next_player()) {
+                    (Some(human_player), _) => human_player,
+                    (None, Some(player)) => player,
+                    (None, None) => return Err(game_finished_error(&params.api_version)),
+                },
+            };
+            record_online_player_presence(session, resigning_player);
+            session.completion_reason = Some(GameCompletionReason::Resignation);
+
+            session
+                .game
+                .add_move(Movement::Action {
+                    player: resigning_player,
+                    action: GameAction::Resign,
+                })
+                .map_err(|e| {
+                    error_response(
+                        &format!("Could not resign game: {}", e),
+                        Some(params.api_version.clone()),
+                    )
+                })?;
 
-                pending_report = prepare_stats_report_if_needed(&params.game_id, session);
-                user_ids_to_release_from_active_game_index = build_finished_game_user_id_list(session);
+            reset_turn_timer(session);
 
-                build_game_state_response(
-                    &params.api_version,
-                    &params.game_id,
-                    session,
-                    Some(resigning_player),
-                )
-            };
+            pending_report = prepare_stats_report_if_needed(&params.game_id, session);
+            user_ids_to_release_from_active_game_index = build_finished_game_user_id_list(session);
+            commit_session_update(&state, &params.game_id, session);
 
-            drop(guard);
-            clear_active_game_registration_if_needed(
-                &state,
+            build_game_state_response(
+                &params.api_version,
                 &params.game_id,
-                user_ids_to_release_from_active_game_index,
+                session,
+                Some(resigning_player),
+                &state,
             )
-            .await;
-            state.metrics().inc_resignations();
-            report_finished_match_if_needed(&state, pending_report).await;
+        };
 
-            return Ok(Json(response));
-        }
+        drop(guard);
+        clear_active_game_registration_if_needed(
+            &state,
+            &params.game_id,
+            user_ids_to_release_from_active_game_index,
+        )
+        .await;
+        state.metrics().inc_resignations();
+        queue_stats_report(&state, pending_report);
+
+        return Ok(Json(response));
     }
 
-    let games = state.games();
-    let mut guard = games.write().await;
-    let session = require_game_session_mut(&mut guard, &params)?;
+    let mut guard = require_game_session(&state, &params).await?;
+    let session = &mut *guard;
     let requesting_player_id = find_player_id_from_header_token(session, &headers);
 
     if let Some(requesting_player_id) = requesting_player_id {
         record_online_player_presence(session, requesting_player_id);
     }
+    resume_bot_turn_if_needed(&state, &params, session, Instant::now());
 
     // Existing GET logic continues here.
     Ok(Json(build_game_state_response(
@@ -287,30 +424,163 @@ pub async fn get_game(
         &params.game_id,
         session,
         requesting_player_id,
+        &state,
     )))
 }
 
+/// Streams the state of a game as server-sent events.
+///
+/// A `state` event with the [`GameStateResponse`] is sent when the stream
+/// opens and after every move, pass, resignation, undo or timeout. The stream
+/// ends after the event that reports the game as finished.
+///
+/// In online games the `x-player-token` header is required. While the stream
+/// is open it counts as presence for that player, so the client does not need
+/// to poll to avoid the inactivity forfeit.
+///
+/// # Route
+/// `GET /{api_version}/games/{game_id}/events`
+pub async fn game_events(
+    State(state): State<AppState>,
+    Path(params): Path<GameParams>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+
+    let (player_id, updates) = {
+        let mut guard = require_game_session(&state, &params).await?;
+        let session = &mut *guard;
+        let player_id = match &session.player_tokens {
+            Some(tokens) if !tokens.is_empty() => Some(resolve_player_from_header_token(
+                session,
+                &headers,
+                &params.api_version,
+            )?),
+            _ => None,
+        };
+        if let Some(player_id) = player_id {
+            record_online_player_presence(session, player_id);
+        }
+        // Subscribing under the lock means no update between this point and
+        // the first event can be missed.
+        (player_id, state.game_events().subscribe(&params.game_id))
+    };
+
+    Ok(GameEventStream::new(state, params, player_id, updates, None).into_sse())
+}
+
+/// State of one open game event stream.
+pub(super) struct GameEventStream {
+    state: AppState,
+    params: GameParams,
+    /// Player the stream belongs to, `None` for local games and spectators.
+    player_id: Option<PlayerId>,
+    updates: watch::Receiver<u64>,
+    presence: tokio::time::Interval,
+    /// Keeps the spectator counted while the stream is open.
+    _spectator_seat: Option<SpectatorSeat>,
+    started: bool,
+    finished: bool,
+}
+
+impl GameEventStream {
+    pub(super) fn new(
+        state: AppState,
+        params: GameParams,
+        player_id: Option<PlayerId>,
+        updates: watch::Receiver<u64>,
+        spectator_seat: Option<SpectatorSeat>,
+    ) -> Self {
+        let presence = tokio::time::interval_at(
+            tokio::time::Instant::now() + GAME_EVENTS_PRESENCE_INTERVAL,
+            GAME_EVENTS_PRESENCE_INTERVAL,
+        );
+        Self {
+            state,
+            params,
+            player_id,
+            updates,
+            presence,
+            _spectator_seat: spectator_seat,
+            started: false,
+            finished: false,
+        }
+    }
+
+    /// Turns the stream into a server-sent event response.
+    pub(super) fn into_sse(self) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+        Sse::new(stream::unfold(self, next_game_event)).keep_alive(KeepAlive::default())
+    }
+}
+
+async fn next_game_event(
+    mut events: GameEventStream,
+) -> Option<(Result<Event, Infallible>, GameEventStream)> {
+    if events.finished {
+        return None;
+    }
+
+    if events.started {
+        loop {
+            tokio::select! {
+                changed = events.updates.changed() => {
+                    changed.ok()?;
+                    break;
+                }
+                _ = events.presence.tick() => {
+                    if let Some(player_id) = events.player_id {
+                        let mut session = events.state.lock_game(&events.params.game_id).await?;
+                        record_online_player_presence(&mut session, player_id);
+                    }
+                }
+            }
+        }
+    }
+    events.started = true;
+
+    let response = {
+        let session = events.state.lock_game(&events.params.game_id).await?;
+        build_game_state_response(
+            &events.params.api_version,
+            &events.params.game_id,
+            &session,
+            events.player_id,
+            &events.state,
+        )
+    };
+    events.finished = response.game_over;
+
+    let event = Event::default().event("state").json_data(&response).ok()?;
+    Some((Ok(event), events))
+}
+
 /// Applies a human move and, in bot mode, immediately applies the bot move.
 ///
+/// The human move is committed before the bot starts thinking, and the bot
+/// move is computed on the bot worker pool without holding the game lock.
+/// The optional `time_limit_ms` query parameter bounds the bot's thinking time.
+///
 /// # Route
 /// `POST /{api_version}/games/{game_id}/moves`
 pub async fn play_move(
     State(state): State<AppState>,
     Path(params): Path<GameParams>,
+    Query(time_limit): Query<BotTimeLimitQuery>,
     Json(request): Json<MoveRequest>,
 ) -> Result<Json<GameStateResponse>, ErrorResponse> {
     check_api_version(&params.api_version)?;
 
-    let bots = state.bots();
-    let games = state.games();
-    let mut guard = games.write().await;
+    let mut guard = require_game_session(&state, &params).await?;
 
     let pending_report: Option<FinishedMatchRequest>;
     let user_ids_to_release_from_active_game_index: Option<Vec<String>>;
+    let bot_turn: Option<BotTurn>;
+    let current_player: PlayerId;
 
     let response = {
-        let session = require_game_session_mut(&mut guard, &params)?;
+        let session = &mut *guard;
         ensure_game_not_finished(&session.game, &params.api_version)?;
+        ensure_players_can_act(session, &params.api_version)?;
 
         validate_coordinates(&request.coords, session.game.board_size()).map_err(|msg| {
             error_response(
@@ -319,18 +589,19 @@ pub async fn play_move(
             )
         })?;
 
-        let current_player = current_player_or_finished(&session.game, &params.api_version)?;
+        current_player = current_player_or_finished(&session.game, &params.api_version)?;
         validate_player_token_for_turn(
             session,
             current_player,
             request.player_token.as_deref(),
             &params.api_version,
         )?;
+        ensure_time_left(session, &params.api_version)?;
         record_online_player_presence(session, current_player);
 
-        if session.bot_id.is_some() && current_player != PlayerId::new(0) {
+        if session.bot_for(current_player).is_some() {
             return Err(error_response(
-                "Human moves are only allowed on player 0 turn in human_vs_bot mode",
+                "Human moves are only allowed on the human's turn in human_vs_bot mode",
                 Some(params.api_version.clone()),
             ));
         }
@@ -352,57 +623,19 @@ pub async fn play_move(
                 response
             })?;
 
-        if let Some(bot_id) = &session.bot_id
-            && !session.game.check_game_over()
-        {
-            let bot = match bots.find(bot_id) {
-                Some(bot) => bot,
-                None => {
-                    let available_bots = bots.names().join(", ");
-                    return Err(bot_not_found_error(
-                        &params.api_version,
-                        bot_id,
-                        &available_bots,
-                    ));
-                }
-            };
-
-            let bot_coords = match bot.choose_move(&session.game) {
-                Some(coords) => coords,
-                None => {
-                    return Err(error_response(
-                        "No valid moves available for the bot",
-                        Some(params.api_version.clone()),
-                    ));
-                }
-            };
-
-            if let Some(bot_player) = session.game.next_player() {
-                session
-                    .game
-                    .add_move(Movement::Placement {
-                        player: bot_player,
-                        coords: bot_coords,
-                    })
-                    .map_err(|e| {
-                        error_response(
-                            &format!("Could not apply bot move: {}", e),
-                            Some(params.api_version.clone()),
-                        )
-                    })?;
-            }
-        }
-
+        bot_turn = prepare_bot_turn(&state, session, &params.api_version)?;
         reset_turn_timer(session);
 
         pending_report = prepare_stats_report_if_needed(&params.game_id, session);
         user_ids_to_release_from_active_game_index = build_finished_game_user_id_list(session);
+        commit_session_update(&state, &params.game_id, session);
 
         build_game_state_response(
             &params.api_version,
             &params.game_id,
             session,
             Some(current_player),
+            &state,
         )
     };
 
@@ -414,14 +647,27 @@ pub async fn play_move(
     )
     .await;
     state.metrics().inc_moves_played();
-    report_finished_match_if_needed(&state, pending_report).await;
+    queue_stats_report(&state, pending_report);
+
+    let Some(bot_turn) = bot_turn else {
+        return Ok(Json(response));
+    };
+    let response = play_bot_turn(
+        &state,
+        &params,
+        bot_turn,
+        &time_limit,
+        current_player,
+        "Could not apply bot move",
+    )
+    .await?;
 
     Ok(Json(response))
 }
 
 /// Resigns the current game.
 ///
-/// In `human_vs_bot`, player 0 resigns.
+/// In `human_vs_bot`, the human resigns.
 /// In `human_vs_human`, the current player resigns.
 ///
 /// # Route
@@ -433,20 +679,20 @@ pub async fn resign_game(
 ) -> Result<Json<GameStateResponse>, ErrorResponse> {
     check_api_version(&params.api_version)?;
 
-    let games = state.games();
-    let mut guard = games.write().await;
+    let mut guard = require_game_session(&state, &params).await?;
 
     let pending_report: Option<FinishedMatchRequest>;
     let user_ids_to_release_from_active_game_index: Option<Vec<String>>;
 
     let response = {
-        let session = require_game_session_mut(&mut guard, &params)?;
+        let session = &mut *guard;
         ensure_game_not_finished(&session.game, &params.api_version)?;
+        ensure_players_can_act(session, &params.api_version)?;
 
         let resigning_player = match &session.player_tokens {
             Some(_) => resolve_player_from_header_token(session, &headers, &params.api_version)?,
-            None => match (&session.bot_id, session.game.next_player()) {
-                (Some(_), _) => PlayerId::new(0),
+            None => match (session.human_player(), session.game.next_player()) {
+                (Some(human_player), _) => human_player,
                 (None, Some(player)) => player,
                 (None, None) => return Err(game_finished_error(&params.api_version)),
             },
@@ -471,12 +717,14 @@ pub async fn resign_game(
 
         pending_report = prepare_stats_report_if_needed(&params.game_id, session);
         user_ids_to_release_from_active_game_index = build_finished_game_user_id_list(session);
+        commit_session_update(&state, &params.game_id, session);
 
         build_game_state_response(
             &params.api_version,
             &params.game_id,
             session,
             Some(resigning_player),
+            &state,
         )
     };
 
@@ -488,38 +736,102 @@ pub async fn resign_game(
     )
     .await;
     state.metrics().inc_resignations();
-    report_finished_match_if_needed(&state, pending_report).await;
+    queue_stats_report(&state, pending_report);
 
     Ok(Json(response))
 }
 
+/// Takes back the human's last move in a `human_vs_bot` game.
+///
+/// The bot's reply is taken back together with the human move, so the human
+/// is to move again afterwards.
+///
+/// # Route
+/// `POST /{api_version}/games/{game_id}/undo`
+pub async fn undo_move(
+    State(state): State<AppState>,
+    Path(params): Path<GameParams>,
+) -> Result<Json<GameStateResponse>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+
+    let mut guard = require_game_session(&state, &params).await?;
+
+    let session = &mut *guard;
+    ensure_game_not_finished(&session.game, &params.api_version)?;
+    ensure_players_can_act(session, &params.api_version)?;
+
+    let Some(human_player) = session.human_player() else {
+        return Err(error_response(
+            "Undo is only allowed in human_vs_bot mode",
+            Some(params.api_version.clone()),
+        ));
+    };
+    if !session
+        .game
+        .history()
+        .iter()
+        .any(|movement| movement.player() == human_player)
+    {
+        return Err(error_response(
+            "There is no human move to undo",
+            Some(params.api_version.clone()),
+        ));
+    }
+
+    loop {
+        let movement = session.game.undo().map_err(|e| {
+            error_response(
+                &format!("Could not undo move: {}", e),
+                Some(params.api_version.clone()),
+            )
+        })?;
+        if movement.player() == human_player {
+            break;
+        }
+    }
+
+    reset_turn_timer(session);
+    commit_session_update(&state, &params.game_id, session);
+
+    Ok(Json(build_game_state_response(
+        &params.api_version,
+        &params.game_id,
+        session,
+        Some(human_player),
+        &state,
+    )))
+}
+
 /// Passes the current turn to the opponent.
 ///
-/// In `human_vs_bot`, player 0 can pass and the bot immediately plays its turn.
+/// In `human_vs_bot`, the human can pass and the bot immediately plays its turn.
 /// In `human_vs_human`, the current player passes and the opponent becomes active.
+/// The optional `time_limit_ms` query parameter bounds the bot's thinking time.
 ///
 /// # Route
 /// `POST /{api_version}/games/{game_id}/pass`
 pub async fn pass_turn(
     State(state): State<AppState>,
     Path(params): Path<GameParams>,
+    Query(time_limit): Query<BotTimeLimitQuery>,
     headers: HeaderMap,
 ) -> Result<Json<GameStateResponse>, ErrorResponse> {
     check_api_version(&params.api_version)?;
 
-    let bots = state.bots();
-    let games = state.games();
-    let mut guard = games.write().await;
+    let mut guard = require_game_session(&state, &params).await?;
 
     let pending_report: Option<FinishedMatchRequest>;
     let user_ids_to_release_from_active_game_index: Option<Vec<String>>;
+    let bot_turn: Option<BotTurn>;
+    let passing_player: PlayerId;
 
     let response = {
-        let session = require_game_session_mut(&mut guard, &params)?;
+        let session = &mut *guard;
         ensure_game_not_finished(&session.game, &params.api_version)?;
+        ensure_players_can_act(session, &params.api_version)?;
 
         let current_player = current_player_or_finished(&session.game, &params.api_version)?;
-        let passing_player = match &session.player_tokens {
+        passing_player = match &session.player_tokens {
             Some(_) => {
                 validate_player_token_for_turn(
                     session,
@@ -529,20 +841,18 @@ pub async fn pass_turn(
                 )?;
                 current_player
             }
-            None => match &session.bot_id {
-                Some(_) => {
-                    if current_player != PlayerId::new(0) {
-                        return Err(error_response(
-                            "Human turn passing is only allowed on player 0 turn in human_vs_bot mode",
-                            Some(params.api_version.clone()),
-                        ));
-                    }
-                    current_player
+            None => {
+                if session.bot_for(current_player).is_some() {
+                    return Err(error_response(
+                        "Human turn passing is only allowed on the human's turn in human_vs_bot mode",
+                        Some(params.api_version.clone()),
+                    ));
                 }
-                None => current_player,
-            },
+                current_player
+            }
         };
 
+        ensure_time_left(session, &params.api_version)?;
         record_online_player_presence(session, passing_player);
 
         session
@@ -558,32 +868,208 @@ pub async fn pass_turn(
                 )
             })?;
 
-        if let Some(bot_id) = &session.bot_id
-            && !session.game.check_game_over()
-        {
-            let bot = match bots.find(bot_id) {
-                Some(bot) => bot,
-                None => {
-                    let available_bots = bots.names().join(", ");
-                    return Err(bot_not_found_error(
-                        &params.api_version,
-                        bot_id,
-                        &available_bots,
-                    ));
-                }
-            };
+        bot_turn = prepare_bot_turn(&state, session, &params.api_version)?;
+        reset_turn_timer(session);
 
-            let bot_coords = match bot.choose_move(&session.game) {
-                Some(coords) => coords,
-                None => {
-                    return Err(error_response(
-                        "No valid moves available for the bot",
-                        Some(params.api_version.clone()),
-                    ));
-                }
-            };
+        pending_report = prepare_stats_report_if_needed(&params.game_id, session);
+        user_ids_to_release_from_active_game_index = build_finished_game_user_id_list(session);
+        commit_session_update(&state, &params.game_id, session);
+
+        build_game_state_response(
+            &params.api_version,
+            &params.game_id,
+            session,
+            Some(passing_player),
+            &state,
+        )
+    };
+
+    drop(guard);
+    clear_active_game_registration_if_needed(
+        &state,
+        &params.game_id,
+        user_ids_to_release_from_active_game_index,
+    )
+    .await;
+    state.metrics().inc_turn_passes();
+    queue_stats_report(&state, pending_report);
+
+    let Some(bot_turn) = bot_turn else {
+        return Ok(Json(response));
+    };
+    let response = play_bot_turn(
+        &state,
+        &params,
+        bot_turn,
+        &time_limit,
+        passing_player,
+        "Could not apply bot move after passing turn",
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+/// A bot reply that is computed after the game lock has been released.
+pub(super) struct BotTurn {
+    bot_id: String,
+    bot: Arc<dyn YBot>,
+    /// The game as the bot sees it.
+    game: GameY,
+}
+
+/// Returns the bot move owed in a `human_vs_bot` or `bot_vs_bot` game, if it
+/// is a bot's turn, and marks the session as waiting for it.
+pub(super) fn prepare_bot_turn(
+    state: &AppState,
+    session: &mut GameSession,
+    api_version: &str,
+) -> Result<Option<BotTurn>, ErrorResponse> {
+    let Some(bot_id) = session
+        .game
+        .next_player()
+        .and_then(|player| session.bot_for(player))
+    else {
+        return Ok(None);
+    };
+
+    let bots = state.bots();
+    let Some(bot) = bots.find(bot_id) else {
+        let available_bots = bots.names().join(", ");
+        return Err(bot_not_found_error(api_version, bot_id, &available_bots));
+    };
+    let bot_turn = BotTurn {
+        bot_id: bot_id.clone(),
+        bot,
+        game: session.game.clone(),
+    };
+    session.bot_turn = BotTurnStatus::Thinking;
+    Ok(Some(bot_turn))
+}
 
-            if let Some(bot_player) = session.game.next_player() {
+/// Lets the bot choose its move on the worker pool, then applies it.
+///
+/// The work runs in its own task, so the move is still applied when the
+/// request that started it goes away. The move is dropped if the game changed
+/// while the bot was thinking, for instance because the human took their move
+/// back; the response then shows the game as it is.
+///
+/// When the bot fails to move, the error is returned. A `human_vs_bot` game
+/// then waits for the move to be retried, while in a `bot_vs_bot` game the
+/// failing bot forfeits.
+pub(super) async fn play_bot_turn(
+    state: &AppState,
+    params: &GameParams,
+    bot_turn: BotTurn,
+    time_limit: &BotTimeLimitQuery,
+    requesting_player: PlayerId,
+    apply_error_message: &'static str,
+) -> Result<GameStateResponse, ErrorResponse> {
+    tokio::spawn(run_bot_turn(
+        state.clone(),
+        params.clone(),
+        bot_turn,
+        time_limit.time_limit(),
+        requesting_player,
+        apply_error_message,
+    ))
+    .await
+    .map_err(|e| {
+        let mut response = error_response(
+            &format!("Bot turn failed: {}", e),
+            Some(params.api_version.clone()),
+        );
+        response.status = axum::http::StatusCode::INTERNAL_SERVER_ERROR;
+        response
+    })?
+}
+
+/// Starts the bot move owed in a `human_vs_bot` game when no task is working
+/// on it: after a restart, or once [`BOT_TURN_RETRY_DELAY`] has passed since
+/// the last attempt failed.
+fn resume_bot_turn_if_needed(
+    state: &AppState,
+    params: &GameParams,
+    session: &mut GameSession,
+    now: Instant,
+) {
+    let due = match session.bot_turn {
+        BotTurnStatus::Idle => true,
+        BotTurnStatus::Thinking => false,
+        BotTurnStatus::Failed(failed_at) => {
+            now.saturating_duration_since(failed_at) >= BOT_TURN_RETRY_DELAY
+        }
+    };
+    let Some(human_player) = session.human_player().filter(|_| due) else {
+        return;
+    };
+
+    match prepare_bot_turn(state, session, &params.api_version) {
+        Ok(Some(bot_turn)) => {
+            tokio::spawn(run_bot_turn(
+                state.clone(),
+                params.clone(),
+                bot_turn,
+                None,
+                human_player,
+                "Could not apply bot move",
+            ));
+        }
+        Ok(None) => {}
+        Err(error) => {
+            warn!(
+                "Could not resume bot turn in game {}: {}",
+                params.game_id, error.message
+            );
+            session.bot_turn = BotTurnStatus::Failed(now);
+        }
+    }
+}
+
+async fn run_bot_turn(
+    state: AppState,
+    params: GameParams,
+    bot_turn: BotTurn,
+    time_limit: Option<Duration>,
+    requesting_player: PlayerId,
+    apply_error_message: &'static str,
+) -> Result<GameStateResponse, ErrorResponse> {
+    let expected_history = bot_turn.game.history().to_vec();
+    let chosen = choose_bot_move(
+        &state,
+        &bot_turn.bot_id,
+        bot_turn.bot,
+        bot_turn.game,
+        time_limit,
+    )
+    .await
+    .map_err(|e| {
+        let mut response = error_response(&e.to_string(), Some(params.api_version.clone()));
+        response.status = axum::http::StatusCode::INTERNAL_SERVER_ERROR;
+        response
+    })
+    .and_then(|coords| {
+        coords.ok_or_else(|| {
+            error_response(
+                "No valid moves available for the bot",
+                Some(params.api_version.clone()),
+            )
+        })
+    });
+
+    let mut guard = require_game_session(&state, &params).await?;
+
+    let mut pending_report: Option<FinishedMatchRequest> = None;
+    let mut user_ids_to_release_from_active_game_index: Option<Vec<String>> = None;
+    let mut forfeit_error: Option<ErrorResponse> = None;
+
+    let response = {
+        let session = &mut *guard;
+
+        if session.game.history() == expected_history.as_slice()
+            && let Some(bot_player) = session.game.next_player()
+        {
+            let applied = chosen.and_then(|bot_coords| {
                 session
                     .game
                     .add_move(Movement::Placement {
@@ -592,23 +1078,52 @@ pub async fn pass_turn(
                     })
                     .map_err(|e| {
                         error_response(
-                            &format!("Could not apply bot move after passing turn: {}", e),
+                            &format!("{}: {}", apply_error_message, e),
+                            Some(params.api_version.clone()),
+                        )
+                    })
+            });
+            if let Err(error) = applied {
+                warn!(
+                    "Bot {} could not move in game {}: {}",
+                    bot_turn.bot_id, params.game_id, error.message
+                );
+                if game_mode(session) != GameMode::BotVsBot {
+                    session.bot_turn = BotTurnStatus::Failed(Instant::now());
+                    return Err(error);
+                }
+                // Nobody is waiting to retry an exhibition move, so the
+                // failing bot loses the game.
+                session.completion_reason = Some(GameCompletionReason::BotFailure);
+                session
+                    .game
+                    .add_move(Movement::Action {
+                        player: bot_player,
+                        action: GameAction::Resign,
+                    })
+                    .map_err(|e| {
+                        error_response(
+                            &format!("Could not forfeit the failing bot: {}", e),
                             Some(params.api_version.clone()),
                         )
                     })?;
+                state.metrics().inc_resignations();
+                forfeit_error = Some(error);
             }
-        }
-
-        reset_turn_timer(session);
+            session.bot_turn = BotTurnStatus::Idle;
+            reset_turn_timer(session);
 
-        pending_report = prepare_stats_report_if_needed(&params.game_id, session);
-        user_ids_to_release_from_active_game_index = build_finished_game_user_id_list(session);
+            pending_report = prepare_stats_report_if_needed(&params.game_id, session);
+            user_ids_to_release_from_active_game_index = build_finished_game_user_id_list(session);
+            commit_session_update(&state, &params.game_id, session);
+        }
 
         build_game_state_response(
             &params.api_version,
             &params.game_id,
             session,
-            Some(passing_player),
+            Some(requesting_player),
+            &state,
         )
     };
 
@@ -619,10 +1134,12 @@ pub async fn pass_turn(
         user_ids_to_release_from_active_game_index,
     )
     .await;
-    state.metrics().inc_turn_passes();
-    report_finished_match_if_needed(&state, pending_report).await;
+    queue_stats_report(&state, pending_report);
 
-    Ok(Json(response))
+    match forfeit_error {
+        Some(error) => Err(error),
+        None => Ok(response),
+    }
 }
 
 fn read_header_string(headers: &HeaderMap, name: &str) -> Option<String> {
@@ -647,26 +1164,27 @@ fn mode_name(session: &GameSession) -> String {
         return "online".to_string();
     }
 
-    match &session.bot_id {
-        Some(_) => "human_vs_bot".to_string(),
-        None => "local_human_vs_human".to_string(),
+    match game_mode(session) {
+        GameMode::BotVsBot => "bot_vs_bot".to_string(),
+        GameMode::HumanVsBot => "human_vs_bot".to_string(),
+        GameMode::HumanVsHuman => "local_human_vs_human".to_string(),
     }
 }
 
-fn player0_id(session: &GameSession) -> String {
-    normalize_identifier(session.player0_user_id.clone()).unwrap_or_else(|| "player-0".to_string())
-}
-
-fn player1_id(session: &GameSession) -> String {
-    if let Some(id) = normalize_identifier(session.player1_user_id.clone()) {
+fn stats_player_id(session: &GameSession, player: PlayerId) -> String {
+    let user_id = match player.id() {
+        0 => &session.player0_user_id,
+        _ => &session.player1_user_id,
+    };
+    if let Some(id) = normalize_identifier(user_id.clone()) {
         return id;
     }
 
-    if let Some(bot_id) = &session.bot_id {
+    if let Some(bot_id) = session.bot_for(player) {
         return format!("bot:{}", bot_id);
     }
 
-    "player-1".to_string()
+    format!("player-{}", player.id())
 }
 
 fn prepare_stats_report_if_needed(
@@ -689,24 +1207,29 @@ fn prepare_stats_report_if_needed(
         .completion_reason
         .unwrap_or(GameCompletionReason::WinCondition);
 
-    let p0 = player0_id(session);
-    let p1 = player1_id(session);
+    let p0 = stats_player_id(session, PlayerId::new(0));
+    let p1 = stats_player_id(session, PlayerId::new(1));
 
     let p0_result = if winner == 0 { "win" } else { "loss" };
     let p1_result = if winner == 1 { "win" } else { "loss" };
 
     let winner_user_id = if winner == 0 { p0.clone() } else { p1.clone() };
     let final_board: YEN = (&session.game).into();
+    let ended_at = session
+        .finished_at
+        .map(|finished_at| SystemTime::now() - finished_at.elapsed())
+        .unwrap_or_else(SystemTime::now);
 
     session.stats_reported = true;
 
     Some(FinishedMatchRequest {
         game_id: game_id.to_string(),
         mode: Some(mode_name(session)),
-        bot_id: session.bot_id.clone(),
+        bot_id: session.bot_id().cloned(),
         reason: Some(game_completion_reason_to_stats_reason(completion_reason).to_string()),
         winner_id: Some(winner_user_id),
         final_board: Some(final_board),
+        start_position: custom_start_position(&session.game),
         players: vec![
             FinishedMatchPlayer {
                 user_id: p0,
@@ -717,70 +1240,77 @@ fn prepare_stats_report_if_needed(
                 result: p1_result.to_string(),
             },
         ],
+        ended_at: ended_at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_millis() as u64),
     })
 }
 
-async fn report_finished_match_if_needed(state: &AppState, pending_report: Option<FinishedMatchRequest>) {
-    let Some(payload) = pending_report else {
-        return;
-    };
-
-    let stats_url =
-        std::env::var("STATS_SERVICE_URL").unwrap_or_else(|_| "http://stats:3001".to_string());
-    let internal_token = std::env::var("STATS_INTERNAL_TOKEN")
-        .unwrap_or_else(|_| "stats-internal-token".to_string());
-
-    let endpoint = format!(
-        "{}/internal/v1/matches/finished",
-        stats_url.trim_end_matches('/')
-    );
-
-    let client = reqwest::Client::new();
-    state.metrics().inc_stats_report_attempts();
-    match client
-        .post(&endpoint)
-        .header("x-service-token", internal_token)
-        .json(&payload)
-        .send()
-        .await
-    {
-        Ok(response) => {
-            if !response.status().is_success() {
-                let status = response.status();
-                let body = response.text().await.unwrap_or_default();
-                warn!(
-                    "Failed to report finished game {} to stats. status={} body={}",
-                    payload.game_id, status, body
-                );
-                state.metrics().inc_stats_report_failures();
-            }
-        }
-        Err(error) => {
-            warn!(
-                "Could not report finished game {} to stats: {}",
-                payload.game_id, error
-            );
-            state.metrics().inc_stats_report_failures();
-        }
+/// Hands a finished-match report to the outbox, which delivers it to the
+/// stats service in the background.
+fn queue_stats_report(state: &AppState, pending_report: Option<FinishedMatchRequest>) {
+    if let Some(report) = pending_report {
+        state.stats_outbox().enqueue(report);
     }
 }
 
 async fn process_online_game_timeouts(state: &AppState) -> Result<(), String> {
+    let config = state.config();
+    let timeouts = &config.online;
     let mut pending_reports = Vec::new();
     let mut finished_games_to_unregister = Vec::new();
     let now = Instant::now();
 
-    let games = state.games();
-    let mut games_guard = games.write().await;
+    // Sessions are locked one at a time, so a sweep never holds up requests
+    // on other games.
+    for (game_id, _) in state.game_sessions().await {
+        let Some(mut session) = state.lock_game(&game_id).await else {
+            continue;
+        };
+        let session = &mut *session;
+
+        // A bot move lost to a restart or a failed attempt is started again.
+        let params = GameParams {
+            api_version: SUPPORTED_VERSION.to_string(),
+            game_id: game_id.clone(),
+        };
+        resume_bot_turn_if_needed(state, &params, session, now);
+
+        let (player_to_forfeit, completion_reason) = if let Some(player) =
+            find_player_out_of_time(session, now)
+        {
+            (player, GameCompletionReason::Timeout)
+        } else if let Some(player) =
+            find_player_to_forfeit_for_inactivity(session, now, timeouts.player_inactivity_timeout)
+        {
+            (player, GameCompletionReason::DisconnectTimeout)
+        } else {
+            let Some(player_to_auto_pass) =
+                find_player_to_auto_pass_for_turn_timeout(session, now, timeouts.turn_timeout)
+            else {
+                continue;
+            };
 
-    for (game_id, session) in games_guard.iter_mut() {
-        let Some(player_to_forfeit) =
-            find_player_to_forfeit_for_inactivity(session, now, ONLINE_PLAYER_INACTIVITY_TIMEOUT)
-        else {
+            session
+                .game
+                .add_move(Movement::Action {
+                    player: player_to_auto_pass,
+                    action: GameAction::PassTurn,
+                })
+                .map_err(|error| {
+                    format!(
+                        "could not auto-pass player {} after turn timeout: {}",
+                        player_to_auto_pass.id(),
+                        error
+                    )
+                })?;
+            reset_turn_timer(session);
+            commit_session_update(state, &game_id, session);
+            state.metrics().inc_turn_passes();
             continue;
         };
 
-        session.completion_reason = Some(GameCompletionReason::DisconnectTimeout);
+        session.completion_reason = Some(completion_reason);
         session
             .game
             .add_move(Movement::Action {
@@ -789,7 +1319,7 @@ async fn process_online_game_timeouts(state: &AppState) -> Result<(), String> {
             })
             .map_err(|error| {
                 format!(
-                    "could not forfeit inactive player {} in game {}: {}",
+                    "could not forfeit player {} in game {}: {}",
                     player_to_forfeit.id(),
                     game_id,
                     error
@@ -798,54 +1328,36 @@ async fn process_online_game_timeouts(state: &AppState) -> Result<(), String> {
         reset_turn_timer(session);
         state.metrics().inc_resignations();
 
-        if let Some(pending_report) = prepare_stats_report_if_needed(game_id, session) {
+        if let Some(pending_report) = prepare_stats_report_if_needed(&game_id, session) {
             pending_reports.push(pending_report);
         }
+        commit_session_update(state, &game_id, session);
 
         if let Some(user_ids_to_unregister) = build_finished_game_user_id_list(session) {
             finished_games_to_unregister.push((game_id.clone(), user_ids_to_unregister));
         }
-
-        continue;
     }
 
-    for (_game_id, session) in games_guard.iter_mut() {
-        let Some(player_to_auto_pass) =
-            find_player_to_auto_pass_for_turn_timeout(session, now, ONLINE_TURN_TIMEOUT)
-        else {
-            continue;
-        };
-
-        session
-            .game
-            .add_move(Movement::Action {
-                player: player_to_auto_pass,
-                action: GameAction::PassTurn,
-            })
-            .map_err(|error| {
-                format!(
-                    "could not auto-pass player {} after turn timeout: {}",
-                    player_to_auto_pass.id(),
-                    error
-                )
-            })?;
-        reset_turn_timer(session);
-        state.metrics().inc_turn_passes();
-    }
-
-    drop(games_guard);
-
     for (game_id, user_ids_to_unregister) in finished_games_to_unregister {
         unregister_active_game_for_user_ids(state, &game_id, &user_ids_to_unregister).await;
     }
 
     for pending_report in pending_reports {
-        report_finished_match_if_needed(state, Some(pending_report)).await;
+        queue_stats_report(state, Some(pending_report));
     }
 
     Ok(())
 }
 
+/// Returns the player whose clock has run out in an unfinished game.
+fn find_player_out_of_time(session: &GameSession, now: Instant) -> Option<PlayerId> {
+    if session.game.check_game_over() {
+        return None;
+    }
+
+    session.clock.as_ref()?.flagged_player(now)
+}
+
 fn find_player_to_forfeit_for_inactivity(
     session: &GameSession,
     now: Instant,
@@ -882,7 +1394,9 @@ fn find_player_to_auto_pass_for_turn_timeout(
     now: Instant,
     turn_timeout: Duration,
 ) -> Option<PlayerId> {
-    if session.player_tokens.is_none() || session.game.check_game_over() {
+    // Games with a clock are decided by flag-fall instead.
+    if session.player_tokens.is_none() || session.clock.is_some() || session.game.check_game_over()
+    {
         return None;
     }
 
@@ -905,7 +1419,23 @@ fn record_online_player_presence(session: &mut GameSession, player_id: PlayerId)
     last_seen_at_by_player_id.insert(player_id.id(), Instant::now());
 }
 
+/// Records activity on the session, writes it through to the game store and
+/// wakes the event streams of the game.
+fn commit_session_update(state: &AppState, game_id: &str, session: &mut GameSession) {
+    session.record_activity(Instant::now());
+    state.persist_game(game_id, session);
+    state.game_events().notify(game_id);
+}
+
+/// Starts the timers of the player to move, or stops them if the game is over.
+///
+/// Also presses the game clock, charging the time of the turn that just ended.
 fn reset_turn_timer(session: &mut GameSession) {
+    let next_player = session.game.next_player();
+    if let Some(clock) = &mut session.clock {
+        clock.press(next_player, Instant::now());
+    }
+
     if session.player_tokens.is_none() {
         session.turn_started_at = None;
         return;
@@ -925,15 +1455,21 @@ fn find_player_id_from_header_token(
     let tokens = session.player_tokens.as_ref()?;
     let provided_token = headers.get("x-player-token")?.to_str().ok()?;
 
+    player_id_for_token(tokens, provided_token)
+}
+
+/// Finds the player owning `provided_token`, comparing in constant time.
+fn player_id_for_token(tokens: &HashMap<u32, String>, provided_token: &str) -> Option<PlayerId> {
     tokens.iter().find_map(|(player_id, stored_token)| {
-        if stored_token == provided_token {
-            Some(PlayerId::new(*player_id))
-        } else {
-            None
-        }
+        tokens_match(stored_token, provided_token).then(|| PlayerId::new(*player_id))
     })
 }
 
+/// Compares two tokens without leaking through timing how much of them matches.
+fn tokens_match(expected: &str, provided: &str) -> bool {
+    expected.as_bytes().ct_eq(provided.as_bytes()).into()
+}
+
 pub(super) async fn ensure_user_id_is_available_for_new_game(
     state: &AppState,
     user_id: Option<&str>,
@@ -989,7 +1525,7 @@ async fn clear_active_game_registration_if_needed(
     unregister_active_game_for_user_ids(state, game_id, &user_ids_to_unregister).await;
 }
 
-async fn unregister_active_game_for_user_ids(
+pub(super) async fn unregister_active_game_for_user_ids(
     state: &AppState,
     game_id: &str,
     user_ids_to_unregister: &[String],
@@ -1024,7 +1560,7 @@ fn build_finished_game_user_id_list(session: &GameSession) -> Option<Vec<String>
     Some(tracked_user_ids)
 }
 
-fn collect_tracked_user_ids(session: &GameSession) -> Vec<String> {
+pub(super) fn collect_tracked_user_ids(session: &GameSession) -> Vec<String> {
     let mut tracked_user_ids = Vec::new();
 
     if let Some(player0_user_id) =
@@ -1055,6 +1591,8 @@ fn game_completion_reason_to_stats_reason(completion_reason: GameCompletionReaso
         GameCompletionReason::WinCondition => "win_condition",
         GameCompletionReason::Resignation => "resignation",
         GameCompletionReason::DisconnectTimeout => "disconnect_timeout",
+        GameCompletionReason::Timeout => "timeout",
+        GameCompletionReason::BotFailure => "bot_failure",
     }
 }
 
@@ -1062,12 +1600,17 @@ fn default_board_size() -> u32 {
     7
 }
 
-fn require_game_session_mut<'a>(
-    games: &'a mut HashMap<String, GameSession>,
+fn default_allow_spectators() -> bool {
+    true
+}
+
+async fn require_game_session(
+    state: &AppState,
     params: &GameParams,
-) -> Result<&'a mut GameSession, ErrorResponse> {
-    games
-        .get_mut(&params.game_id)
+) -> Result<OwnedMutexGuard<GameSession>, ErrorResponse> {
+    state
+        .lock_game(&params.game_id)
+        .await
         .ok_or_else(|| game_not_found_error(&params.api_version, &params.game_id))
 }
 
@@ -1090,6 +1633,32 @@ fn ensure_game_not_finished(game: &GameY, api_version: &str) -> Result<(), Error
     }
 }
 
+/// Rejects moves and actions sent by clients in games played by bots only.
+fn ensure_players_can_act(session: &GameSession, api_version: &str) -> Result<(), ErrorResponse> {
+    if game_mode(session) == GameMode::BotVsBot {
+        return Err(error_response(
+            "Players cannot act in bot_vs_bot mode",
+            Some(api_version.to_string()),
+        ));
+    }
+    Ok(())
+}
+
+/// Rejects a turn from a player whose clock has already run out.
+fn ensure_time_left(session: &GameSession, api_version: &str) -> Result<(), ErrorResponse> {
+    match session
+        .clock
+        .as_ref()
+        .and_then(|clock| clock.flagged_player(Instant::now()))
+    {
+        Some(player) => Err(error_response(
+            &format!("Player {} has run out of time", player.id()),
+            Some(api_version.to_string()),
+        )),
+        None => Ok(()),
+    }
+}
+
 fn current_player_or_finished(
     game: &GameY,
     api_version: &str,
@@ -1121,7 +1690,7 @@ fn validate_player_token_for_turn(
         )
     })?;
 
-    if provided != expected_token {
+    if !tokens_match(expected_token, provided) {
         return Err(error_response(
             "Invalid player_token for current turn",
             Some(api_version.to_string()),
@@ -1153,21 +1722,12 @@ fn resolve_player_from_header_token(
             )
         })?;
 
-    tokens
-        .iter()
-        .find_map(|(player_id, token)| {
-            if token == provided {
-                Some(PlayerId::new(*player_id))
-            } else {
-                None
-            }
-        })
-        .ok_or_else(|| {
-            error_response(
-                "Invalid x-player-token for this game",
-                Some(api_version.to_string()),
-            )
-        })
+    player_id_for_token(tokens, provided).ok_or_else(|| {
+        error_response(
+            "Invalid x-player-token for this game",
+            Some(api_version.to_string()),
+        )
+    })
 }
 
 fn bot_not_found_error(
@@ -1184,32 +1744,212 @@ fn bot_not_found_error(
     )
 }
 
-fn resolve_bot_id(
-    state: &AppState,
+fn resolve_bot_id(
+    state: &AppState,
+    mode: GameMode,
+    requested_bot_id: Option<String>,
+    api_version: &str,
+) -> Result<Option<String>, ErrorResponse> {
+    match mode {
+        GameMode::HumanVsHuman => {
+            if requested_bot_id.is_some() {
+                return Err(error_response(
+                    "bot_id is only valid in human_vs_bot mode",
+                    Some(api_version.to_string()),
+                ));
+            }
+            Ok(None)
+        }
+        GameMode::HumanVsBot => {
+            let bot_id = requested_bot_id.unwrap_or_else(|| "random_bot".to_string());
+            ensure_bot_is_registered(state, &bot_id, api_version)?;
+            Ok(Some(bot_id))
+        }
+        GameMode::BotVsBot => {
+            let Some(bot_id) = requested_bot_id else {
+                return Err(error_response(
+                    "bot_id is required in bot_vs_bot mode",
+                    Some(api_version.to_string()),
+                ));
+            };
+            ensure_bot_is_registered(state, &bot_id, api_version)?;
+            Ok(Some(bot_id))
+        }
+    }
+}
+
+fn resolve_opponent_bot_id(
+    state: &AppState,
+    mode: GameMode,
+    requested_bot_id: Option<String>,
+    api_version: &str,
+) -> Result<Option<String>, ErrorResponse> {
+    match (mode, requested_bot_id) {
+        (GameMode::BotVsBot, Some(bot_id)) => {
+            ensure_bot_is_registered(state, &bot_id, api_version)?;
+            Ok(Some(bot_id))
+        }
+        (GameMode::BotVsBot, None) => Err(error_response(
+            "opponent_bot_id is required in bot_vs_bot mode",
+            Some(api_version.to_string()),
+        )),
+        (_, Some(_)) => Err(error_response(
+            "opponent_bot_id is only valid in bot_vs_bot mode",
+            Some(api_version.to_string()),
+        )),
+        (_, None) => Ok(None),
+    }
+}
+
+/// Reserves one of the `exhibition.max_concurrent` exhibition slots.
+fn take_exhibition_slot(
+    state: &AppState,
+    api_version: &str,
+) -> Result<ExhibitionSlot, ErrorResponse> {
+    state
+        .exhibitions()
+        .try_take(state.config().exhibition.max_concurrent)
+        .ok_or_else(|| {
+            let mut response = error_response(
+                "Too many bot_vs_bot games are running, try again later",
+                Some(api_version.to_string()),
+            );
+            response.status = axum::http::StatusCode::TOO_MANY_REQUESTS;
+            response
+        })
+}
+
+fn ensure_bot_is_registered(
+    state: &AppState,
+    bot_id: &str,
+    api_version: &str,
+) -> Result<(), ErrorResponse> {
+    let bots = state.bots();
+    if bots.find(bot_id).is_none() {
+        let available_bots = bots.names().join(", ");
+        return Err(bot_not_found_error(api_version, bot_id, &available_bots));
+    }
+    Ok(())
+}
+
+/// Returns the seat the human plays in a `human_vs_bot` game.
+fn resolve_human_player(
     mode: GameMode,
-    requested_bot_id: Option<String>,
+    color: Option<ColorChoice>,
     api_version: &str,
-) -> Result<Option<String>, ErrorResponse> {
-    match mode {
-        GameMode::HumanVsHuman => {
-            if requested_bot_id.is_some() {
+) -> Result<Option<PlayerId>, ErrorResponse> {
+    match (mode, color) {
+        (GameMode::HumanVsBot, color) => Ok(Some(match color.unwrap_or_default() {
+            ColorChoice::First => PlayerId::new(0),
+            ColorChoice::Second => PlayerId::new(1),
+            ColorChoice::Random => PlayerId::new(rand::random_range(0..2)),
+        })),
+        (_, Some(_)) => Err(error_response(
+            "color is only valid in human_vs_bot mode",
+            Some(api_version.to_string()),
+        )),
+        (GameMode::HumanVsHuman | GameMode::BotVsBot, None) => Ok(None),
+    }
+}
+
+fn resolve_time_control(
+    mode: GameMode,
+    time_control: Option<TimeControl>,
+    api_version: &str,
+) -> Result<Option<TimeControl>, ErrorResponse> {
+    let Some(time_control) = time_control else {
+        return Ok(None);
+    };
+
+    if mode != GameMode::HumanVsHuman {
+        return Err(error_response(
+            "time_control is only valid in human_vs_human mode",
+            Some(api_version.to_string()),
+        ));
+    }
+    time_control.validate().map_err(|message| {
+        error_response(
+            &format!("Invalid time control: {}", message),
+            Some(api_version.to_string()),
+        )
+    })?;
+
+    Ok(Some(time_control))
+}
+
+/// Builds the game a new session starts from.
+///
+/// The requested moves are folded into the starting position, so undo never
+/// goes back past them and the session records the position as non-standard.
+fn build_starting_game(
+    size: Option<u32>,
+    initial_position: Option<YEN>,
+    moves: Option<Vec<Coordinates>>,
+    api_version: &str,
+) -> Result<GameY, ErrorResponse> {
+    let mut game = match initial_position {
+        Some(yen) => {
+            if let Some(size) = size.filter(|&size| size != yen.size()) {
                 return Err(error_response(
-                    "bot_id is only valid in human_vs_bot mode",
+                    &format!(
+                        "size {} does not match the initial_position size {}",
+                        size,
+                        yen.size()
+                    ),
                     Some(api_version.to_string()),
                 ));
             }
-            Ok(None)
-        }
-        GameMode::HumanVsBot => {
-            let bot_id = requested_bot_id.unwrap_or_else(|| "random_bot".to_string());
-            let bots = state.bots();
-            if bots.find(&bot_id).is_none() {
-                let available_bots = bots.names().join(", ");
-                return Err(bot_not_found_error(api_version, &bot_id, &available_bots));
-            }
-            Ok(Some(bot_id))
+            GameY::try_from(yen).map_err(|e| {
+                error_response(
+                    &format!("Invalid initial_position: {}", e),
+                    Some(api_version.to_string()),
+                )
+            })?
         }
+        None => GameY::new(size.unwrap_or_else(default_board_size)),
+    };
+
+    let moves = moves.unwrap_or_default();
+    for (idx, coords) in moves.iter().enumerate() {
+        let invalid_move = |reason: &str| {
+            error_response(
+                &format!("Invalid move {} in moves: {}", idx, reason),
+                Some(api_version.to_string()),
+            )
+        };
+        validate_coordinates(coords, game.board_size()).map_err(|msg| invalid_move(&msg))?;
+        let player = game
+            .next_player()
+            .ok_or_else(|| invalid_move("the game is already over"))?;
+        game.add_move(Movement::Placement {
+            player,
+            coords: *coords,
+        })
+        .map_err(|e| invalid_move(&e.to_string()))?;
+    }
+
+    if game.check_game_over() {
+        return Err(error_response(
+            "The starting position is already finished",
+            Some(api_version.to_string()),
+        ));
+    }
+    if moves.is_empty() {
+        return Ok(game);
     }
+
+    let start: YEN = (&game).into();
+    GameY::try_from(start).map_err(|e| {
+        error_response(
+            &format!("Invalid starting position: {}", e),
+            Some(api_version.to_string()),
+        )
+    })
+}
+
+/// Returns the starting position of `game` if it is not the empty board.
+fn custom_start_position(game: &GameY) -> Option<YEN> {
+    game.has_custom_start().then(|| game.starting_position())
 }
 
 fn validate_coordinates(coords: &Coordinates, board_size: u32) -> Result<(), String> {
@@ -1233,12 +1973,23 @@ fn validate_coordinates(coords: &Coordinates, board_size: u32) -> Result<(), Str
     Ok(())
 }
 
-fn build_game_state_response(
+/// Returns the API game mode of a session.
+pub(super) fn game_mode(session: &GameSession) -> GameMode {
+    match session.bots.iter().flatten().count() {
+        0 => GameMode::HumanVsHuman,
+        1 => GameMode::HumanVsBot,
+        _ => GameMode::BotVsBot,
+    }
+}
+
+pub(super) fn build_game_state_response(
     api_version: &str,
     game_id: &str,
     session: &GameSession,
     requesting_player_id: Option<PlayerId>,
+    state: &AppState,
 ) -> GameStateResponse {
+    let timeouts = &state.config().online;
     let (game_over, next_player, winner) = match session.game.status() {
         GameStatus::Ongoing { next_player } => (false, Some(next_player.id()), None),
         GameStatus::Finished { winner } => (true, None, Some(winner.id())),
@@ -1247,12 +1998,15 @@ fn build_game_state_response(
     GameStateResponse {
         api_version: api_version.to_string(),
         game_id: game_id.to_string(),
-        mode: match &session.bot_id {
-            Some(_) => GameMode::HumanVsBot,
-            None => GameMode::HumanVsHuman,
+        mode: game_mode(session),
+        bot_id: session.bot_id().cloned(),
+        opponent_bot_id: match game_mode(session) {
+            GameMode::BotVsBot => session.bot_for(PlayerId::new(1)).cloned(),
+            GameMode::HumanVsHuman | GameMode::HumanVsBot => None,
         },
-        bot_id: session.bot_id.clone(),
+        human_player: session.human_player().map(|player| player.id()),
         yen: (&session.game).into(),
+        start_position: custom_start_position(&session.game),
         game_over,
         next_player,
         winner,
@@ -1264,8 +2018,18 @@ fn build_game_state_response(
                 session,
                 requesting_player_id,
                 Instant::now(),
+                timeouts.player_inactivity_timeout,
             ),
-        turn_timeout_remaining_ms: calculate_turn_timeout_remaining_ms(session, Instant::now()),
+        turn_timeout_remaining_ms: calculate_turn_timeout_remaining_ms(
+            session,
+            Instant::now(),
+            timeouts.turn_timeout,
+        ),
+        spectator_count: state.spectators().count(game_id),
+        clock: session
+            .clock
+            .as_ref()
+            .map(|clock| clock.state(Instant::now())),
     }
 }
 
@@ -1273,6 +2037,7 @@ fn calculate_opponent_inactivity_timeout_remaining_ms(
     session: &GameSession,
     requesting_player_id: Option<PlayerId>,
     now: Instant,
+    inactivity_timeout: Duration,
 ) -> Option<u64> {
     if session.game.check_game_over() {
         return None;
@@ -1286,19 +2051,24 @@ fn calculate_opponent_inactivity_timeout_remaining_ms(
         .copied()
         .unwrap_or(session.created_at);
     let elapsed = now.saturating_duration_since(opponent_last_seen_at);
-    let remaining = ONLINE_PLAYER_INACTIVITY_TIMEOUT.saturating_sub(elapsed);
+    let remaining = inactivity_timeout.saturating_sub(elapsed);
 
     Some(remaining.as_millis().min(u128::from(u64::MAX)) as u64)
 }
 
-fn calculate_turn_timeout_remaining_ms(session: &GameSession, now: Instant) -> Option<u64> {
-    if session.game.check_game_over() || session.player_tokens.is_none() {
+fn calculate_turn_timeout_remaining_ms(
+    session: &GameSession,
+    now: Instant,
+    turn_timeout: Duration,
+) -> Option<u64> {
+    if session.game.check_game_over() || session.player_tokens.is_none() || session.clock.is_some()
+    {
         return None;
     }
 
     let turn_started_at = session.turn_started_at?;
     let elapsed = now.saturating_duration_since(turn_started_at);
-    let remaining = ONLINE_TURN_TIMEOUT.saturating_sub(elapsed);
+    let remaining = turn_timeout.saturating_sub(elapsed);
 
     Some(remaining.as_millis().min(u128::from(u64::MAX)) as u64)
 }
@@ -1339,24 +2109,20 @@ mod tests {
     fn test_mode_name_distinguishes_local_bot_and_online_stats_modes() {
         let now = Instant::now();
         let mut session = GameSession {
-            game: GameY::new(3),
-            bot_id: None,
             created_at: now,
-            turn_started_at: None,
-            player_tokens: None,
-            last_seen_at_by_player_id: None,
             player0_user_id: Some("fernando".to_string()),
-            player1_user_id: None,
-            stats_reported: false,
-            completion_reason: None,
+            ..GameSession::for_test(GameY::new(3))
         };
 
         assert_eq!(mode_name(&session), "local_human_vs_human");
 
-        session.bot_id = Some("greedy_bot".to_string());
+        session.bots = [None, Some("greedy_bot".to_string())];
         assert_eq!(mode_name(&session), "human_vs_bot");
 
-        session.bot_id = None;
+        session.bots[0] = Some("random_bot".to_string());
+        assert_eq!(mode_name(&session), "bot_vs_bot");
+
+        session.bots = [None, None];
         session.player_tokens = Some(HashMap::from([
             (0, "player-0-token".to_string()),
             (1, "player-1-token".to_string()),
@@ -1372,8 +2138,6 @@ mod tests {
         last_seen_at_by_player_id.insert(1, now);
 
         let session = GameSession {
-            game: GameY::new(3),
-            bot_id: None,
             created_at: now
                 .checked_sub(Duration::from_secs(61))
                 .expect("instant subtraction should succeed"),
@@ -1385,8 +2149,7 @@ mod tests {
             last_seen_at_by_player_id: Some(last_seen_at_by_player_id),
             player0_user_id: Some("fernando".to_string()),
             player1_user_id: Some("jose".to_string()),
-            stats_reported: false,
-            completion_reason: None,
+            ..GameSession::for_test(GameY::new(3))
         };
 
         let forfeiting_player =
@@ -1399,8 +2162,6 @@ mod tests {
     fn test_find_player_to_auto_pass_for_turn_timeout_returns_current_turn_player() {
         let now = Instant::now();
         let session = GameSession {
-            game: GameY::new(3),
-            bot_id: None,
             created_at: now,
             turn_started_at: Some(
                 now.checked_sub(Duration::from_secs(61))
@@ -1413,8 +2174,7 @@ mod tests {
             last_seen_at_by_player_id: Some(HashMap::from([(0, now), (1, now)])),
             player0_user_id: Some("fernando".to_string()),
             player1_user_id: Some("jose".to_string()),
-            stats_reported: false,
-            completion_reason: None,
+            ..GameSession::for_test(GameY::new(3))
         };
 
         let timed_out_player =
@@ -1444,19 +2204,12 @@ mod tests {
         let user_id = "fernando".to_string();
 
         let session = GameSession {
-            game: GameY::new(3),
-            bot_id: None,
-            created_at: Instant::now(),
-            turn_started_at: None,
-            player_tokens: None,
-            last_seen_at_by_player_id: None,
             player0_user_id: Some(user_id.clone()),
-            player1_user_id: None,
             stats_reported: true,
-            completion_reason: None,
+            ..GameSession::for_test(GameY::new(3))
         };
 
-        state.games().write().await.insert(game_id.clone(), session);
+        state.insert_game(game_id.clone(), session).await;
         state
             .active_game_id_by_user_id()
             .write()
@@ -1481,32 +2234,70 @@ mod tests {
         assert!(response.game_over);
         assert_eq!(response.completion_reason, Some(GameCompletionReason::Resignation));
 
-        let games = state.games();
-        let games_guard = games.read().await;
-        let stored_session = games_guard
-            .get(&game_id)
+        let stored_session = state
+            .lock_game(&game_id)
+            .await
             .expect("game should still exist in memory");
         assert!(stored_session.game.check_game_over());
         assert_eq!(
             stored_session.completion_reason,
             Some(GameCompletionReason::Resignation)
         );
-        drop(games_guard);
+        drop(stored_session);
 
         let active_game_id_by_user_id = state.active_game_id_by_user_id();
         let active_game_id_by_user_id_guard = active_game_id_by_user_id.read().await;
         assert!(!active_game_id_by_user_id_guard.contains_key(&user_id));
     }
 
+    #[tokio::test]
+    async fn test_game_events_require_token_and_record_presence_in_matchmaking_games() {
+        let state = AppState::new(YBotRegistry::new());
+        let game_id = "game-events-token".to_string();
+        let created_at = Instant::now() - Duration::from_secs(30);
+
+        let session = GameSession {
+            created_at,
+            turn_started_at: Some(created_at),
+            player_tokens: Some(HashMap::from([
+                (0, "player-0-token".to_string()),
+                (1, "player-1-token".to_string()),
+            ])),
+            last_seen_at_by_player_id: Some(HashMap::new()),
+            player0_user_id: Some("fernando".to_string()),
+            player1_user_id: Some("jose".to_string()),
+            stats_reported: true,
+            last_activity_at: created_at,
+            ..GameSession::for_test(GameY::new(3))
+        };
+        state.insert_game(game_id.clone(), session).await;
+        let params = || GameParams {
+            api_version: "v1".to_string(),
+            game_id: game_id.clone(),
+        };
+
+        let missing_token =
+            game_events(State(state.clone()), Path(params()), HeaderMap::new()).await;
+        assert!(missing_token.is_err());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-player-token", HeaderValue::from_static("player-1-token"));
+        let stream = game_events(State(state.clone()), Path(params()), headers).await;
+        assert!(stream.is_ok());
+
+        let session = state.lock_game(&game_id).await.unwrap();
+        let last_seen = session.last_seen_at_by_player_id.as_ref().unwrap();
+        assert!(last_seen.contains_key(&1));
+        assert!(!last_seen.contains_key(&0));
+        assert_eq!(state.game_events().subscriber_count(&game_id), 1);
+    }
+
     #[tokio::test]
     async fn test_resign_game_requires_token_for_matchmaking_games() {
         let state = AppState::new(YBotRegistry::new());
         let game_id = "game-resign-token-required".to_string();
 
         let session = GameSession {
-            game: GameY::new(3),
-            bot_id: None,
-            created_at: Instant::now(),
             turn_started_at: Some(Instant::now()),
             player_tokens: Some(HashMap::from([
                 (0, "player-0-token".to_string()),
@@ -1516,10 +2307,10 @@ mod tests {
             player0_user_id: Some("fernando".to_string()),
             player1_user_id: Some("jose".to_string()),
             stats_reported: true,
-            completion_reason: None,
+            ..GameSession::for_test(GameY::new(3))
         };
 
-        state.games().write().await.insert(game_id.clone(), session);
+        state.insert_game(game_id.clone(), session).await;
 
         let result = resign_game(
             State(state),
@@ -1549,8 +2340,6 @@ mod tests {
 
         let now = Instant::now();
         let session = GameSession {
-            game,
-            bot_id: None,
             created_at: now,
             turn_started_at: Some(now),
             player_tokens: Some(HashMap::from([
@@ -1561,10 +2350,10 @@ mod tests {
             player0_user_id: Some("fernando".to_string()),
             player1_user_id: Some("jose".to_string()),
             stats_reported: true,
-            completion_reason: None,
+            ..GameSession::for_test(game)
         };
 
-        state.games().write().await.insert(game_id.clone(), session);
+        state.insert_game(game_id.clone(), session).await;
 
         let mut headers = HeaderMap::new();
         headers.insert("x-player-token", HeaderValue::from_static("player-1-token"));
@@ -1595,19 +2384,13 @@ mod tests {
         let game_id = "game-resign-bot".to_string();
 
         let session = GameSession {
-            game: GameY::new(3),
-            bot_id: Some("random_bot".to_string()),
-            created_at: Instant::now(),
-            turn_started_at: None,
-            player_tokens: None,
-            last_seen_at_by_player_id: None,
+            bots: [None, Some("random_bot".to_string())],
             player0_user_id: Some("human".to_string()),
-            player1_user_id: None,
             stats_reported: true,
-            completion_reason: None,
+            ..GameSession::for_test(GameY::new(3))
         };
 
-        state.games().write().await.insert(game_id.clone(), session);
+        state.insert_game(game_id.clone(), session).await;
 
         let response = resign_game(
             State(state),
@@ -1632,19 +2415,13 @@ mod tests {
         let game_id = "game-resign-local".to_string();
 
         let session = GameSession {
-            game: GameY::new(3),
-            bot_id: None,
-            created_at: Instant::now(),
-            turn_started_at: None,
-            player_tokens: None,
-            last_seen_at_by_player_id: None,
             player0_user_id: Some("p1".to_string()),
             player1_user_id: Some("p2".to_string()),
             stats_reported: true,
-            completion_reason: None,
+            ..GameSession::for_test(GameY::new(3))
         };
 
-        state.games().write().await.insert(game_id.clone(), session);
+        state.insert_game(game_id.clone(), session).await;
 
         let response = resign_game(
             State(state),
@@ -1671,19 +2448,12 @@ mod tests {
         game.add_move(Movement::Action { player: PlayerId::new(0), action: GameAction::Resign }).unwrap();
 
         let session = GameSession {
-            game,
-            bot_id: None,
-            created_at: Instant::now(),
-            turn_started_at: None,
-            player_tokens: None,
-            last_seen_at_by_player_id: None,
-            player0_user_id: None,
-            player1_user_id: None,
             stats_reported: true,
             completion_reason: Some(GameCompletionReason::Resignation),
+            ..GameSession::for_test(game)
         };
 
-        state.games().write().await.insert(game_id.clone(), session);
+        state.insert_game(game_id.clone(), session).await;
 
         // Test POST /resign
         let result_post = resign_game(
@@ -1718,8 +2488,6 @@ mod tests {
         last_seen.insert(1, now);      // Player 1 active
 
         let session = GameSession {
-            game: GameY::new(3),
-            bot_id: None,
             created_at: long_ago,
             turn_started_at: Some(long_ago),
             player_tokens: Some(HashMap::from([
@@ -1730,18 +2498,17 @@ mod tests {
             player0_user_id: Some("user0".to_string()),
             player1_user_id: Some("user1".to_string()),
             stats_reported: true,
-            completion_reason: None,
+            ..GameSession::for_test(GameY::new(3))
         };
 
-        state.games().write().await.insert(game_id.clone(), session);
+        state.insert_game(game_id.clone(), session).await;
 
         process_online_game_timeouts(&state).await.expect("timeout processing should succeed");
 
-        let games_lock = state.games();
-        let games = games_lock.read().await;
-        let updated_session = games.get(&game_id).unwrap();
+        let updated_session = state.lock_game(&game_id).await.unwrap();
         assert!(updated_session.game.check_game_over());
         assert_eq!(updated_session.completion_reason, Some(GameCompletionReason::DisconnectTimeout));
+        assert_eq!(updated_session.player_tokens, Some(HashMap::new()));
         
         // Winner should be player 1 since player 0 resigned due to timeout
         let winner = match updated_session.game.status() {
@@ -1751,6 +2518,369 @@ mod tests {
         assert_eq!(winner, Some(1));
     }
 
+    fn clocked_local_session(turn_started_secs_ago: u64) -> GameSession {
+        let started_at = Instant::now() - Duration::from_secs(turn_started_secs_ago);
+        let game = GameY::new(3);
+        let clock = GameClock::new(
+            TimeControl::Fischer {
+                base_ms: 5_000,
+                increment_ms: 0,
+            },
+            game.next_player(),
+            started_at,
+        );
+
+        GameSession {
+            created_at: started_at,
+            stats_reported: true,
+            last_activity_at: started_at,
+            clock: Some(clock),
+            ..GameSession::for_test(game)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_game_rejects_time_control_in_human_vs_bot() {
+        let state =
+            AppState::new(YBotRegistry::new().with_bot(std::sync::Arc::new(crate::RandomBot)));
+
+        let result = create_game(
+            State(state),
+            Path(ApiVersionParams {
+                api_version: "v1".to_string(),
+            }),
+            HeaderMap::new(),
+            Json(CreateGameRequest {
+                size: Some(3),
+                initial_position: None,
+                moves: None,
+                mode: GameMode::HumanVsBot,
+                bot_id: None,
+                opponent_bot_id: None,
+                color: None,
+                allow_spectators: true,
+                time_control: Some(TimeControl::Fischer {
+                    base_ms: 60_000,
+                    increment_ms: 0,
+                }),
+            }),
+        )
+        .await;
+
+        assert!(result.unwrap_err().message.contains("human_vs_human"));
+    }
+
+    #[tokio::test]
+    async fn test_process_online_game_timeouts_ends_game_on_flag_fall() {
+        let state = AppState::new(YBotRegistry::new());
+        let game_id = "game-flag-fall".to_string();
+        state
+            .insert_game(game_id.clone(), clocked_local_session(10))
+            .await;
+
+        process_online_game_timeouts(&state)
+            .await
+            .expect("timeout processing should succeed");
+
+        let updated_session = state.lock_game(&game_id).await.unwrap();
+        assert_eq!(
+            updated_session.completion_reason,
+            Some(GameCompletionReason::Timeout)
+        );
+        assert!(matches!(
+            updated_session.game.status(),
+            GameStatus::Finished { winner } if *winner == PlayerId::new(1)
+        ));
+        let clock = updated_session
+            .clock
+            .as_ref()
+            .unwrap()
+            .state(Instant::now());
+        assert_eq!(clock.remaining_ms, [0, 5_000]);
+        assert_eq!(clock.running_player, None);
+    }
+
+    #[tokio::test]
+    async fn test_play_move_rejects_player_out_of_time() {
+        let state = AppState::new(YBotRegistry::new());
+        let game_id = "game-out-of-time".to_string();
+        state
+            .insert_game(game_id.clone(), clocked_local_session(10))
+            .await;
+
+        let result = play_move(
+            State(state.clone()),
+            Path(GameParams {
+                api_version: "v1".to_string(),
+                game_id: game_id.clone(),
+            }),
+            Query(BotTimeLimitQuery::default()),
+            Json(MoveRequest {
+                coords: Coordinates::new(2, 0, 0),
+                player_token: None,
+            }),
+        )
+        .await;
+
+        assert!(result.unwrap_err().message.contains("run out of time"));
+        assert!(
+            state
+                .lock_game(&game_id)
+                .await
+                .unwrap()
+                .game
+                .history()
+                .is_empty()
+        );
+    }
+
+    fn local_bot_session() -> GameSession {
+        GameSession {
+            bots: [None, Some("random_bot".to_string())],
+            player0_user_id: Some("human".to_string()),
+            ..GameSession::for_test(GameY::new(3))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_play_move_applies_bot_reply_computed_on_worker_pool() {
+        let state = AppState::new(YBotRegistry::new().with_bot(Arc::new(crate::RandomBot)));
+        let game_id = "game-bot-reply".to_string();
+        state
+            .insert_game(game_id.clone(), local_bot_session())
+            .await;
+
+        let response = play_move(
+            State(state.clone()),
+            Path(GameParams {
+                api_version: "v1".to_string(),
+                game_id: game_id.clone(),
+            }),
+            Query(BotTimeLimitQuery::default()),
+            Json(MoveRequest {
+                coords: Coordinates::new(2, 0, 0),
+                player_token: None,
+            }),
+        )
+        .await
+        .expect("move should succeed")
+        .0;
+
+        assert_eq!(response.next_player, Some(0));
+        let session = state.lock_game(&game_id).await.unwrap();
+        assert_eq!(session.game.history().len(), 2);
+        assert_eq!(state.bot_workers().running(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_bot_reply_is_dropped_when_game_changed_while_thinking() {
+        let state = AppState::new(YBotRegistry::new().with_bot(Arc::new(crate::RandomBot)));
+        let game_id = "game-stale-bot-reply".to_string();
+        let mut session = local_bot_session();
+        session
+            .game
+            .add_move(Movement::Placement {
+                player: PlayerId::new(0),
+                coords: Coordinates::new(2, 0, 0),
+            })
+            .unwrap();
+        let bot_turn = prepare_bot_turn(&state, &mut session, "v1")
+            .unwrap()
+            .expect("bot should be to move");
+        session.game.undo().unwrap();
+        state.insert_game(game_id.clone(), session).await;
+
+        let params = GameParams {
+            api_version: "v1".to_string(),
+            game_id: game_id.clone(),
+        };
+        let response = play_bot_turn(
+            &state,
+            &params,
+            bot_turn,
+            &BotTimeLimitQuery::default(),
+            PlayerId::new(0),
+            "Could not apply bot move",
+        )
+        .await
+        .expect("stale reply should not be an error");
+
+        assert_eq!(response.next_player, Some(0));
+        assert!(
+            state
+                .lock_game(&game_id)
+                .await
+                .unwrap()
+                .game
+                .history()
+                .is_empty()
+        );
+    }
+
+    /// Bot that never finds a move, standing in for a broken bot.
+    struct StuckBot;
+
+    impl YBot for StuckBot {
+        fn name(&self) -> &str {
+            "stuck_bot"
+        }
+
+        fn choose_move(&self, _board: &GameY) -> Option<Coordinates> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_bot_turn_is_retried_after_a_delay() {
+        let state = AppState::new(YBotRegistry::new().with_bot(Arc::new(StuckBot)));
+        let game_id = "game-stuck-bot".to_string();
+        let session = GameSession {
+            bots: [None, Some("stuck_bot".to_string())],
+            ..local_bot_session()
+        };
+        state.insert_game(game_id.clone(), session).await;
+        let params = GameParams {
+            api_version: "v1".to_string(),
+            game_id: game_id.clone(),
+        };
+
+        let result = play_move(
+            State(state.clone()),
+            Path(params.clone()),
+            Query(BotTimeLimitQuery::default()),
+            Json(MoveRequest {
+                coords: Coordinates::new(2, 0, 0),
+                player_token: None,
+            }),
+        )
+        .await;
+
+        assert!(result.is_err());
+        let mut session = state.lock_game(&game_id).await.unwrap();
+        let BotTurnStatus::Failed(failed_at) = session.bot_turn else {
+            panic!("the failure should be recorded");
+        };
+        resume_bot_turn_if_needed(&state, &params, &mut session, failed_at);
+        assert_eq!(session.bot_turn, BotTurnStatus::Failed(failed_at));
+        resume_bot_turn_if_needed(
+            &state,
+            &params,
+            &mut session,
+            failed_at + BOT_TURN_RETRY_DELAY,
+        );
+        assert_eq!(session.bot_turn, BotTurnStatus::Thinking);
+    }
+
+    #[tokio::test]
+    async fn test_failing_exhibition_bot_forfeits_the_game() {
+        let state = AppState::new(
+            YBotRegistry::new()
+                .with_bot(Arc::new(StuckBot))
+                .with_bot(Arc::new(crate::RandomBot)),
+        );
+        let game_id = "game-stuck-exhibition".to_string();
+        let mut session = GameSession {
+            bots: [
+                Some("stuck_bot".to_string()),
+                Some("random_bot".to_string()),
+            ],
+            ..GameSession::for_test(GameY::new(3))
+        };
+        let bot_turn = prepare_bot_turn(&state, &mut session, "v1")
+            .unwrap()
+            .expect("the first bot should be to move");
+        state.insert_game(game_id.clone(), session).await;
+        let params = GameParams {
+            api_version: "v1".to_string(),
+            game_id: game_id.clone(),
+        };
+
+        let result = play_bot_turn(
+            &state,
+            &params,
+            bot_turn,
+            &BotTimeLimitQuery::default(),
+            PlayerId::new(0),
+            "Could not apply exhibition move",
+        )
+        .await;
+
+        assert!(result.is_err());
+        let session = state.lock_game(&game_id).await.unwrap();
+        assert!(matches!(
+            session.game.status(),
+            GameStatus::Finished { winner } if *winner == PlayerId::new(1)
+        ));
+        assert_eq!(
+            session.completion_reason,
+            Some(GameCompletionReason::BotFailure)
+        );
+        assert_eq!(session.bot_turn, BotTurnStatus::Idle);
+    }
+
+    #[tokio::test]
+    async fn test_get_game_resumes_a_lost_bot_turn() {
+        let state = AppState::new(YBotRegistry::new().with_bot(Arc::new(crate::RandomBot)));
+        let game_id = "game-lost-bot-turn".to_string();
+        let session = GameSession {
+            bots: [Some("random_bot".to_string()), None],
+            ..local_bot_session()
+        };
+        state.insert_game(game_id.clone(), session).await;
+
+        let response = get_game(
+            State(state.clone()),
+            Path(GameParams {
+                api_version: "v1".to_string(),
+                game_id: game_id.clone(),
+            }),
+            Query(GetGameQuery { action: None }),
+            HeaderMap::new(),
+        )
+        .await
+        .expect("get should succeed")
+        .0;
+        assert_eq!(response.next_player, Some(0));
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while state
+                .lock_game(&game_id)
+                .await
+                .unwrap()
+                .game
+                .history()
+                .is_empty()
+            {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the bot should play its opening move");
+        let session = state.lock_game(&game_id).await.unwrap();
+        assert_eq!(session.game.next_player(), Some(PlayerId::new(1)));
+        assert_eq!(session.bot_turn, BotTurnStatus::Idle);
+    }
+
+    #[test]
+    fn test_clocked_games_are_not_auto_passed() {
+        let mut session = clocked_local_session(0);
+        session.player_tokens = Some(HashMap::from([
+            (0, "token0".to_string()),
+            (1, "token1".to_string()),
+        ]));
+        session.turn_started_at = Some(Instant::now() - Duration::from_secs(120));
+
+        let turn_timeout = Duration::from_secs(60);
+        let player =
+            find_player_to_auto_pass_for_turn_timeout(&session, Instant::now(), turn_timeout);
+
+        assert_eq!(player, None);
+        assert_eq!(
+            calculate_turn_timeout_remaining_ms(&session, Instant::now(), turn_timeout),
+            None
+        );
+    }
+
     #[test]
     fn test_prepare_stats_report_for_resignation() {
         let mut game = GameY::new(3);
@@ -1761,16 +2891,10 @@ mod tests {
         .unwrap();
 
         let mut session = GameSession {
-            game,
-            bot_id: None,
-            created_at: Instant::now(),
-            turn_started_at: None,
-            player_tokens: None,
-            last_seen_at_by_player_id: None,
             player0_user_id: Some("user0".to_string()),
             player1_user_id: Some("user1".to_string()),
-            stats_reported: false,
             completion_reason: Some(GameCompletionReason::Resignation),
+            ..GameSession::for_test(game)
         };
 
         let report = prepare_stats_report_if_needed("game-id", &mut session).expect("should prepare report");
@@ -1778,4 +2902,50 @@ mod tests {
         assert_eq!(report.winner_id, Some("user1".to_string()));
         assert!(session.stats_reported);
     }
+
+    #[test]
+    fn test_prepare_stats_report_attributes_bot_win_when_human_plays_second() {
+        let mut session = local_bot_session();
+        session.bots = [Some("random_bot".to_string()), None];
+        session.player0_user_id = None;
+        session.player1_user_id = Some("human".to_string());
+        session
+            .game
+            .add_move(Movement::Action {
+                player: PlayerId::new(1),
+                action: GameAction::Resign,
+            })
+            .unwrap();
+
+        let report =
+            prepare_stats_report_if_needed("game-id", &mut session).expect("should prepare report");
+
+        assert_eq!(report.winner_id, Some("bot:random_bot".to_string()));
+        assert_eq!(report.players[0].user_id, "bot:random_bot");
+        assert_eq!(report.players[0].result, "win");
+        assert_eq!(report.players[1].user_id, "human");
+        assert_eq!(report.players[1].result, "loss");
+    }
+
+    #[test]
+    fn test_custom_start_position_ignores_the_empty_board() {
+        assert!(custom_start_position(&GameY::new(3)).is_none());
+
+        let game = build_starting_game(Some(3), None, Some(vec![Coordinates::new(2, 0, 0)]), "v1")
+            .expect("start should be valid");
+        let start = custom_start_position(&game).expect("start is not the empty board");
+        assert_eq!(start.turn(), 1);
+        assert!(game.history().is_empty());
+    }
+
+    #[test]
+    fn test_player_id_for_token_requires_exact_match() {
+        let tokens = HashMap::from([(0, "ptk-aa".to_string()), (1, "ptk-bb".to_string())]);
+
+        assert_eq!(player_id_for_token(&tokens, "ptk-bb"), Some(PlayerId::new(1)));
+        assert_eq!(player_id_for_token(&tokens, "ptk-b"), None);
+        assert_eq!(player_id_for_token(&tokens, "ptk-bbb"), None);
+        assert_eq!(player_id_for_token(&tokens, ""), None);
+        assert!(!tokens_match("ptk-aa", "PTK-AA"));
+    }
 }
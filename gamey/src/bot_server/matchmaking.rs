@@ -1,27 +1,64 @@
 use super::{
+    clock::{GameClock, TimeControl},
+    config::{EnvLookup, deserialize_secs, env_secs},
     error::ErrorResponse,
     games::{
         ensure_user_id_is_available_for_new_game, normalize_user_id_for_tracking,
         register_active_game_for_session_users,
     },
     state::{
-        AppState, GameSession, MatchmakingQueueEntry, MatchmakingState, MatchmakingTicketStatus,
+        AppState, BotTurnStatus, GameSession, MatchmakingQueueEntry, MatchmakingState,
+        MatchmakingTicketStatus, TicketHeartbeat,
     },
     version::check_api_version,
 };
-use crate::GameY;
+use crate::{GameY, GameYError};
 use axum::{
     Json,
     extract::{Path, State},
     http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
 };
+use futures_util::{Stream, stream};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
 use std::time::{Duration, Instant};
+use tokio::sync::watch;
 
-const DEFAULT_BOARD_SIZE: u32 = 7;
 const DEFAULT_POLL_AFTER_MS: u64 = 1_000;
-const MATCHMAKING_TICK_MS: u64 = 300;
+
+/// How often an open ticket event stream counts as a heartbeat, well inside
+/// the heartbeat timeout.
+const TICKET_EVENTS_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(15);
+const DEFAULT_RESOLVED_TICKET_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Rating given to players whose rating is not known.
+pub const DEFAULT_RATING: f64 = 1500.0;
+/// Rating gap accepted as soon as a ticket is enqueued.
+pub const INITIAL_RATING_GAP: f64 = 100.0;
+/// Extra rating gap accepted for every second a ticket has waited.
+pub const RATING_GAP_WIDENING_PER_SEC: f64 = 25.0;
+
+/// Looks up the rating of a player entering the matchmaking queue.
+///
+/// Used when the enqueue request does not carry a rating. Implementations can
+/// read ratings from the users service or any other store.
+pub trait RatingProvider: Send + Sync {
+    /// Returns the rating of `user_id`, or `None` if it is unknown.
+    fn rating_for(&self, user_id: Option<&str>) -> Option<f64>;
+}
+
+/// Provider that knows no ratings, so every player gets [`DEFAULT_RATING`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultRatingProvider;
+
+impl RatingProvider for DefaultRatingProvider {
+    fn rating_for(&self, _user_id: Option<&str>) -> Option<f64> {
+        None
+    }
+}
 
 #[derive(Deserialize)]
 pub struct ApiVersionParams {
@@ -36,8 +73,21 @@ pub struct TicketParams {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EnqueueRequest {
-    #[serde(default = "default_board_size")]
-    pub size: u32,
+    /// Board size. Defaults to the server's matchmaking `default_board_size`.
+    #[serde(default)]
+    pub size: Option<u32>,
+    /// Rating of the player. When missing it is looked up through the
+    /// server's [`RatingProvider`].
+    #[serde(default)]
+    pub rating: Option<f64>,
+    /// Whether the player accepts spectators. The matched game allows them
+    /// only if both players do.
+    #[serde(default = "default_allow_spectators")]
+    pub allow_spectators: bool,
+    /// Clock for the matched game. Only tickets asking for the same time
+    /// control are paired.
+    #[serde(default)]
+    pub time_control: Option<TimeControl>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -46,6 +96,8 @@ pub enum MatchmakingStatus {
     Waiting,
     Matched,
     Cancelled,
+    /// The client stopped polling the ticket before an opponent was found.
+    Expired,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -58,13 +110,74 @@ pub struct TicketResponse {
     pub game_id: Option<String>,
     pub player_id: Option<u32>,
     pub player_token: Option<String>,
+    /// Rating difference between the two players, once matched.
+    pub rating_gap: Option<f64>,
+}
+
+/// How long matchmaking tickets are kept.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TicketExpiryPolicy {
+    /// Time a waiting ticket stays queued without being polled.
+    #[serde(
+        rename = "heartbeat_timeout_secs",
+        deserialize_with = "deserialize_secs"
+    )]
+    pub heartbeat_timeout: Duration,
+    /// Time matched, cancelled and expired tickets can still be read.
+    #[serde(
+        rename = "resolved_ticket_ttl_secs",
+        deserialize_with = "deserialize_secs"
+    )]
+    pub resolved_ticket_ttl: Duration,
+}
+
+impl Default for TicketExpiryPolicy {
+    fn default() -> Self {
+        Self {
+            heartbeat_timeout: DEFAULT_HEARTBEAT_TIMEOUT,
+            resolved_ticket_ttl: DEFAULT_RESOLVED_TICKET_TTL,
+        }
+    }
 }
 
+impl TicketExpiryPolicy {
+    /// Overrides the policy with the environment variables that are set.
+    ///
+    /// - `GAMEY_MATCHMAKING_HEARTBEAT_TIMEOUT_SECS`
+    /// - `GAMEY_MATCHMAKING_RESOLVED_TICKET_TTL_SECS`
+    pub fn apply_env(&mut self, env: EnvLookup<'_>) -> Result<(), GameYError> {
+        if let Some(timeout) = env_secs(env, "GAMEY_MATCHMAKING_HEARTBEAT_TIMEOUT_SECS")? {
+            self.heartbeat_timeout = timeout;
+        }
+        if let Some(ttl) = env_secs(env, "GAMEY_MATCHMAKING_RESOLVED_TICKET_TTL_SECS")? {
+            self.resolved_ticket_ttl = ttl;
+        }
+        Ok(())
+    }
+}
+
+/// Spawns the background task that expires tickets and pairs waiting ones,
+/// using the matchmaking settings of the server configuration.
 pub fn start_matchmaking_worker(state: AppState) {
+    let config = state.config().matchmaking.clone();
+    let policy = config.tickets;
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(Duration::from_millis(MATCHMAKING_TICK_MS));
+        let mut interval = tokio::time::interval(config.tick);
         loop {
             interval.tick().await;
+            let sweep = {
+                let matchmaking = state.matchmaking();
+                let mut guard = matchmaking.write().await;
+                sweep_tickets(&mut guard, &policy, Instant::now())
+            };
+            for ticket_id in &sweep.expired {
+                state.metrics().inc_matchmaking_expired();
+                state.ticket_events().notify(ticket_id);
+            }
+            for ticket_id in &sweep.removed {
+                state.ticket_events().close(ticket_id);
+            }
             if let Err(err) = process_once(&state).await {
                 tracing::warn!("matchmaking worker error: {}", err);
             }
@@ -80,15 +193,36 @@ pub async fn enqueue(
 ) -> Result<Json<TicketResponse>, ErrorResponse> {
     check_api_version(&params.api_version)?;
 
-    if request.size == 0 {
+    let size = request
+        .size
+        .unwrap_or(state.config().matchmaking.default_board_size);
+    if size == 0 {
         return Err(error_response(
             "Board size must be >= 1",
             Some(params.api_version),
         ));
     }
 
+    if request.rating.is_some_and(|rating| !rating.is_finite()) {
+        return Err(error_response(
+            "Rating must be a finite number",
+            Some(params.api_version),
+        ));
+    }
+
+    if let Some(Err(message)) = request.time_control.map(|control| control.validate()) {
+        return Err(error_response(
+            &format!("Invalid time control: {}", message),
+            Some(params.api_version),
+        ));
+    }
+
     let ticket_id = state.new_ticket_id();
     let user_id = read_header_string(&headers, "x-user-id");
+    let rating = request
+        .rating
+        .or_else(|| state.rating_provider().rating_for(user_id.as_deref()))
+        .unwrap_or(DEFAULT_RATING);
     let normalized_user_id = normalize_user_id_for_tracking(user_id.as_deref());
 
     ensure_user_id_is_available_for_new_game(&state, user_id.as_deref(), &params.api_version)
@@ -110,17 +244,23 @@ pub async fn enqueue(
         ));
     }
 
+    let enqueued_at = Instant::now();
     guard.queue.push_back(MatchmakingQueueEntry {
         ticket_id: ticket_id.clone(),
-        size: request.size,
+        size,
         user_id: user_id.clone(),
+        rating,
+        enqueued_at,
+        allow_spectators: request.allow_spectators,
+        time_control: request.time_control,
     });
     guard.tickets.insert(
         ticket_id.clone(),
         MatchmakingTicketStatus::Waiting {
-            size: request.size,
+            size,
             user_id,
-            enqueued_at: Instant::now(),
+            enqueued_at,
+            last_polled_at: TicketHeartbeat::new(enqueued_at),
         },
     );
     state.metrics().inc_matchmaking_enqueued();
@@ -135,6 +275,7 @@ pub async fn enqueue(
         game_id: None,
         player_id: None,
         player_token: None,
+        rating_gap: None,
     }))
 }
 
@@ -146,51 +287,166 @@ pub async fn get_ticket(
 
     let matchmaking = state.matchmaking();
     let guard = matchmaking.read().await;
-    let ticket_status = guard.tickets.get(&params.ticket_id).ok_or_else(|| {
-        error_response(
-            &format!("Ticket not found: {}", params.ticket_id),
-            Some(params.api_version.clone()),
-        )
-    })?;
+    touch_ticket(&guard, &params.ticket_id);
+
+    build_ticket_response(&guard, &params.api_version, &params.ticket_id)
+        .map(Json)
+        .ok_or_else(|| {
+            error_response(
+                &format!("Ticket not found: {}", params.ticket_id),
+                Some(params.api_version),
+            )
+        })
+}
+
+/// Streams the status of a matchmaking ticket as server-sent events.
+///
+/// A `ticket` event with the [`TicketResponse`] is sent when the stream opens
+/// and whenever the ticket is matched, cancelled or expires. The stream ends
+/// after the first event that is not `waiting`. While the stream is open it
+/// counts as the heartbeat of the ticket, so the client does not need to poll.
+///
+/// # Route
+/// `GET /{api_version}/matchmaking/tickets/{ticket_id}/events`
+pub async fn ticket_events(
+    State(state): State<AppState>,
+    Path(params): Path<TicketParams>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+
+    let updates = {
+        let matchmaking = state.matchmaking();
+        let guard = matchmaking.read().await;
+        if !touch_ticket(&guard, &params.ticket_id) {
+            return Err(error_response(
+                &format!("Ticket not found: {}", params.ticket_id),
+                Some(params.api_version),
+            ));
+        }
+        state.ticket_events().subscribe(&params.ticket_id)
+    };
+
+    let heartbeat = tokio::time::interval_at(
+        tokio::time::Instant::now() + TICKET_EVENTS_HEARTBEAT_INTERVAL,
+        TICKET_EVENTS_HEARTBEAT_INTERVAL,
+    );
+    let events = TicketEventStream {
+        state,
+        params,
+        updates,
+        heartbeat,
+        started: false,
+        finished: false,
+    };
+
+    Ok(Sse::new(stream::unfold(events, next_ticket_event)).keep_alive(KeepAlive::default()))
+}
+
+/// State of one open ticket event stream.
+struct TicketEventStream {
+    state: AppState,
+    params: TicketParams,
+    updates: watch::Receiver<u64>,
+    heartbeat: tokio::time::Interval,
+    started: bool,
+    finished: bool,
+}
+
+async fn next_ticket_event(
+    mut events: TicketEventStream,
+) -> Option<(Result<Event, Infallible>, TicketEventStream)> {
+    if events.finished {
+        return None;
+    }
 
-    let response = match ticket_status {
+    if events.started {
+        loop {
+            tokio::select! {
+                changed = events.updates.changed() => {
+                    changed.ok()?;
+                    break;
+                }
+                _ = events.heartbeat.tick() => {
+                    let matchmaking = events.state.matchmaking();
+                    let guard = matchmaking.read().await;
+                    touch_ticket(&guard, &events.params.ticket_id);
+                }
+            }
+        }
+    }
+    events.started = true;
+
+    let response = {
+        let matchmaking = events.state.matchmaking();
+        let guard = matchmaking.read().await;
+        build_ticket_response(&guard, &events.params.api_version, &events.params.ticket_id)?
+    };
+    events.finished = response.status != MatchmakingStatus::Waiting;
+
+    let event = Event::default().event("ticket").json_data(&response).ok()?;
+    Some((Ok(event), events))
+}
+
+/// Records a heartbeat for a waiting ticket. Returns `false` if the ticket
+/// does not exist.
+fn touch_ticket(state: &MatchmakingState, ticket_id: &str) -> bool {
+    match state.tickets.get(ticket_id) {
+        Some(MatchmakingTicketStatus::Waiting { last_polled_at, .. }) => {
+            last_polled_at.record(Instant::now());
+            true
+        }
+        Some(_) => true,
+        None => false,
+    }
+}
+
+/// Builds the response describing the current status of a ticket.
+fn build_ticket_response(
+    state: &MatchmakingState,
+    api_version: &str,
+    ticket_id: &str,
+) -> Option<TicketResponse> {
+    let response = match state.tickets.get(ticket_id)? {
         MatchmakingTicketStatus::Waiting { .. } => TicketResponse {
-            api_version: params.api_version,
-            ticket_id: params.ticket_id.clone(),
+            api_version: api_version.to_string(),
+            ticket_id: ticket_id.to_string(),
             status: MatchmakingStatus::Waiting,
             poll_after_ms: Some(DEFAULT_POLL_AFTER_MS),
-            position: queue_position(&guard, &params.ticket_id),
+            position: queue_position(state, ticket_id),
             game_id: None,
             player_id: None,
             player_token: None,
+            rating_gap: None,
         },
         MatchmakingTicketStatus::Matched {
             game_id,
             player_id,
             player_token,
+            rating_gap,
+            ..
         } => TicketResponse {
-            api_version: params.api_version,
-            ticket_id: params.ticket_id.clone(),
+            api_version: api_version.to_string(),
+            ticket_id: ticket_id.to_string(),
             status: MatchmakingStatus::Matched,
             poll_after_ms: None,
             position: None,
             game_id: Some(game_id.clone()),
             player_id: Some(*player_id),
             player_token: Some(player_token.clone()),
+            rating_gap: Some(*rating_gap),
         },
-        MatchmakingTicketStatus::Cancelled => TicketResponse {
-            api_version: params.api_version,
-            ticket_id: params.ticket_id.clone(),
-            status: MatchmakingStatus::Cancelled,
-            poll_after_ms: None,
-            position: None,
-            game_id: None,
-            player_id: None,
-            player_token: None,
-        },
+        MatchmakingTicketStatus::Cancelled { .. } => closed_ticket_response(
+            api_version.to_string(),
+            ticket_id.to_string(),
+            MatchmakingStatus::Cancelled,
+        ),
+        MatchmakingTicketStatus::Expired { .. } => closed_ticket_response(
+            api_version.to_string(),
+            ticket_id.to_string(),
+            MatchmakingStatus::Expired,
+        ),
     };
-
-    Ok(Json(response))
+    Some(response)
 }
 
 pub async fn cancel_ticket(
@@ -208,13 +464,17 @@ pub async fn cancel_ticket(
         )
     })?;
 
-    match ticket_status {
+    let status = match ticket_status {
         MatchmakingTicketStatus::Waiting { .. } => {
-            *ticket_status = MatchmakingTicketStatus::Cancelled;
+            *ticket_status = MatchmakingTicketStatus::Cancelled {
+                cancelled_at: Instant::now(),
+            };
             guard
                 .queue
                 .retain(|entry| entry.ticket_id != params.ticket_id);
             state.metrics().inc_matchmaking_cancelled();
+            state.ticket_events().notify(&params.ticket_id);
+            MatchmakingStatus::Cancelled
         }
         MatchmakingTicketStatus::Matched { .. } => {
             return Err(error_response(
@@ -222,32 +482,88 @@ pub async fn cancel_ticket(
                 Some(params.api_version),
             ));
         }
-        MatchmakingTicketStatus::Cancelled => {}
-    }
+        MatchmakingTicketStatus::Cancelled { .. } => MatchmakingStatus::Cancelled,
+        MatchmakingTicketStatus::Expired { .. } => MatchmakingStatus::Expired,
+    };
 
-    Ok(Json(TicketResponse {
-        api_version: params.api_version,
-        ticket_id: params.ticket_id,
-        status: MatchmakingStatus::Cancelled,
+    Ok(Json(closed_ticket_response(
+        params.api_version,
+        params.ticket_id,
+        status,
+    )))
+}
+
+/// Response for a ticket that was cancelled or expired.
+fn closed_ticket_response(
+    api_version: String,
+    ticket_id: String,
+    status: MatchmakingStatus,
+) -> TicketResponse {
+    TicketResponse {
+        api_version,
+        ticket_id,
+        status,
         poll_after_ms: None,
         position: None,
         game_id: None,
         player_id: None,
         player_token: None,
-    }))
+        rating_gap: None,
+    }
+}
+
+/// Tickets changed by [`sweep_tickets`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TicketSweep {
+    /// Waiting tickets that expired.
+    pub expired: Vec<String>,
+    /// Resolved tickets that were forgotten.
+    pub removed: Vec<String>,
+}
+
+/// Expires waiting tickets whose client has not polled within the heartbeat
+/// timeout and forgets resolved tickets older than the retention period.
+pub fn sweep_tickets(
+    state: &mut MatchmakingState,
+    policy: &TicketExpiryPolicy,
+    now: Instant,
+) -> TicketSweep {
+    let mut sweep = TicketSweep::default();
+    for (ticket_id, status) in state.tickets.iter_mut() {
+        if let MatchmakingTicketStatus::Waiting { last_polled_at, .. } = status
+            && now.saturating_duration_since(last_polled_at.last()) >= policy.heartbeat_timeout
+        {
+            *status = MatchmakingTicketStatus::Expired { expired_at: now };
+            sweep.expired.push(ticket_id.clone());
+        }
+    }
+
+    state.tickets.retain(|ticket_id, status| {
+        let keep = status.resolved_at().is_none_or(|resolved_at| {
+            now.saturating_duration_since(resolved_at) < policy.resolved_ticket_ttl
+        });
+        if !keep {
+            sweep.removed.push(ticket_id.clone());
+        }
+        keep
+    });
+    let tickets = &state.tickets;
+    state
+        .queue
+        .retain(|entry| is_waiting_ticket(tickets, &entry.ticket_id));
+
+    sweep
 }
 
 async fn process_once(state: &AppState) -> Result<(), String> {
-    loop {
-        let pair = {
-            let matchmaking = state.matchmaking();
-            let mut guard = matchmaking.write().await;
-            take_next_pair(&mut guard)
-        };
+    let pairs = {
+        let matchmaking = state.matchmaking();
+        let mut guard = matchmaking.write().await;
+        take_pairs(&mut guard, Instant::now())
+    };
 
-        let Some((a, b)) = pair else {
-            break;
-        };
+    for (a, b) in pairs {
+        let rating_gap = (a.rating - b.rating).abs();
 
         let game_id = state.new_game_id();
         let player_a_token = state.new_player_token();
@@ -257,11 +573,14 @@ async fn process_once(state: &AppState) -> Result<(), String> {
             (1_u32, player_b_token.clone()),
         ]);
 
-        let games = state.games();
-        let mut games_guard = games.write().await;
+        let game = GameY::new(a.size);
+        let clock = a
+            .time_control
+            .map(|control| GameClock::new(control, game.next_player(), Instant::now()));
+
         let session = GameSession {
-            game: GameY::new(a.size),
-            bot_id: None,
+            game,
+            bots: [None, None],
             created_at: Instant::now(),
             turn_started_at: Some(Instant::now()),
             player_tokens: Some(player_tokens),
@@ -270,19 +589,28 @@ async fn process_once(state: &AppState) -> Result<(), String> {
             player1_user_id: b.user_id.clone(),
             stats_reported: false,
             completion_reason: None,
+            last_activity_at: Instant::now(),
+            finished_at: None,
+            allow_spectators: a.allow_spectators && b.allow_spectators,
+            clock,
+            bot_turn: BotTurnStatus::Idle,
         };
-        games_guard.insert(game_id.clone(), session.clone());
-        drop(games_guard);
+        state.insert_game(game_id.clone(), session.clone()).await;
+        state.persist_game(&game_id, &session);
         register_active_game_for_session_users(state, &game_id, &session).await;
 
         let matchmaking = state.matchmaking();
         let mut mm_guard = matchmaking.write().await;
+        state.ticket_events().notify(&a.ticket_id);
+        state.ticket_events().notify(&b.ticket_id);
         mm_guard.tickets.insert(
             a.ticket_id,
             MatchmakingTicketStatus::Matched {
                 game_id: game_id.clone(),
                 player_id: 0,
                 player_token: player_a_token,
+                rating_gap,
+                matched_at: Instant::now(),
             },
         );
         mm_guard.tickets.insert(
@@ -291,6 +619,8 @@ async fn process_once(state: &AppState) -> Result<(), String> {
                 game_id,
                 player_id: 1,
                 player_token: player_b_token,
+                rating_gap,
+                matched_at: Instant::now(),
             },
         );
     }
@@ -298,30 +628,117 @@ async fn process_once(state: &AppState) -> Result<(), String> {
     Ok(())
 }
 
-fn take_next_pair(
+/// Tickets that can be paired with each other.
+///
+/// Only tickets in the same bucket are ever matched, so a ticket waiting for an
+/// uncommon board size or time control does not hold back the rest of the
+/// queue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct MatchmakingBucket {
+    size: u32,
+    time_control: Option<TimeControl>,
+}
+
+impl MatchmakingBucket {
+    fn of(entry: &MatchmakingQueueEntry) -> Self {
+        Self {
+            size: entry.size,
+            time_control: entry.time_control,
+        }
+    }
+}
+
+/// Takes every pair of tickets that can be matched right now from the queue.
+///
+/// Waiting tickets are grouped by [`MatchmakingBucket`]. Within a bucket they
+/// are considered oldest first, and each one is paired with the remaining
+/// ticket whose rating is closest, as long as the gap fits in the search
+/// window of both tickets (see [`allowed_rating_gap`]). The pairs are returned
+/// in the order their oldest ticket joined the queue, and unmatched tickets
+/// keep their place.
+fn take_pairs(
     state: &mut MatchmakingState,
-) -> Option<(MatchmakingQueueEntry, MatchmakingQueueEntry)> {
-    while let Some(first) = state.queue.pop_front() {
-        if !is_waiting_ticket(&state.tickets, &first.ticket_id) {
+    now: Instant,
+) -> Vec<(MatchmakingQueueEntry, MatchmakingQueueEntry)> {
+    let tickets = &state.tickets;
+    state
+        .queue
+        .retain(|entry| is_waiting_ticket(tickets, &entry.ticket_id));
+
+    let mut buckets: HashMap<MatchmakingBucket, Vec<usize>> = HashMap::new();
+    for (idx, entry) in state.queue.iter().enumerate() {
+        buckets
+            .entry(MatchmakingBucket::of(entry))
+            .or_default()
+            .push(idx);
+    }
+
+    let mut matched_indices = Vec::new();
+    for indices in buckets.values() {
+        matched_indices.extend(pair_bucket(&state.queue, indices, now));
+    }
+    matched_indices.sort_unstable();
+
+    let mut entries: Vec<Option<MatchmakingQueueEntry>> = state.queue.drain(..).map(Some).collect();
+    let pairs = matched_indices
+        .into_iter()
+        .filter_map(|(first, second)| Some((entries[first].take()?, entries[second].take()?)))
+        .collect();
+    state.queue = entries.into_iter().flatten().collect();
+    pairs
+}
+
+/// Pairs the tickets at `indices` of `queue`, which belong to one bucket and
+/// are ordered by wait time. Returns the queue indices of each pair, oldest
+/// ticket first.
+fn pair_bucket(
+    queue: &VecDeque<MatchmakingQueueEntry>,
+    indices: &[usize],
+    now: Instant,
+) -> Vec<(usize, usize)> {
+    let window = |entry: &MatchmakingQueueEntry| {
+        allowed_rating_gap(now.saturating_duration_since(entry.enqueued_at))
+    };
+    let mut taken = vec![false; indices.len()];
+    let mut pairs = Vec::new();
+
+    for (pos, &first_idx) in indices.iter().enumerate() {
+        if taken[pos] {
             continue;
         }
+        let first = &queue[first_idx];
+        let first_window = window(first);
 
-        let second_idx = state.queue.iter().position(|candidate| {
-            candidate.size == first.size
-                && is_waiting_ticket(&state.tickets, &candidate.ticket_id)
-                && !share_matchmaking_identity(&first, candidate)
-        });
+        let best = indices
+            .iter()
+            .enumerate()
+            .skip(pos + 1)
+            .filter(|(candidate_pos, _)| !taken[*candidate_pos])
+            .filter_map(|(candidate_pos, &candidate_idx)| {
+                let candidate = &queue[candidate_idx];
+                let gap = (first.rating - candidate.rating).abs();
+                (!share_matchmaking_identity(first, candidate)
+                    && gap <= first_window.min(window(candidate)))
+                .then_some((candidate_pos, gap))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
 
-        if let Some(idx) = second_idx
-            && let Some(second) = state.queue.remove(idx)
-        {
-            return Some((first, second));
+        if let Some((candidate_pos, _)) = best {
+            taken[pos] = true;
+            taken[candidate_pos] = true;
+            pairs.push((first_idx, indices[candidate_pos]));
         }
-
-        state.queue.push_front(first);
-        return None;
     }
-    None
+    pairs
+}
+
+/// Largest rating gap a ticket accepts after waiting for `waited`.
+///
+/// The window starts at [`INITIAL_RATING_GAP`] and grows by
+/// [`RATING_GAP_WIDENING_PER_SEC`] every second, so every ticket is
+/// eventually matched if an opponent for its board size is waiting.
+pub fn allowed_rating_gap(waited: Duration) -> f64 {
+    INITIAL_RATING_GAP + RATING_GAP_WIDENING_PER_SEC * waited.as_secs_f64()
 }
 
 fn is_waiting_ticket(tickets: &HashMap<String, MatchmakingTicketStatus>, ticket_id: &str) -> bool {
@@ -366,8 +783,8 @@ fn queue_position(state: &MatchmakingState, ticket_id: &str) -> Option<usize> {
         .map(|idx| idx + 1)
 }
 
-fn default_board_size() -> u32 {
-    DEFAULT_BOARD_SIZE
+fn default_allow_spectators() -> bool {
+    true
 }
 
 fn read_header_string(headers: &HeaderMap, name: &str) -> Option<String> {
@@ -388,17 +805,25 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_take_next_pair_fifo_same_size() {
+    fn test_take_pairs_fifo_same_size() {
         let mut state = MatchmakingState::default();
         state.queue.push_back(MatchmakingQueueEntry {
             ticket_id: "ticket-1".to_string(),
             size: 7,
             user_id: None,
+            rating: DEFAULT_RATING,
+            enqueued_at: Instant::now(),
+            allow_spectators: true,
+            time_control: None,
         });
         state.queue.push_back(MatchmakingQueueEntry {
             ticket_id: "ticket-2".to_string(),
             size: 7,
             user_id: None,
+            rating: DEFAULT_RATING,
+            enqueued_at: Instant::now(),
+            allow_spectators: true,
+            time_control: None,
         });
         state.tickets.insert(
             "ticket-1".to_string(),
@@ -406,6 +831,7 @@ mod tests {
                 size: 7,
                 user_id: None,
                 enqueued_at: Instant::now(),
+                last_polled_at: TicketHeartbeat::new(Instant::now()),
             },
         );
         state.tickets.insert(
@@ -414,31 +840,44 @@ mod tests {
                 size: 7,
                 user_id: None,
                 enqueued_at: Instant::now(),
+                last_polled_at: TicketHeartbeat::new(Instant::now()),
             },
         );
 
-        let pair = take_next_pair(&mut state).unwrap();
+        let pair = take_pairs(&mut state, Instant::now()).remove(0);
         assert_eq!(pair.0.ticket_id, "ticket-1");
         assert_eq!(pair.1.ticket_id, "ticket-2");
     }
 
     #[test]
-    fn test_take_next_pair_skips_same_identity_and_uses_next_distinct_ticket() {
+    fn test_take_pairs_skips_same_identity_and_uses_next_distinct_ticket() {
         let mut state = MatchmakingState::default();
         state.queue.push_back(MatchmakingQueueEntry {
             ticket_id: "ticket-1".to_string(),
             size: 7,
             user_id: Some("guest-a".to_string()),
+            rating: DEFAULT_RATING,
+            enqueued_at: Instant::now(),
+            allow_spectators: true,
+            time_control: None,
         });
         state.queue.push_back(MatchmakingQueueEntry {
             ticket_id: "ticket-2".to_string(),
             size: 7,
             user_id: Some("guest-a".to_string()),
+            rating: DEFAULT_RATING,
+            enqueued_at: Instant::now(),
+            allow_spectators: true,
+            time_control: None,
         });
         state.queue.push_back(MatchmakingQueueEntry {
             ticket_id: "ticket-3".to_string(),
             size: 7,
             user_id: Some("guest-b".to_string()),
+            rating: DEFAULT_RATING,
+            enqueued_at: Instant::now(),
+            allow_spectators: true,
+            time_control: None,
         });
         state.tickets.insert(
             "ticket-1".to_string(),
@@ -446,6 +885,7 @@ mod tests {
                 size: 7,
                 user_id: Some("guest-a".to_string()),
                 enqueued_at: Instant::now(),
+                last_polled_at: TicketHeartbeat::new(Instant::now()),
             },
         );
         state.tickets.insert(
@@ -454,6 +894,7 @@ mod tests {
                 size: 7,
                 user_id: Some("guest-a".to_string()),
                 enqueued_at: Instant::now(),
+                last_polled_at: TicketHeartbeat::new(Instant::now()),
             },
         );
         state.tickets.insert(
@@ -462,10 +903,11 @@ mod tests {
                 size: 7,
                 user_id: Some("guest-b".to_string()),
                 enqueued_at: Instant::now(),
+                last_polled_at: TicketHeartbeat::new(Instant::now()),
             },
         );
 
-        let pair = take_next_pair(&mut state).unwrap();
+        let pair = take_pairs(&mut state, Instant::now()).remove(0);
         assert_eq!(pair.0.ticket_id, "ticket-1");
         assert_eq!(pair.1.ticket_id, "ticket-3");
     }
@@ -479,10 +921,372 @@ mod tests {
                 size: 7,
                 user_id: Some("Guest-A".to_string()),
                 enqueued_at: Instant::now(),
+                last_polled_at: TicketHeartbeat::new(Instant::now()),
             },
         );
 
         let existing_ticket_id = find_waiting_ticket_id_for_user_id(&state, "guest-a");
         assert_eq!(existing_ticket_id, Some("ticket-2"));
     }
+
+    fn waiting_entry(
+        state: &mut MatchmakingState,
+        ticket_id: &str,
+        rating: f64,
+        enqueued_at: Instant,
+    ) {
+        sized_waiting_entry(state, ticket_id, 7, rating, enqueued_at);
+    }
+
+    fn sized_waiting_entry(
+        state: &mut MatchmakingState,
+        ticket_id: &str,
+        size: u32,
+        rating: f64,
+        enqueued_at: Instant,
+    ) {
+        state.queue.push_back(MatchmakingQueueEntry {
+            ticket_id: ticket_id.to_string(),
+            size,
+            user_id: None,
+            rating,
+            enqueued_at,
+            allow_spectators: true,
+            time_control: None,
+        });
+        state.tickets.insert(
+            ticket_id.to_string(),
+            MatchmakingTicketStatus::Waiting {
+                size,
+                user_id: None,
+                enqueued_at,
+                last_polled_at: TicketHeartbeat::new(enqueued_at),
+            },
+        );
+    }
+
+    fn ticket_ids(pairs: &[(MatchmakingQueueEntry, MatchmakingQueueEntry)]) -> Vec<(&str, &str)> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.ticket_id.as_str(), b.ticket_id.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_take_pairs_does_not_block_on_unmatched_size() {
+        let now = Instant::now();
+        let mut state = MatchmakingState::default();
+        sized_waiting_entry(&mut state, "ticket-9", 9, DEFAULT_RATING, now);
+        sized_waiting_entry(&mut state, "ticket-7a", 7, DEFAULT_RATING, now);
+        sized_waiting_entry(&mut state, "ticket-7b", 7, DEFAULT_RATING, now);
+
+        let pairs = take_pairs(&mut state, now);
+
+        assert_eq!(ticket_ids(&pairs), vec![("ticket-7a", "ticket-7b")]);
+        assert_eq!(queue_position(&state, "ticket-9"), Some(1));
+        assert_eq!(state.queue.len(), 1);
+    }
+
+    #[test]
+    fn test_take_pairs_matches_every_bucket_in_wait_order() {
+        let now = Instant::now();
+        let mut state = MatchmakingState::default();
+        sized_waiting_entry(&mut state, "ticket-1", 9, DEFAULT_RATING, now);
+        sized_waiting_entry(&mut state, "ticket-2", 7, DEFAULT_RATING, now);
+        sized_waiting_entry(&mut state, "ticket-3", 5, DEFAULT_RATING, now);
+        sized_waiting_entry(&mut state, "ticket-4", 7, DEFAULT_RATING, now);
+        sized_waiting_entry(&mut state, "ticket-5", 9, DEFAULT_RATING, now);
+        sized_waiting_entry(&mut state, "ticket-6", 7, DEFAULT_RATING, now);
+        sized_waiting_entry(&mut state, "ticket-7", 7, DEFAULT_RATING, now);
+
+        let pairs = take_pairs(&mut state, now);
+
+        assert_eq!(
+            ticket_ids(&pairs),
+            vec![
+                ("ticket-1", "ticket-5"),
+                ("ticket-2", "ticket-4"),
+                ("ticket-6", "ticket-7"),
+            ]
+        );
+        assert_eq!(queue_position(&state, "ticket-3"), Some(1));
+        assert_eq!(state.queue.len(), 1);
+    }
+
+    #[test]
+    fn test_take_pairs_only_pairs_tickets_with_the_same_time_control() {
+        let now = Instant::now();
+        let blitz = TimeControl::Fischer {
+            base_ms: 180_000,
+            increment_ms: 2_000,
+        };
+        let mut state = MatchmakingState::default();
+        waiting_entry(&mut state, "ticket-untimed", DEFAULT_RATING, now);
+        waiting_entry(&mut state, "ticket-blitz-a", DEFAULT_RATING, now);
+        waiting_entry(&mut state, "ticket-blitz-b", DEFAULT_RATING, now);
+        state.queue[1].time_control = Some(blitz);
+        state.queue[2].time_control = Some(blitz);
+
+        let pairs = take_pairs(&mut state, now);
+
+        assert_eq!(
+            ticket_ids(&pairs),
+            vec![("ticket-blitz-a", "ticket-blitz-b")]
+        );
+        assert_eq!(pairs[0].0.time_control, Some(blitz));
+        assert_eq!(queue_position(&state, "ticket-untimed"), Some(1));
+    }
+
+    #[test]
+    fn test_take_pairs_drops_cancelled_tickets_from_queue() {
+        let now = Instant::now();
+        let mut state = MatchmakingState::default();
+        waiting_entry(&mut state, "ticket-1", DEFAULT_RATING, now);
+        waiting_entry(&mut state, "ticket-2", DEFAULT_RATING, now);
+        waiting_entry(&mut state, "ticket-3", DEFAULT_RATING, now);
+        state.tickets.insert(
+            "ticket-2".to_string(),
+            MatchmakingTicketStatus::Cancelled { cancelled_at: now },
+        );
+
+        let pairs = take_pairs(&mut state, now);
+
+        assert_eq!(ticket_ids(&pairs), vec![("ticket-1", "ticket-3")]);
+        assert!(state.queue.is_empty());
+    }
+
+    #[test]
+    fn test_take_pairs_prefers_closest_rating() {
+        let now = Instant::now();
+        let mut state = MatchmakingState::default();
+        waiting_entry(&mut state, "ticket-1", 1500.0, now);
+        waiting_entry(&mut state, "ticket-2", 1580.0, now);
+        waiting_entry(&mut state, "ticket-3", 1510.0, now);
+
+        let pair = take_pairs(&mut state, now).remove(0);
+        assert_eq!(pair.0.ticket_id, "ticket-1");
+        assert_eq!(pair.1.ticket_id, "ticket-3");
+        assert_eq!(state.queue.len(), 1);
+    }
+
+    #[test]
+    fn test_take_pairs_waits_for_window_to_widen() {
+        let now = Instant::now();
+        let mut state = MatchmakingState::default();
+        waiting_entry(&mut state, "ticket-new", 1000.0, now);
+        waiting_entry(&mut state, "ticket-veteran", 1400.0, now);
+
+        assert!(take_pairs(&mut state, now).is_empty());
+        assert_eq!(state.queue.len(), 2);
+
+        let later = now + Duration::from_secs(20);
+        let pair = take_pairs(&mut state, later).remove(0);
+        assert_eq!(pair.0.ticket_id, "ticket-new");
+        assert_eq!(pair.1.ticket_id, "ticket-veteran");
+    }
+
+    #[test]
+    fn test_take_pairs_requires_gap_within_both_windows() {
+        let now = Instant::now();
+        let mut state = MatchmakingState::default();
+        waiting_entry(&mut state, "ticket-old", 1000.0, now);
+        waiting_entry(
+            &mut state,
+            "ticket-new",
+            1400.0,
+            now + Duration::from_secs(20),
+        );
+
+        assert!(take_pairs(&mut state, now + Duration::from_secs(20)).is_empty());
+        assert!(!take_pairs(&mut state, now + Duration::from_secs(40)).is_empty());
+    }
+
+    #[test]
+    fn test_take_pairs_skips_blocked_ticket_and_pairs_later_ones() {
+        let now = Instant::now();
+        let mut state = MatchmakingState::default();
+        waiting_entry(&mut state, "ticket-1", 2500.0, now);
+        waiting_entry(&mut state, "ticket-2", 1500.0, now);
+        waiting_entry(&mut state, "ticket-3", 1550.0, now);
+
+        let pair = take_pairs(&mut state, now).remove(0);
+        assert_eq!(pair.0.ticket_id, "ticket-2");
+        assert_eq!(pair.1.ticket_id, "ticket-3");
+        assert_eq!(queue_position(&state, "ticket-1"), Some(1));
+    }
+
+    #[test]
+    fn test_allowed_rating_gap_widens_with_wait() {
+        assert_eq!(allowed_rating_gap(Duration::ZERO), INITIAL_RATING_GAP);
+        assert_eq!(
+            allowed_rating_gap(Duration::from_secs(4)),
+            INITIAL_RATING_GAP + 4.0 * RATING_GAP_WIDENING_PER_SEC
+        );
+    }
+
+    #[test]
+    fn test_default_rating_provider_knows_no_ratings() {
+        assert_eq!(DefaultRatingProvider.rating_for(Some("alice")), None);
+    }
+
+    struct FixedRatings;
+
+    impl RatingProvider for FixedRatings {
+        fn rating_for(&self, user_id: Option<&str>) -> Option<f64> {
+            match user_id {
+                Some("alice") => Some(1620.0),
+                _ => None,
+            }
+        }
+    }
+
+    async fn enqueue_as(state: &AppState, user_id: &str, rating: Option<f64>) -> TicketResponse {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-user-id", user_id.parse().unwrap());
+        let Json(response) = enqueue(
+            State(state.clone()),
+            Path(ApiVersionParams {
+                api_version: "v1".to_string(),
+            }),
+            headers,
+            Json(EnqueueRequest {
+                size: Some(7),
+                rating,
+                allow_spectators: true,
+                time_control: None,
+            }),
+        )
+        .await
+        .unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_enqueue_uses_rating_provider_and_match_reports_gap() {
+        let state = AppState::new(crate::YBotRegistry::new())
+            .with_rating_provider(std::sync::Arc::new(FixedRatings));
+
+        let alice = enqueue_as(&state, "alice", None).await;
+        let bob = enqueue_as(&state, "bob", Some(1560.0)).await;
+        {
+            let matchmaking = state.matchmaking();
+            let guard = matchmaking.read().await;
+            let ratings: Vec<f64> = guard.queue.iter().map(|entry| entry.rating).collect();
+            assert_eq!(ratings, vec![1620.0, 1560.0]);
+        }
+
+        process_once(&state).await.unwrap();
+
+        for ticket in [alice, bob] {
+            let Json(response) = get_ticket(
+                State(state.clone()),
+                Path(TicketParams {
+                    api_version: "v1".to_string(),
+                    ticket_id: ticket.ticket_id,
+                }),
+            )
+            .await
+            .unwrap();
+            assert_eq!(response.status, MatchmakingStatus::Matched);
+            assert_eq!(response.rating_gap, Some(60.0));
+        }
+    }
+
+    fn expiry_policy() -> TicketExpiryPolicy {
+        TicketExpiryPolicy {
+            heartbeat_timeout: Duration::from_secs(10),
+            resolved_ticket_ttl: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn test_sweep_tickets_expires_waiting_tickets_without_heartbeat() {
+        let now = Instant::now();
+        let mut state = MatchmakingState::default();
+        waiting_entry(&mut state, "ticket-ghost", DEFAULT_RATING, now);
+        waiting_entry(&mut state, "ticket-alive", DEFAULT_RATING, now);
+        if let Some(MatchmakingTicketStatus::Waiting { last_polled_at, .. }) =
+            state.tickets.get("ticket-alive")
+        {
+            last_polled_at.record(now + Duration::from_secs(8));
+        }
+
+        let sweep = sweep_tickets(&mut state, &expiry_policy(), now + Duration::from_secs(12));
+
+        assert_eq!(sweep.expired, vec!["ticket-ghost".to_string()]);
+        assert!(matches!(
+            state.tickets.get("ticket-ghost"),
+            Some(MatchmakingTicketStatus::Expired { .. })
+        ));
+        assert_eq!(queue_position(&state, "ticket-alive"), Some(1));
+        assert_eq!(state.queue.len(), 1);
+    }
+
+    #[test]
+    fn test_sweep_tickets_forgets_resolved_tickets_after_ttl() {
+        let now = Instant::now();
+        let mut state = MatchmakingState::default();
+        state.tickets.insert(
+            "ticket-cancelled".to_string(),
+            MatchmakingTicketStatus::Cancelled { cancelled_at: now },
+        );
+        state.tickets.insert(
+            "ticket-expired".to_string(),
+            MatchmakingTicketStatus::Expired {
+                expired_at: now + Duration::from_secs(30),
+            },
+        );
+        state.tickets.insert(
+            "ticket-matched".to_string(),
+            MatchmakingTicketStatus::Matched {
+                game_id: "game-1".to_string(),
+                player_id: 0,
+                player_token: "ptk-1".to_string(),
+                rating_gap: 0.0,
+                matched_at: now,
+            },
+        );
+
+        let sweep = sweep_tickets(&mut state, &expiry_policy(), now + Duration::from_secs(61));
+
+        assert!(sweep.expired.is_empty());
+        assert_eq!(sweep.removed.len(), 2);
+        let remaining: Vec<&str> = state.tickets.keys().map(String::as_str).collect();
+        assert_eq!(remaining, vec!["ticket-expired"]);
+    }
+
+    #[tokio::test]
+    async fn test_polling_keeps_ticket_alive_and_expired_ticket_reports_status() {
+        let state = AppState::new(crate::YBotRegistry::new());
+        let ticket = enqueue_as(&state, "alice", None).await;
+        let params = || TicketParams {
+            api_version: "v1".to_string(),
+            ticket_id: ticket.ticket_id.clone(),
+        };
+
+        let later = Instant::now() + Duration::from_secs(9);
+        let Json(polled) = get_ticket(State(state.clone()), Path(params()))
+            .await
+            .unwrap();
+        assert_eq!(polled.status, MatchmakingStatus::Waiting);
+        {
+            let matchmaking = state.matchmaking();
+            let mut guard = matchmaking.write().await;
+            let sweep = sweep_tickets(&mut guard, &expiry_policy(), later);
+            assert!(sweep.expired.is_empty());
+            let sweep = sweep_tickets(&mut guard, &expiry_policy(), later + Duration::from_secs(5));
+            assert_eq!(sweep.expired.len(), 1);
+        }
+
+        let Json(expired) = get_ticket(State(state.clone()), Path(params()))
+            .await
+            .unwrap();
+        assert_eq!(expired.status, MatchmakingStatus::Expired);
+        assert_eq!(expired.poll_after_ms, None);
+
+        let Json(cancelled) = cancel_ticket(State(state.clone()), Path(params()))
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status, MatchmakingStatus::Expired);
+    }
 }
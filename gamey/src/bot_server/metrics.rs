@@ -1,3 +1,4 @@
+use super::retention::EvictionReason;
 use super::state::{AppState, MatchmakingTicketStatus};
 use axum::{
     extract::{MatchedPath, Request, State},
@@ -31,17 +32,29 @@ struct HttpMetricValue {
     duration_sum_seconds: f64,
 }
 
+#[derive(Clone, Debug, Default)]
+struct BotMoveMetricValue {
+    count: u64,
+    queue_wait_sum_seconds: f64,
+    think_time_sum_seconds: f64,
+}
+
 pub struct AppMetrics {
     started_at: Instant,
     http_metrics: Mutex<HashMap<HttpMetricKey, HttpMetricValue>>,
+    bot_move_metrics: Mutex<HashMap<String, BotMoveMetricValue>>,
     games_created_total: AtomicU64,
     moves_played_total: AtomicU64,
     resignations_total: AtomicU64,
     turn_passes_total: AtomicU64,
     matchmaking_enqueued_total: AtomicU64,
     matchmaking_cancelled_total: AtomicU64,
+    matchmaking_expired_total: AtomicU64,
     stats_report_attempts_total: AtomicU64,
     stats_report_failures_total: AtomicU64,
+    games_evicted_finished_total: AtomicU64,
+    games_evicted_idle_total: AtomicU64,
+    games_evicted_capacity_total: AtomicU64,
 }
 
 impl AppMetrics {
@@ -49,14 +62,19 @@ impl AppMetrics {
         Self {
             started_at: Instant::now(),
             http_metrics: Mutex::new(HashMap::new()),
+            bot_move_metrics: Mutex::new(HashMap::new()),
             games_created_total: AtomicU64::new(0),
             moves_played_total: AtomicU64::new(0),
             resignations_total: AtomicU64::new(0),
             turn_passes_total: AtomicU64::new(0),
             matchmaking_enqueued_total: AtomicU64::new(0),
             matchmaking_cancelled_total: AtomicU64::new(0),
+            matchmaking_expired_total: AtomicU64::new(0),
             stats_report_attempts_total: AtomicU64::new(0),
             stats_report_failures_total: AtomicU64::new(0),
+            games_evicted_finished_total: AtomicU64::new(0),
+            games_evicted_idle_total: AtomicU64::new(0),
+            games_evicted_capacity_total: AtomicU64::new(0),
         }
     }
 
@@ -82,6 +100,19 @@ impl AppMetrics {
         entry.duration_sum_seconds += duration.as_secs_f64();
     }
 
+    /// Records a bot move that waited `queue_wait` for a worker and took
+    /// `think_time` to compute.
+    pub fn observe_bot_move(&self, bot_id: &str, queue_wait: Duration, think_time: Duration) {
+        let mut guard = self
+            .bot_move_metrics
+            .lock()
+            .expect("bot move metrics mutex should not be poisoned");
+        let entry = guard.entry(bot_id.to_string()).or_default();
+        entry.count += 1;
+        entry.queue_wait_sum_seconds += queue_wait.as_secs_f64();
+        entry.think_time_sum_seconds += think_time.as_secs_f64();
+    }
+
     pub fn inc_games_created(&self) {
         self.games_created_total.fetch_add(1, Ordering::Relaxed);
     }
@@ -107,6 +138,11 @@ impl AppMetrics {
             .fetch_add(1, Ordering::Relaxed);
     }
 
+    pub fn inc_matchmaking_expired(&self) {
+        self.matchmaking_expired_total
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
     pub fn inc_stats_report_attempts(&self) {
         self.stats_report_attempts_total
             .fetch_add(1, Ordering::Relaxed);
@@ -117,6 +153,15 @@ impl AppMetrics {
             .fetch_add(1, Ordering::Relaxed);
     }
 
+    pub fn inc_games_evicted(&self, reason: EvictionReason) {
+        let counter = match reason {
+            EvictionReason::Finished => &self.games_evicted_finished_total,
+            EvictionReason::Idle => &self.games_evicted_idle_total,
+            EvictionReason::Capacity => &self.games_evicted_capacity_total,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
     pub async fn render(&self, state: &AppState) -> String {
         let http_metrics_snapshot = {
             let guard = self
@@ -129,34 +174,52 @@ impl AppMetrics {
                 .collect::<Vec<_>>()
         };
 
-        let (ongoing_games, finished_games_in_memory) = {
-            let games = state.games();
-            let guard = games.read().await;
-            guard.values().fold((0_u64, 0_u64), |(ongoing, finished), session| {
-                if session.game.check_game_over() {
-                    (ongoing, finished + 1)
-                } else {
-                    (ongoing + 1, finished)
-                }
-            })
+        let mut bot_move_metrics_snapshot = {
+            let guard = self
+                .bot_move_metrics
+                .lock()
+                .expect("bot move metrics mutex should not be poisoned");
+            guard
+                .iter()
+                .map(|(bot_id, value)| (bot_id.clone(), value.clone()))
+                .collect::<Vec<_>>()
         };
+        bot_move_metrics_snapshot.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        let mut ongoing_games = 0_u64;
+        let mut finished_games_in_memory = 0_u64;
+        for (_, session) in state.game_sessions().await {
+            if session.lock().await.game.check_game_over() {
+                finished_games_in_memory += 1;
+            } else {
+                ongoing_games += 1;
+            }
+        }
 
-        let (matchmaking_queue_size, waiting_tickets, matched_tickets, cancelled_tickets) = {
+        let (
+            matchmaking_queue_size,
+            waiting_tickets,
+            matched_tickets,
+            cancelled_tickets,
+            expired_tickets,
+        ) = {
             let matchmaking = state.matchmaking();
             let guard = matchmaking.read().await;
             let mut waiting = 0_u64;
             let mut matched = 0_u64;
             let mut cancelled = 0_u64;
+            let mut expired = 0_u64;
 
             for ticket in guard.tickets.values() {
                 match ticket {
                     MatchmakingTicketStatus::Waiting { .. } => waiting += 1,
                     MatchmakingTicketStatus::Matched { .. } => matched += 1,
-                    MatchmakingTicketStatus::Cancelled => cancelled += 1,
+                    MatchmakingTicketStatus::Cancelled { .. } => cancelled += 1,
+                    MatchmakingTicketStatus::Expired { .. } => expired += 1,
                 }
             }
 
-            (guard.queue.len() as u64, waiting, matched, cancelled)
+            (guard.queue.len() as u64, waiting, matched, cancelled, expired)
         };
 
         let mut lines = Vec::new();
@@ -294,6 +357,110 @@ impl AppMetrics {
             &[("service", SERVICE_NAME), ("status", "cancelled")],
             cancelled_tickets,
         );
+        append_sample(
+            &mut lines,
+            "yovi_gamey_matchmaking_tickets",
+            &[("service", SERVICE_NAME), ("status", "expired")],
+            expired_tickets,
+        );
+
+        let bot_workers = state.bot_workers();
+        append_metric_header(
+            &mut lines,
+            "yovi_gamey_bot_moves_queued",
+            "gauge",
+            "Bot moves waiting for a free bot worker",
+        );
+        append_sample(
+            &mut lines,
+            "yovi_gamey_bot_moves_queued",
+            &[("service", SERVICE_NAME)],
+            bot_workers.queued(),
+        );
+        append_metric_header(
+            &mut lines,
+            "yovi_gamey_bot_moves_running",
+            "gauge",
+            "Bot moves being computed",
+        );
+        append_sample(
+            &mut lines,
+            "yovi_gamey_bot_moves_running",
+            &[("service", SERVICE_NAME)],
+            bot_workers.running(),
+        );
+
+        append_metric_header(
+            &mut lines,
+            "yovi_gamey_bot_think_duration_seconds_sum",
+            "counter",
+            "Total time bots spent computing moves in seconds",
+        );
+        append_metric_header(
+            &mut lines,
+            "yovi_gamey_bot_think_duration_seconds_count",
+            "counter",
+            "Number of bot moves computed",
+        );
+        append_metric_header(
+            &mut lines,
+            "yovi_gamey_bot_queue_wait_seconds_sum",
+            "counter",
+            "Total time bot moves waited for a free bot worker in seconds",
+        );
+        for (bot_id, value) in bot_move_metrics_snapshot {
+            let labels = [("service", SERVICE_NAME), ("bot", bot_id.as_str())];
+            append_sample(
+                &mut lines,
+                "yovi_gamey_bot_think_duration_seconds_sum",
+                &labels,
+                value.think_time_sum_seconds,
+            );
+            append_sample(
+                &mut lines,
+                "yovi_gamey_bot_think_duration_seconds_count",
+                &labels,
+                value.count,
+            );
+            append_sample(
+                &mut lines,
+                "yovi_gamey_bot_queue_wait_seconds_sum",
+                &labels,
+                value.queue_wait_sum_seconds,
+            );
+        }
+
+        append_metric_header(
+            &mut lines,
+            "yovi_gamey_stats_outbox_pending",
+            "gauge",
+            "Finished-match reports waiting to be delivered to the stats service",
+        );
+        append_sample(
+            &mut lines,
+            "yovi_gamey_stats_outbox_pending",
+            &[("service", SERVICE_NAME)],
+            state.stats_outbox().len(),
+        );
+
+        append_metric_header(
+            &mut lines,
+            "yovi_gamey_games_evicted_total",
+            "counter",
+            "Game sessions evicted from memory by the retention sweeper",
+        );
+        for (reason, counter) in [
+            (EvictionReason::Finished, &self.games_evicted_finished_total),
+            (EvictionReason::Idle, &self.games_evicted_idle_total),
+            (EvictionReason::Capacity, &self.games_evicted_capacity_total),
+        ] {
+            append_sample(
+                &mut lines,
+                "yovi_gamey_games_evicted_total",
+                &[("service", SERVICE_NAME), ("reason", reason.as_str())],
+                counter.load(Ordering::Relaxed),
+            );
+        }
 
         append_counter_sample(
             &mut lines,
@@ -331,6 +498,12 @@ impl AppMetrics {
             "Matchmaking tickets cancelled by clients",
             self.matchmaking_cancelled_total.load(Ordering::Relaxed),
         );
+        append_counter_sample(
+            &mut lines,
+            "yovi_gamey_matchmaking_expired_total",
+            "Matchmaking tickets expired because their client stopped polling",
+            self.matchmaking_expired_total.load(Ordering::Relaxed),
+        );
         append_counter_sample(
             &mut lines,
             "yovi_gamey_stats_report_attempts_total",
@@ -348,6 +521,12 @@ impl AppMetrics {
     }
 }
 
+impl Default for AppMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub async fn track_http_metrics(
     State(state): State<AppState>,
     request: Request,
@@ -476,6 +655,52 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_render_reports_evictions_by_reason() {
+        let state = AppState::new(crate::YBotRegistry::new());
+        let metrics = state.metrics();
+        metrics.inc_games_evicted(EvictionReason::Idle);
+        metrics.inc_games_evicted(EvictionReason::Idle);
+        metrics.inc_games_evicted(EvictionReason::Capacity);
+
+        let rendered = metrics.render(&state).await;
+
+        assert!(rendered.contains(
+            r#"yovi_gamey_games_evicted_total{service="gamey",reason="idle"} 2"#
+        ));
+        assert!(rendered.contains(
+            r#"yovi_gamey_games_evicted_total{service="gamey",reason="capacity"} 1"#
+        ));
+        assert!(rendered.contains(
+            r#"yovi_gamey_games_evicted_total{service="gamey",reason="finished"} 0"#
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_render_reports_bot_move_times_by_bot() {
+        let state = AppState::new(crate::YBotRegistry::new());
+        let metrics = state.metrics();
+        metrics.observe_bot_move("minimax_bot", Duration::ZERO, Duration::from_millis(1500));
+        metrics.observe_bot_move(
+            "minimax_bot",
+            Duration::from_millis(250),
+            Duration::from_secs(1),
+        );
+
+        let rendered = metrics.render(&state).await;
+
+        assert!(rendered.contains(
+            r#"yovi_gamey_bot_think_duration_seconds_sum{service="gamey",bot="minimax_bot"} 2.5"#
+        ));
+        assert!(rendered.contains(
+            r#"yovi_gamey_bot_think_duration_seconds_count{service="gamey",bot="minimax_bot"} 2"#
+        ));
+        assert!(rendered.contains(
+            r#"yovi_gamey_bot_queue_wait_seconds_sum{service="gamey",bot="minimax_bot"} 0.25"#
+        ));
+        assert!(rendered.contains(r#"yovi_gamey_bot_moves_queued{service="gamey"} 0"#));
+    }
+
     #[test]
     fn test_escape_label_value_escapes_prometheus_special_characters() {
         assert_eq!(escape_label_value("line\"one\\two"), "line\\\"one\\\\two");
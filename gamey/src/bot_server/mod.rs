@@ -6,35 +6,52 @@
 //! # Endpoints
 //! - `GET /status` - Health check endpoint
 //! - `POST /{api_version}/ybot/choose/{bot_id}` - Request a move from a bot
+//! - `POST /{api_version}/ybot/analyze` - Rank candidate moves for a position
+//! - `GET /{api_version}/games/{game_id}/events` - Stream game state changes
+//! - `GET /{api_version}/matchmaking/tickets/{ticket_id}/events` - Stream ticket changes
+//! - `GET /{api_version}/spectate/games` - List live games open to spectators
+//! - `GET /{api_version}/spectate/games/{game_id}/events` - Watch a game as a spectator
 //!
 //! # Example
 //! ```no_run
-//! use gamey::run_bot_server;
+//! use gamey::{ServerConfig, run_bot_server};
 //!
 //! #[tokio::main]
 //! async fn main() {
-//!     if let Err(e) = run_bot_server(3000).await {
+//!     if let Err(e) = run_bot_server(ServerConfig::default()).await {
 //!         eprintln!("Server error: {}", e);
 //!     }
 //! }
 //! ```
 
+pub mod analysis;
+pub mod bot_workers;
 pub mod choose;
+pub mod clock;
+pub mod config;
 pub mod error;
+pub mod events;
+pub mod exhibition;
 pub mod games;
 pub mod matchmaking;
 pub mod metrics;
+pub mod retention;
+pub mod spectators;
 pub mod state;
+pub mod stats_outbox;
+pub mod storage;
 pub mod version;
 use axum::middleware;
 use axum::response::IntoResponse;
+pub use analysis::AnalysisResponse;
 pub use choose::MoveResponse;
+pub use config::ServerConfig;
 pub use error::ErrorResponse;
 use std::sync::Arc;
 pub use version::*;
 
 use self::state::AppState;
-use crate::{BiasedRandomBot, GameYError, GreedyBot, MinimaxBot, RandomBot, YBotRegistry};
+use crate::GameYError;
 
 /// Creates the Axum router with the given state.
 ///
@@ -49,6 +66,10 @@ pub fn create_router(state: AppState) -> axum::Router {
             "/{api_version}/ybot/choose/{bot_id}",
             axum::routing::post(choose::choose),
         )
+        .route(
+            "/{api_version}/ybot/analyze",
+            axum::routing::post(analysis::analyze),
+        )
         .route(
             "/{api_version}/games",
             axum::routing::post(games::create_game),
@@ -57,6 +78,10 @@ pub fn create_router(state: AppState) -> axum::Router {
             "/{api_version}/games/{game_id}",
             axum::routing::get(games::get_game),
         )
+        .route(
+            "/{api_version}/games/{game_id}/events",
+            axum::routing::get(games::game_events),
+        )
         .route(
             "/{api_version}/games/{game_id}/moves",
             axum::routing::post(games::play_move),
@@ -69,6 +94,18 @@ pub fn create_router(state: AppState) -> axum::Router {
             "/{api_version}/games/{game_id}/pass",
             axum::routing::post(games::pass_turn),
         )
+        .route(
+            "/{api_version}/games/{game_id}/undo",
+            axum::routing::post(games::undo_move),
+        )
+        .route(
+            "/{api_version}/spectate/games",
+            axum::routing::get(spectators::list_live_games),
+        )
+        .route(
+            "/{api_version}/spectate/games/{game_id}/events",
+            axum::routing::get(spectators::spectate_game),
+        )
         .route(
             "/{api_version}/matchmaking/enqueue",
             axum::routing::post(matchmaking::enqueue),
@@ -77,6 +114,10 @@ pub fn create_router(state: AppState) -> axum::Router {
             "/{api_version}/matchmaking/tickets/{ticket_id}",
             axum::routing::get(matchmaking::get_ticket),
         )
+        .route(
+            "/{api_version}/matchmaking/tickets/{ticket_id}/events",
+            axum::routing::get(matchmaking::ticket_events),
+        )
         .route(
             "/{api_version}/matchmaking/tickets/{ticket_id}/cancel",
             axum::routing::post(matchmaking::cancel_ticket),
@@ -92,32 +133,62 @@ pub fn create_router(state: AppState) -> axum::Router {
 ///
 /// The default state includes the `RandomBot` which selects moves randomly.
 pub fn create_default_state() -> AppState {
-    let bots = YBotRegistry::new()
-        .with_bot(Arc::new(RandomBot))
-        .with_bot(Arc::new(BiasedRandomBot))
-        .with_bot(Arc::new(GreedyBot))
-        .with_bot(Arc::new(MinimaxBot::default()));
-    AppState::new(bots)
+    AppState::new(config::all_bots())
 }
 
-/// Starts the bot server on the specified port.
+/// Creates the application state described by `config`.
 ///
-/// This function blocks until the server is shut down.
+/// Only the enabled bots are registered. Game sessions are stored as JSON
+/// files when `storage_dir` is set and kept in memory otherwise; the same goes
+/// for undelivered stats reports and `stats.outbox_dir`.
+///
+/// # Errors
+/// Returns `GameYError::ConfigError` if a bot is unknown.
+/// Returns `GameYError::IoError` if a storage directory cannot be created.
+pub fn create_state(config: ServerConfig) -> Result<AppState, GameYError> {
+    let mut state = AppState::new(config.bot_registry()?);
+    if let Some(storage_dir) = &config.storage_dir {
+        state = state.with_game_store(Arc::new(storage::FileGameStore::new(storage_dir)?));
+    }
+    if let Some(outbox_dir) = &config.stats.outbox_dir {
+        state =
+            state.with_stats_outbox(Arc::new(stats_outbox::StatsOutbox::persistent(outbox_dir)?));
+    }
+    Ok(state.with_config(config))
+}
+
+/// Starts the bot server with the given configuration.
 ///
-/// # Arguments
-/// * `port` - The TCP port to listen on
+/// This function blocks until the server is shut down. Games found in the
+/// storage directory are restored before it starts listening.
 ///
 /// # Errors
+/// Returns `GameYError::ConfigError` if the configuration is invalid.
+/// Returns `GameYError::IoError` if the storage directory cannot be read.
 /// Returns `GameYError::ServerError` if:
 /// - The TCP port cannot be bound (e.g., port already in use, permission denied)
 /// - The server encounters an error while running
-pub async fn run_bot_server(port: u16) -> Result<(), GameYError> {
-    let state = create_default_state();
+pub async fn run_bot_server(config: ServerConfig) -> Result<(), GameYError> {
+    config.validate()?;
+    let addr = std::net::SocketAddr::new(config.bind_address, config.port);
+    let state = create_state(config)?;
+    let restored_games = storage::restore_games(&state).await?;
+    if restored_games > 0 {
+        println!("Restored {} games from storage", restored_games);
+    }
+    let pending_reports = state.stats_outbox().len();
+    if pending_reports > 0 {
+        println!(
+            "{} stats reports are waiting to be delivered",
+            pending_reports
+        );
+    }
     matchmaking::start_matchmaking_worker(state.clone());
     games::start_inactive_online_game_monitor(state.clone());
+    retention::start_game_retention_sweeper(state.clone());
+    stats_outbox::start_stats_outbox_sender(state.clone());
     let app = create_router(state);
 
-    let addr = format!("0.0.0.0:{}", port);
     let listener =
         tokio::net::TcpListener::bind(&addr)
             .await
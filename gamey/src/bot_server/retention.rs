@@ -0,0 +1,354 @@
+//! Eviction of finished and abandoned game sessions.
+//!
+//! Sessions are kept in memory while they are being played. A background
+//! sweeper started with [`start_game_retention_sweeper`] removes them again
+//! according to a [`RetentionPolicy`]:
+//! - finished games are kept for `finished_game_ttl` after they end,
+//! - local games (human vs bot and local human vs human) are dropped after
+//!   `idle_local_game_ttl` without moves,
+//! - if more than `max_sessions` remain, the oldest finished and local games
+//!   are evicted first.
+//!
+//! Ongoing online games are never evicted here: they are ended by the online
+//! inactivity and turn timeouts instead.
+
+use super::{
+    config::{EnvLookup, deserialize_secs, env_secs, env_value},
+    games::{collect_tracked_user_ids, unregister_active_game_for_user_ids},
+    state::{AppState, GameSession},
+};
+use crate::GameYError;
+use serde::Deserialize;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::OwnedMutexGuard;
+
+const DEFAULT_FINISHED_GAME_TTL: Duration = Duration::from_secs(10 * 60);
+const DEFAULT_IDLE_LOCAL_GAME_TTL: Duration = Duration::from_secs(2 * 60 * 60);
+const DEFAULT_MAX_SESSIONS: usize = 10_000;
+const DEFAULT_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long game sessions are kept in memory.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionPolicy {
+    /// Time a finished game stays available after it ends.
+    #[serde(
+        rename = "finished_game_ttl_secs",
+        deserialize_with = "deserialize_secs"
+    )]
+    pub finished_game_ttl: Duration,
+    /// Time an unfinished local game may go without moves.
+    #[serde(
+        rename = "idle_local_game_ttl_secs",
+        deserialize_with = "deserialize_secs"
+    )]
+    pub idle_local_game_ttl: Duration,
+    /// Maximum number of sessions kept in memory.
+    pub max_sessions: usize,
+    /// Time between two sweeps.
+    #[serde(rename = "sweep_interval_secs", deserialize_with = "deserialize_secs")]
+    pub sweep_interval: Duration,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            finished_game_ttl: DEFAULT_FINISHED_GAME_TTL,
+            idle_local_game_ttl: DEFAULT_IDLE_LOCAL_GAME_TTL,
+            max_sessions: DEFAULT_MAX_SESSIONS,
+            sweep_interval: DEFAULT_SWEEP_INTERVAL,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// Overrides the policy with the environment variables that are set.
+    ///
+    /// - `GAMEY_FINISHED_GAME_TTL_SECS`
+    /// - `GAMEY_IDLE_LOCAL_GAME_TTL_SECS`
+    /// - `GAMEY_MAX_GAME_SESSIONS`
+    /// - `GAMEY_RETENTION_SWEEP_INTERVAL_SECS`
+    pub fn apply_env(&mut self, env: EnvLookup<'_>) -> Result<(), GameYError> {
+        if let Some(ttl) = env_secs(env, "GAMEY_FINISHED_GAME_TTL_SECS")? {
+            self.finished_game_ttl = ttl;
+        }
+        if let Some(ttl) = env_secs(env, "GAMEY_IDLE_LOCAL_GAME_TTL_SECS")? {
+            self.idle_local_game_ttl = ttl;
+        }
+        if let Some(max_sessions) = env_value(env, "GAMEY_MAX_GAME_SESSIONS")? {
+            self.max_sessions = max_sessions;
+        }
+        if let Some(interval) = env_secs(env, "GAMEY_RETENTION_SWEEP_INTERVAL_SECS")? {
+            self.sweep_interval = interval;
+        }
+        Ok(())
+    }
+}
+
+/// Why a session was evicted, used as the metrics label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionReason {
+    /// The game finished longer than `finished_game_ttl` ago.
+    Finished,
+    /// The local game had no moves for `idle_local_game_ttl`.
+    Idle,
+    /// The session was dropped to stay under `max_sessions`.
+    Capacity,
+}
+
+impl EvictionReason {
+    /// Returns the label used for this reason in metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvictionReason::Finished => "finished",
+            EvictionReason::Idle => "idle",
+            EvictionReason::Capacity => "capacity",
+        }
+    }
+}
+
+/// Spawns the background task that applies the retention policy of the
+/// server configuration.
+pub fn start_game_retention_sweeper(state: AppState) {
+    let policy = state.config().retention.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(policy.sweep_interval);
+        loop {
+            interval.tick().await;
+            sweep_games(&state, &policy, Instant::now()).await;
+        }
+    });
+}
+
+/// Evicts the sessions that the policy no longer allows to keep.
+///
+/// Evicted games are removed from the game store and from the active-game
+/// index. Sessions locked by a request are skipped until the next sweep.
+/// Returns the number of evicted sessions.
+pub async fn sweep_games(state: &AppState, policy: &RetentionPolicy, now: Instant) -> usize {
+    let evicted = {
+        let games = state.games();
+        let mut index = games.write().await;
+
+        // Sessions locked by a request are in use and kept for this sweep.
+        // Waiting for them here would stall every request on the index.
+        let sessions: Vec<(String, OwnedMutexGuard<GameSession>)> = index
+            .iter()
+            .filter_map(|(game_id, session)| {
+                Arc::clone(session)
+                    .try_lock_owned()
+                    .ok()
+                    .map(|guard| (game_id.clone(), guard))
+            })
+            .collect();
+
+        let mut evictions: Vec<(String, EvictionReason)> = sessions
+            .iter()
+            .filter_map(|(game_id, session)| {
+                expired_reason(session, policy, now).map(|reason| (game_id.clone(), reason))
+            })
+            .collect();
+
+        let remaining = index.len() - evictions.len();
+        if remaining > policy.max_sessions {
+            let mut candidates: Vec<&(String, OwnedMutexGuard<GameSession>)> = sessions
+                .iter()
+                .filter(|(game_id, session)| {
+                    is_evictable_for_capacity(session)
+                        && !evictions.iter().any(|(evicted, _)| evicted == game_id)
+                })
+                .collect();
+            candidates.sort_by_key(|(_, session)| capacity_eviction_order(session));
+            evictions.extend(
+                candidates
+                    .into_iter()
+                    .take(remaining - policy.max_sessions)
+                    .map(|(game_id, _)| (game_id.clone(), EvictionReason::Capacity)),
+            );
+        }
+
+        evictions
+            .into_iter()
+            .filter_map(|(game_id, reason)| {
+                index.remove(&game_id)?;
+                let (_, session) = sessions.iter().find(|(locked, _)| *locked == game_id)?;
+                Some((game_id, reason, collect_tracked_user_ids(session)))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let evicted_count = evicted.len();
+    for (game_id, reason, tracked_user_ids) in evicted {
+        state.forget_game(&game_id);
+        unregister_active_game_for_user_ids(state, &game_id, &tracked_user_ids).await;
+        state.game_events().close(&game_id);
+        state.metrics().inc_games_evicted(reason);
+    }
+    evicted_count
+}
+
+fn expired_reason(
+    session: &GameSession,
+    policy: &RetentionPolicy,
+    now: Instant,
+) -> Option<EvictionReason> {
+    if session.game.check_game_over() {
+        let finished_at = session.finished_at.unwrap_or(session.last_activity_at);
+        return (now.saturating_duration_since(finished_at) >= policy.finished_game_ttl)
+            .then_some(EvictionReason::Finished);
+    }
+
+    let is_local = session.player_tokens.is_none();
+    (is_local
+        && now.saturating_duration_since(session.last_activity_at) >= policy.idle_local_game_ttl)
+        .then_some(EvictionReason::Idle)
+}
+
+fn is_evictable_for_capacity(session: &GameSession) -> bool {
+    session.game.check_game_over() || session.player_tokens.is_none()
+}
+
+/// Finished games go first, then the games that have been idle the longest.
+fn capacity_eviction_order(session: &GameSession) -> (bool, Instant) {
+    match session.finished_at {
+        Some(finished_at) => (false, finished_at),
+        None if session.game.check_game_over() => (false, session.last_activity_at),
+        None => (true, session.last_activity_at),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GameAction, GameY, Movement, PlayerId, YBotRegistry};
+    use std::collections::HashMap;
+
+    fn session_at(last_activity_at: Instant, finished: bool, online: bool) -> GameSession {
+        let mut game = GameY::new(3);
+        if finished {
+            game.add_move(Movement::Action {
+                player: PlayerId::new(0),
+                action: GameAction::Resign,
+            })
+            .unwrap();
+        }
+        GameSession {
+            created_at: last_activity_at,
+            player_tokens: online.then(|| HashMap::from([(0, "a".to_string())])),
+            player0_user_id: Some("alice".to_string()),
+            stats_reported: true,
+            last_activity_at,
+            finished_at: finished.then_some(last_activity_at),
+            ..GameSession::for_test(game)
+        }
+    }
+
+    fn policy() -> RetentionPolicy {
+        RetentionPolicy {
+            finished_game_ttl: Duration::from_secs(60),
+            idle_local_game_ttl: Duration::from_secs(600),
+            max_sessions: 100,
+            sweep_interval: Duration::from_secs(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sweep_evicts_expired_finished_and_idle_local_games() {
+        let state = AppState::new(YBotRegistry::new());
+        let now = Instant::now();
+        let long_ago = now - Duration::from_secs(3600);
+        state
+            .insert_game("finished-old".into(), session_at(long_ago, true, false))
+            .await;
+        state
+            .insert_game("finished-recent".into(), session_at(now, true, false))
+            .await;
+        state
+            .insert_game("local-idle".into(), session_at(long_ago, false, false))
+            .await;
+        state
+            .insert_game("online-idle".into(), session_at(long_ago, false, true))
+            .await;
+        state
+            .active_game_id_by_user_id()
+            .write()
+            .await
+            .insert("alice".to_string(), "local-idle".to_string());
+
+        let evicted = sweep_games(&state, &policy(), now).await;
+
+        assert_eq!(evicted, 2);
+        let games = state.games();
+        let guard = games.read().await;
+        assert!(guard.contains_key("finished-recent"));
+        assert!(guard.contains_key("online-idle"));
+        assert!(state.active_game_id_by_user_id().read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_enforces_session_cap_starting_with_finished_games() {
+        let state = AppState::new(YBotRegistry::new());
+        let now = Instant::now();
+        state
+            .insert_game(
+                "local-old".into(),
+                session_at(now - Duration::from_secs(20), false, false),
+            )
+            .await;
+        state
+            .insert_game("local-new".into(), session_at(now, false, false))
+            .await;
+        state
+            .insert_game("finished".into(), session_at(now, true, false))
+            .await;
+        state
+            .insert_game(
+                "online".into(),
+                session_at(now - Duration::from_secs(30), false, true),
+            )
+            .await;
+        let policy = RetentionPolicy {
+            max_sessions: 2,
+            ..policy()
+        };
+
+        let evicted = sweep_games(&state, &policy, now).await;
+
+        assert_eq!(evicted, 2);
+        let games = state.games();
+        let guard = games.read().await;
+        assert!(guard.contains_key("local-new"));
+        assert!(guard.contains_key("online"));
+    }
+
+    #[tokio::test]
+    async fn test_sweep_skips_sessions_locked_by_a_request() {
+        let state = AppState::new(YBotRegistry::new());
+        let now = Instant::now();
+        let long_ago = now - Duration::from_secs(3600);
+        state
+            .insert_game("finished-busy".into(), session_at(long_ago, true, false))
+            .await;
+        state
+            .insert_game("finished-idle".into(), session_at(long_ago, true, false))
+            .await;
+        let busy = state.lock_game("finished-busy").await.unwrap();
+
+        let evicted = sweep_games(&state, &policy(), now).await;
+
+        assert_eq!(evicted, 1);
+        drop(busy);
+        assert!(state.lock_game("finished-busy").await.is_some());
+        assert!(state.lock_game("finished-idle").await.is_none());
+    }
+
+    #[test]
+    fn test_eviction_reason_labels() {
+        assert_eq!(EvictionReason::Finished.as_str(), "finished");
+        assert_eq!(EvictionReason::Idle.as_str(), "idle");
+        assert_eq!(EvictionReason::Capacity.as_str(), "capacity");
+    }
+}
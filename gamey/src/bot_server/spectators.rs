@@ -0,0 +1,236 @@
+//! Read-only access to live games for spectators.
+//!
+//! Spectators can list the live games that accept them and follow one of them
+//! over server-sent events. They never send a player token: watching a game
+//! does not count as presence for either player. Games created with
+//! `allow_spectators: false` are neither listed nor watchable.
+
+use super::{
+    error::ErrorResponse,
+    games::{ApiVersionParams, GameEventStream, GameMode, GameParams, game_mode},
+    state::AppState,
+    version::check_api_version,
+};
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::sse::{Event, Sse},
+};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Summary of a game that can be watched.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LiveGameSummary {
+    pub game_id: String,
+    pub mode: GameMode,
+    pub bot_id: Option<String>,
+    pub size: u32,
+    pub player0_user_id: Option<String>,
+    pub player1_user_id: Option<String>,
+    /// Number of moves played so far.
+    pub moves: usize,
+    pub next_player: Option<u32>,
+    pub spectator_count: usize,
+}
+
+/// Response of the live games listing.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LiveGamesResponse {
+    pub api_version: String,
+    /// Live games, the most watched first.
+    pub games: Vec<LiveGameSummary>,
+}
+
+/// Number of open spectator streams per game.
+#[derive(Debug, Default)]
+pub struct SpectatorRegistry {
+    counts: Mutex<HashMap<String, usize>>,
+}
+
+impl SpectatorRegistry {
+    /// Creates a registry without spectators.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of spectators watching `game_id`.
+    pub fn count(&self, game_id: &str) -> usize {
+        self.counts().get(game_id).copied().unwrap_or(0)
+    }
+
+    /// Counts a new spectator of `game_id` until the returned seat is dropped.
+    pub fn join(self: &Arc<Self>, game_id: &str) -> SpectatorSeat {
+        *self.counts().entry(game_id.to_string()).or_insert(0) += 1;
+        SpectatorSeat {
+            registry: Arc::clone(self),
+            game_id: game_id.to_string(),
+        }
+    }
+
+    fn leave(&self, game_id: &str) {
+        let mut counts = self.counts();
+        if let Some(count) = counts.get_mut(game_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                counts.remove(game_id);
+            }
+        }
+    }
+
+    fn counts(&self) -> MutexGuard<'_, HashMap<String, usize>> {
+        self.counts.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A spectator of one game, counted for as long as this value lives.
+#[derive(Debug)]
+pub struct SpectatorSeat {
+    registry: Arc<SpectatorRegistry>,
+    game_id: String,
+}
+
+impl Drop for SpectatorSeat {
+    fn drop(&mut self) {
+        self.registry.leave(&self.game_id);
+    }
+}
+
+/// Lists the unfinished games that accept spectators.
+///
+/// # Route
+/// `GET /{api_version}/spectate/games`
+pub async fn list_live_games(
+    State(state): State<AppState>,
+    Path(params): Path<ApiVersionParams>,
+) -> Result<Json<LiveGamesResponse>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+
+    let spectators = state.spectators();
+    let mut games = Vec::new();
+    for (game_id, session) in state.game_sessions().await {
+        let session = session.lock().await;
+        if !session.allow_spectators || session.game.check_game_over() {
+            continue;
+        }
+        games.push(LiveGameSummary {
+            mode: game_mode(&session),
+            bot_id: session.bot_id().cloned(),
+            size: session.game.board_size(),
+            player0_user_id: session.player0_user_id.clone(),
+            player1_user_id: session.player1_user_id.clone(),
+            moves: session.game.history().len(),
+            next_player: session.game.next_player().map(|player| player.id()),
+            spectator_count: spectators.count(&game_id),
+            game_id,
+        });
+    }
+    games.sort_by(|a, b| {
+        b.spectator_count
+            .cmp(&a.spectator_count)
+            .then_with(|| a.game_id.cmp(&b.game_id))
+    });
+
+    Ok(Json(LiveGamesResponse {
+        api_version: params.api_version,
+        games,
+    }))
+}
+
+/// Streams a game to a spectator as server-sent events.
+///
+/// The events are the same `state` events players receive from
+/// `/games/{game_id}/events`, without the player-specific timeout fields.
+/// No player token is needed and the stream never counts as presence.
+///
+/// # Route
+/// `GET /{api_version}/spectate/games/{game_id}/events`
+pub async fn spectate_game(
+    State(state): State<AppState>,
+    Path(params): Path<GameParams>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ErrorResponse> {
+    check_api_version(&params.api_version)?;
+
+    let updates = {
+        let Some(session) = state.lock_game(&params.game_id).await else {
+            return Err(ErrorResponse::error(
+                &format!("Game not found: {}", params.game_id),
+                Some(params.api_version),
+                None,
+            ));
+        };
+        if !session.allow_spectators {
+            return Err(ErrorResponse::error(
+                "This game does not allow spectators",
+                Some(params.api_version),
+                None,
+            ));
+        }
+        state.game_events().subscribe(&params.game_id)
+    };
+
+    let seat = state.spectators().join(&params.game_id);
+    Ok(GameEventStream::new(state, params, None, updates, Some(seat)).into_sse())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_server::state::GameSession;
+    use crate::{GameY, YBotRegistry};
+    use std::time::Instant;
+
+    #[test]
+    fn test_spectator_seats_are_counted_until_dropped() {
+        let registry = Arc::new(SpectatorRegistry::new());
+
+        let first = registry.join("game-1");
+        let second = registry.join("game-1");
+        let other = registry.join("game-2");
+        assert_eq!(registry.count("game-1"), 2);
+        assert_eq!(registry.count("game-2"), 1);
+
+        drop(first);
+        drop(other);
+        assert_eq!(registry.count("game-1"), 1);
+        assert_eq!(registry.count("game-2"), 0);
+
+        drop(second);
+        assert!(registry.counts().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_spectating_an_online_game_does_not_record_presence() {
+        let state = AppState::new(YBotRegistry::new());
+        let session = GameSession {
+            turn_started_at: Some(Instant::now()),
+            player_tokens: Some(HashMap::from([
+                (0, "player-0-token".to_string()),
+                (1, "player-1-token".to_string()),
+            ])),
+            last_seen_at_by_player_id: Some(HashMap::new()),
+            player0_user_id: Some("fernando".to_string()),
+            player1_user_id: Some("jose".to_string()),
+            ..GameSession::for_test(GameY::new(3))
+        };
+        state.insert_game("game-online".to_string(), session).await;
+
+        let stream = spectate_game(
+            State(state.clone()),
+            Path(GameParams {
+                api_version: "v1".to_string(),
+                game_id: "game-online".to_string(),
+            }),
+        )
+        .await;
+
+        assert!(stream.is_ok());
+        assert_eq!(state.spectators().count("game-online"), 1);
+        let session = state.lock_game("game-online").await.unwrap();
+        let last_seen = session.last_seen_at_by_player_id.as_ref();
+        assert!(last_seen.unwrap().is_empty());
+    }
+}
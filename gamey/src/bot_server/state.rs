@@ -1,15 +1,23 @@
+use super::bot_workers::BotWorkerPool;
+use super::clock::{GameClock, TimeControl};
+use super::config::ServerConfig;
+use super::events::EventHub;
+use super::exhibition::ExhibitionSlots;
+use super::matchmaking::{DefaultRatingProvider, RatingProvider};
 use super::metrics::AppMetrics;
-use crate::{GameY, YBotRegistry};
+use super::spectators::SpectatorRegistry;
+use super::stats_outbox::StatsOutbox;
+use super::storage::{GameStore, GameStoreWriter, PersistedGameSession};
+use crate::{GameY, PlayerId, YBotRegistry};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, VecDeque},
-    sync::{
-        Arc,
-        atomic::{AtomicU64, Ordering},
-    },
+    fmt::Write,
+    sync::{Arc, PoisonError},
     time::Instant,
 };
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -17,16 +25,25 @@ pub enum GameCompletionReason {
     WinCondition,
     Resignation,
     DisconnectTimeout,
+    /// A player ran out of time on their game clock.
+    Timeout,
+    /// A bot could not move in a bot_vs_bot game and forfeited it.
+    BotFailure,
 }
 
 /// In-memory state for a running game session.
 #[derive(Clone)]
 pub struct GameSession {
     pub game: GameY,
-    pub bot_id: Option<String>,
+    /// Bot playing each seat, indexed by player id. Seats without a bot are
+    /// played by humans.
+    pub bots: [Option<String>; 2],
     pub created_at: Instant,
     pub turn_started_at: Option<Instant>,
     /// Token by player id for authenticated multiplayer matchmaking games.
+    ///
+    /// The map is emptied when the game finishes, so the tokens stop being
+    /// accepted.
     pub player_tokens: Option<HashMap<u32, String>>,
     /// Last time each online player contacted the server for this match.
     pub last_seen_at_by_player_id: Option<HashMap<u32, Instant>>,
@@ -34,14 +51,134 @@ pub struct GameSession {
     pub player1_user_id: Option<String>,
     pub stats_reported: bool,
     pub completion_reason: Option<GameCompletionReason>,
+    /// Last time a move or action changed the session.
+    pub last_activity_at: Instant,
+    /// When the game finished, if it has.
+    pub finished_at: Option<Instant>,
+    /// Whether people other than the players may watch the game.
+    pub allow_spectators: bool,
+    /// Per-player clocks, for games created with a time control.
+    pub clock: Option<GameClock>,
+    /// Progress of the bot move owed in the current position. Not persisted.
+    pub bot_turn: BotTurnStatus,
+}
+
+/// Progress of the bot move owed in a `human_vs_bot` game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BotTurnStatus {
+    /// No task is computing a bot move for the current position.
+    #[default]
+    Idle,
+    /// A task is computing the bot move and will apply it.
+    Thinking,
+    /// The last attempt failed at the given time; it is retried later.
+    Failed(Instant),
+}
+
+impl GameSession {
+    /// Returns the bot playing `player`'s seat, if a bot plays it.
+    pub fn bot_for(&self, player: PlayerId) -> Option<&String> {
+        self.bots.get(player.id() as usize)?.as_ref()
+    }
+
+    /// Returns the bot of a `human_vs_bot` game, or the bot moving first in a
+    /// `bot_vs_bot` game.
+    pub fn bot_id(&self) -> Option<&String> {
+        self.bots.iter().flatten().next()
+    }
+
+    /// Returns the seat the human plays in a `human_vs_bot` game: the only
+    /// seat without a bot. `None` in the other modes.
+    pub fn human_player(&self) -> Option<PlayerId> {
+        match &self.bots {
+            [None, Some(_)] => Some(PlayerId::new(0)),
+            [Some(_), None] => Some(PlayerId::new(1)),
+            _ => None,
+        }
+    }
+
+    /// Marks the session as changed at `now`, noting when the game finished.
+    ///
+    /// Player tokens are invalidated once the game is over.
+    pub fn record_activity(&mut self, now: Instant) {
+        self.last_activity_at = now;
+        if self.game.check_game_over() {
+            if self.finished_at.is_none() {
+                self.finished_at = Some(now);
+            }
+            if let Some(tokens) = &mut self.player_tokens {
+                tokens.clear();
+            }
+        }
+    }
+
+    /// Returns a fresh local human_vs_human session of `game`. Tests override
+    /// the fields they care about with struct update syntax.
+    #[cfg(test)]
+    pub fn for_test(game: GameY) -> Self {
+        let now = Instant::now();
+        Self {
+            game,
+            bots: [None, None],
+            created_at: now,
+            turn_started_at: None,
+            player_tokens: None,
+            last_seen_at_by_player_id: None,
+            player0_user_id: None,
+            player1_user_id: None,
+            stats_reported: false,
+            completion_reason: None,
+            last_activity_at: now,
+            finished_at: None,
+            allow_spectators: true,
+            clock: None,
+            bot_turn: BotTurnStatus::Idle,
+        }
+    }
 }
 
+/// A game session shared between requests.
+///
+/// Each session has its own lock, so requests on different games do not wait
+/// for each other.
+pub type SharedGameSession = Arc<Mutex<GameSession>>;
+
 /// Queue entry for matchmaking.
 #[derive(Clone, Debug)]
 pub struct MatchmakingQueueEntry {
     pub ticket_id: String,
     pub size: u32,
     pub user_id: Option<String>,
+    /// Skill rating used to pair players of similar strength.
+    pub rating: f64,
+    pub enqueued_at: Instant,
+    /// Whether the player accepts spectators in the matched game.
+    pub allow_spectators: bool,
+    /// Time control requested for the matched game.
+    pub time_control: Option<TimeControl>,
+}
+
+/// Last time the client of a waiting ticket enqueued or polled it.
+///
+/// The time sits in its own cell so a poll can record it under the
+/// matchmaking read lock, without waiting for the matchmaking worker.
+#[derive(Clone, Debug)]
+pub struct TicketHeartbeat(Arc<std::sync::Mutex<Instant>>);
+
+impl TicketHeartbeat {
+    pub fn new(at: Instant) -> Self {
+        Self(Arc::new(std::sync::Mutex::new(at)))
+    }
+
+    /// Returns the time of the last heartbeat.
+    pub fn last(&self) -> Instant {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Records a heartbeat at `at`.
+    pub fn record(&self, at: Instant) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = at;
+    }
 }
 
 /// Internal state for a matchmaking ticket.
@@ -51,13 +188,36 @@ pub enum MatchmakingTicketStatus {
         size: u32,
         user_id: Option<String>,
         enqueued_at: Instant,
+        /// Last time the client enqueued or polled the ticket.
+        last_polled_at: TicketHeartbeat,
     },
     Matched {
         game_id: String,
         player_id: u32,
         player_token: String,
+        /// Rating difference between the two matched players.
+        rating_gap: f64,
+        matched_at: Instant,
+    },
+    Cancelled {
+        cancelled_at: Instant,
+    },
+    /// The client stopped polling before an opponent was found.
+    Expired {
+        expired_at: Instant,
     },
-    Cancelled,
+}
+
+impl MatchmakingTicketStatus {
+    /// Returns when the ticket left the queue, or `None` while it is waiting.
+    pub fn resolved_at(&self) -> Option<Instant> {
+        match self {
+            MatchmakingTicketStatus::Waiting { .. } => None,
+            MatchmakingTicketStatus::Matched { matched_at, .. } => Some(*matched_at),
+            MatchmakingTicketStatus::Cancelled { cancelled_at } => Some(*cancelled_at),
+            MatchmakingTicketStatus::Expired { expired_at } => Some(*expired_at),
+        }
+    }
 }
 
 /// Shared in-memory matchmaking structures.
@@ -77,46 +237,136 @@ pub struct AppState {
     /// The registry of available bots, wrapped in Arc for thread-safe sharing.
     bots: Arc<YBotRegistry>,
     /// In-memory game sessions indexed by game id.
-    games: Arc<RwLock<HashMap<String, GameSession>>>,
+    ///
+    /// The index lock is only held to look sessions up, add or remove them;
+    /// each session is then locked on its own.
+    games: Arc<RwLock<HashMap<String, SharedGameSession>>>,
     /// Active game id indexed by normalized user id.
     active_game_id_by_user_id: Arc<RwLock<HashMap<String, String>>>,
     /// In-memory matchmaking queue and ticket statuses.
     matchmaking: Arc<RwLock<MatchmakingState>>,
     /// In-memory metrics registry used to expose Prometheus telemetry.
     metrics: Arc<AppMetrics>,
-    /// Counter used to generate unique game identifiers.
-    next_game_id: Arc<AtomicU64>,
-    /// Counter used to generate unique ticket identifiers.
-    next_ticket_id: Arc<AtomicU64>,
-    /// Counter used to generate unique player tokens.
-    next_player_token_id: Arc<AtomicU64>,
+    /// Writer game sessions are persisted through, if a store is configured.
+    game_store: Option<Arc<GameStoreWriter>>,
+    /// Source of player ratings for matchmaking tickets that do not carry one.
+    rating_provider: Arc<dyn RatingProvider>,
+    /// Change notifications for game event streams, by game id.
+    game_events: Arc<EventHub>,
+    /// Change notifications for ticket event streams, by ticket id.
+    ticket_events: Arc<EventHub>,
+    /// Open spectator streams by game id.
+    spectators: Arc<SpectatorRegistry>,
+    /// Exhibitions being played, capped by `exhibition.max_concurrent`.
+    exhibitions: Arc<ExhibitionSlots>,
+    /// Runtime settings of the server.
+    config: Arc<ServerConfig>,
+    /// Finished-match reports waiting to be delivered to the stats service.
+    stats_outbox: Arc<StatsOutbox>,
+    /// Blocking threads bot moves are computed on.
+    bot_workers: Arc<BotWorkerPool>,
 }
 
 impl AppState {
     /// Creates a new application state with the given bot registry.
     pub fn new(bots: YBotRegistry) -> Self {
+        let config = ServerConfig::default();
         Self {
             bots: Arc::new(bots),
             games: Arc::new(RwLock::new(HashMap::new())),
             active_game_id_by_user_id: Arc::new(RwLock::new(HashMap::new())),
             matchmaking: Arc::new(RwLock::new(MatchmakingState::default())),
             metrics: Arc::new(AppMetrics::new()),
-            next_game_id: Arc::new(AtomicU64::new(1)),
-            next_ticket_id: Arc::new(AtomicU64::new(1)),
-            next_player_token_id: Arc::new(AtomicU64::new(1)),
+            game_store: None,
+            rating_provider: Arc::new(DefaultRatingProvider),
+            game_events: Arc::new(EventHub::new()),
+            ticket_events: Arc::new(EventHub::new()),
+            spectators: Arc::new(SpectatorRegistry::new()),
+            exhibitions: Arc::new(ExhibitionSlots::new()),
+            stats_outbox: Arc::new(StatsOutbox::in_memory()),
+            bot_workers: Arc::new(BotWorkerPool::new(config.max_concurrent_bot_moves)),
+            config: Arc::new(config),
         }
     }
 
+    /// Replaces the runtime settings used by the handlers and workers.
+    ///
+    /// The bot worker pool is resized to `max_concurrent_bot_moves`.
+    pub fn with_config(mut self, config: ServerConfig) -> Self {
+        self.bot_workers = Arc::new(BotWorkerPool::new(config.max_concurrent_bot_moves));
+        self.config = Arc::new(config);
+        self
+    }
+
+    /// Persists game sessions to `game_store`, written on a dedicated thread.
+    ///
+    /// Without a store, sessions only live in memory.
+    pub fn with_game_store(mut self, game_store: Arc<dyn GameStore>) -> Self {
+        self.game_store = Some(Arc::new(GameStoreWriter::new(game_store)));
+        self
+    }
+
+    /// Replaces the outbox finished-match reports are queued in.
+    pub fn with_stats_outbox(mut self, stats_outbox: Arc<StatsOutbox>) -> Self {
+        self.stats_outbox = stats_outbox;
+        self
+    }
+
+    /// Replaces the provider used to look up player ratings for matchmaking.
+    pub fn with_rating_provider(mut self, rating_provider: Arc<dyn RatingProvider>) -> Self {
+        self.rating_provider = rating_provider;
+        self
+    }
+
     /// Returns a clone of the Arc-wrapped bot registry.
     pub fn bots(&self) -> Arc<YBotRegistry> {
         Arc::clone(&self.bots)
     }
 
-    /// Returns the in-memory game storage.
-    pub fn games(&self) -> Arc<RwLock<HashMap<String, GameSession>>> {
+    /// Returns the index of in-memory game sessions.
+    ///
+    /// Never wait for a session lock while holding the index lock: handlers
+    /// lock the index again after locking a session.
+    pub fn games(&self) -> Arc<RwLock<HashMap<String, SharedGameSession>>> {
         Arc::clone(&self.games)
     }
 
+    /// Adds a session to the index, replacing any session with the same id.
+    pub async fn insert_game(&self, game_id: String, session: GameSession) {
+        self.games
+            .write()
+            .await
+            .insert(game_id, Arc::new(Mutex::new(session)));
+    }
+
+    /// Locks the session of `game_id`, or returns `None` if there is none.
+    ///
+    /// A session evicted while this call waited for its lock is reported as
+    /// missing, so it is never changed or persisted again.
+    pub async fn lock_game(&self, game_id: &str) -> Option<OwnedMutexGuard<GameSession>> {
+        let session = self.games.read().await.get(game_id).cloned()?;
+        let guard = Arc::clone(&session).lock_owned().await;
+        let still_indexed = self
+            .games
+            .read()
+            .await
+            .get(game_id)
+            .is_some_and(|current| Arc::ptr_eq(current, &session));
+        still_indexed.then_some(guard)
+    }
+
+    /// Returns every session in the index, for sweeps over all games.
+    ///
+    /// The sessions are not locked; lock them one at a time.
+    pub async fn game_sessions(&self) -> Vec<(String, SharedGameSession)> {
+        self.games
+            .read()
+            .await
+            .iter()
+            .map(|(game_id, session)| (game_id.clone(), Arc::clone(session)))
+            .collect()
+    }
+
     /// Returns the in-memory active-game ownership index.
     pub fn active_game_id_by_user_id(&self) -> Arc<RwLock<HashMap<String, String>>> {
         Arc::clone(&self.active_game_id_by_user_id)
@@ -132,23 +382,110 @@ impl AppState {
         Arc::clone(&self.metrics)
     }
 
-    /// Returns a new unique game identifier.
+    /// Returns the storage backend for game sessions, if one is configured.
+    pub fn game_store(&self) -> Option<Arc<dyn GameStore>> {
+        self.game_store.as_ref().map(|writer| writer.store())
+    }
+
+    /// Returns the provider used to look up player ratings.
+    pub fn rating_provider(&self) -> Arc<dyn RatingProvider> {
+        Arc::clone(&self.rating_provider)
+    }
+
+    /// Returns the change notifications for game event streams.
+    pub fn game_events(&self) -> Arc<EventHub> {
+        Arc::clone(&self.game_events)
+    }
+
+    /// Returns the change notifications for ticket event streams.
+    pub fn ticket_events(&self) -> Arc<EventHub> {
+        Arc::clone(&self.ticket_events)
+    }
+
+    /// Returns the runtime settings of the server.
+    pub fn config(&self) -> Arc<ServerConfig> {
+        Arc::clone(&self.config)
+    }
+
+    /// Returns the outbox of finished-match reports.
+    pub fn stats_outbox(&self) -> Arc<StatsOutbox> {
+        Arc::clone(&self.stats_outbox)
+    }
+
+    /// Returns the pool bot moves are computed on.
+    pub fn bot_workers(&self) -> Arc<BotWorkerPool> {
+        Arc::clone(&self.bot_workers)
+    }
+
+    /// Returns the registry of open spectator streams.
+    pub fn spectators(&self) -> Arc<SpectatorRegistry> {
+        Arc::clone(&self.spectators)
+    }
+
+    /// Returns the count of exhibitions being played.
+    pub fn exhibitions(&self) -> Arc<ExhibitionSlots> {
+        Arc::clone(&self.exhibitions)
+    }
+
+    /// Queues a snapshot of the session to be written to the game store.
+    ///
+    /// Does nothing without a store. Storage failures are logged and do not
+    /// fail the request: the session is still served from memory.
+    pub fn persist_game(&self, game_id: &str, session: &GameSession) {
+        if let Some(writer) = &self.game_store {
+            writer.save(
+                game_id,
+                PersistedGameSession::from_session(session, Instant::now()),
+            );
+        }
+    }
+
+    /// Queues the stored copy of the game to be removed, if there is a store.
+    pub fn forget_game(&self, game_id: &str) {
+        if let Some(writer) = &self.game_store {
+            writer.delete(game_id);
+        }
+    }
+
+    /// Waits until every queued change has been written to the game store.
+    pub async fn flush_game_store(&self) {
+        if let Some(writer) = &self.game_store {
+            writer.flush().await;
+        }
+    }
+
+    /// Returns a new unguessable game identifier.
     pub fn new_game_id(&self) -> String {
-        let id = self.next_game_id.fetch_add(1, Ordering::Relaxed);
-        format!("game-{}", id)
+        random_identifier("game-", ID_BYTES)
     }
 
-    /// Returns a new unique ticket identifier.
+    /// Returns a new unguessable ticket identifier.
     pub fn new_ticket_id(&self) -> String {
-        let id = self.next_ticket_id.fetch_add(1, Ordering::Relaxed);
-        format!("ticket-{}", id)
+        random_identifier("ticket-", ID_BYTES)
     }
 
-    /// Returns a new unique player token.
+    /// Returns a new unguessable player token.
     pub fn new_player_token(&self) -> String {
-        let id = self.next_player_token_id.fetch_add(1, Ordering::Relaxed);
-        format!("ptk-{}", id)
+        random_identifier("ptk-", PLAYER_TOKEN_BYTES)
+    }
+}
+
+/// Random bytes in game and ticket identifiers.
+const ID_BYTES: usize = 16;
+/// Random bytes in player tokens.
+const PLAYER_TOKEN_BYTES: usize = 32;
+
+/// Builds `prefix` followed by `len` bytes from the thread-local CSPRNG, hex
+/// encoded.
+fn random_identifier(prefix: &str, len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::rng().fill(bytes.as_mut_slice());
+    let mut id = String::with_capacity(prefix.len() + len * 2);
+    id.push_str(prefix);
+    for byte in bytes {
+        let _ = write!(id, "{:02x}", byte);
     }
+    id
 }
 
 #[cfg(test)]
@@ -190,6 +527,102 @@ mod tests {
         assert_eq!(bots1.names(), bots2.names());
     }
 
+    #[test]
+    fn test_generated_identifiers_are_random_and_prefixed() {
+        let state = AppState::new(YBotRegistry::new());
+        let first = state.new_player_token();
+        let second = state.new_player_token();
+
+        assert_ne!(first, second);
+        assert!(first.starts_with("ptk-"));
+        assert_eq!(first.len(), "ptk-".len() + 64);
+        assert!(first["ptk-".len()..].chars().all(|c| c.is_ascii_hexdigit()));
+        assert!(state.new_game_id().starts_with("game-"));
+        assert_ne!(state.new_game_id(), state.new_game_id());
+    }
+
+    #[test]
+    fn test_seats_name_the_bots_and_the_human_player() {
+        let mut session = GameSession::for_test(GameY::new(3));
+        assert_eq!(session.bot_id(), None);
+        assert_eq!(session.human_player(), None);
+
+        session.bots = [Some("greedy_bot".to_string()), None];
+        assert_eq!(session.bot_id().map(String::as_str), Some("greedy_bot"));
+        assert_eq!(session.bot_for(PlayerId::new(1)), None);
+        assert_eq!(session.human_player(), Some(PlayerId::new(1)));
+
+        session.bots[1] = Some("random_bot".to_string());
+        assert_eq!(session.bot_id().map(String::as_str), Some("greedy_bot"));
+        assert_eq!(
+            session.bot_for(PlayerId::new(1)).map(String::as_str),
+            Some("random_bot")
+        );
+        assert_eq!(session.human_player(), None);
+    }
+
+    #[test]
+    fn test_record_activity_invalidates_tokens_when_game_ends() {
+        let mut session = GameSession {
+            player_tokens: Some(HashMap::from([(0, "a".to_string()), (1, "b".to_string())])),
+            ..GameSession::for_test(GameY::new(3))
+        };
+
+        session.record_activity(Instant::now());
+        assert_eq!(session.player_tokens.as_ref().map(HashMap::len), Some(2));
+
+        session
+            .game
+            .add_move(crate::Movement::Action {
+                player: crate::PlayerId::new(0),
+                action: crate::GameAction::Resign,
+            })
+            .unwrap();
+        session.record_activity(Instant::now());
+
+        assert!(session.finished_at.is_some());
+        assert_eq!(session.player_tokens, Some(HashMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_locking_one_game_does_not_block_others() {
+        let state = AppState::new(YBotRegistry::new());
+        state
+            .insert_game("game-1".to_string(), GameSession::for_test(GameY::new(3)))
+            .await;
+        state
+            .insert_game("game-2".to_string(), GameSession::for_test(GameY::new(3)))
+            .await;
+
+        let _first = state.lock_game("game-1").await.unwrap();
+        let second =
+            tokio::time::timeout(std::time::Duration::from_secs(1), state.lock_game("game-2"))
+                .await
+                .expect("other games should stay available");
+
+        assert!(second.is_some());
+        assert!(state.lock_game("game-3").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lock_game_reports_sessions_removed_while_waiting() {
+        let state = AppState::new(YBotRegistry::new());
+        state
+            .insert_game("game-1".to_string(), GameSession::for_test(GameY::new(3)))
+            .await;
+        let held = state.lock_game("game-1").await.unwrap();
+
+        let waiting = tokio::spawn({
+            let state = state.clone();
+            async move { state.lock_game("game-1").await.is_some() }
+        });
+        tokio::task::yield_now().await;
+        state.games().write().await.remove("game-1");
+        drop(held);
+
+        assert!(!waiting.await.unwrap());
+    }
+
     #[test]
     fn test_metrics_arc_clone() {
         let registry = YBotRegistry::new();
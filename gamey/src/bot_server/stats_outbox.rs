@@ -0,0 +1,548 @@
+//! Delivery of finished-match reports to the stats service.
+//!
+//! Finished games are not reported from the request that ends them. Their
+//! report is put in a [`StatsOutbox`] instead, and a background sender started
+//! with [`start_stats_outbox_sender`] posts it to the stats service, retrying
+//! with exponential backoff until it is accepted.
+//!
+//! Reports are keyed by game id, so a game is never queued twice. The stats
+//! service also ignores reports for games it already recorded, which makes it
+//! safe to send a report again after a timeout whose answer was lost.
+//!
+//! When `stats.outbox_dir` is configured, pending reports are written to one
+//! JSON file per game and reloaded on startup, so a restart does not lose them.
+//! The sender does the file I/O on a blocking thread: queueing a report from a
+//! request handler only touches memory.
+
+use super::state::AppState;
+use crate::{GameYError, YEN};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tokio::sync::Notify;
+use tracing::warn;
+
+/// Time the sender waits for new reports when nothing is pending.
+const IDLE_WAIT: Duration = Duration::from_secs(60);
+/// Time a single delivery may take before it counts as failed.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Result of one player in a finished match.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FinishedMatchPlayer {
+    pub user_id: String,
+    pub result: String,
+}
+
+/// Body of the stats service `POST /internal/v1/matches/finished` request.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FinishedMatchRequest {
+    pub game_id: String,
+    pub mode: Option<String>,
+    pub bot_id: Option<String>,
+    pub reason: Option<String>,
+    pub winner_id: Option<String>,
+    pub final_board: Option<YEN>,
+    /// Position the game started from, when it was not the empty board.
+    #[serde(default)]
+    pub start_position: Option<YEN>,
+    pub players: Vec<FinishedMatchPlayer>,
+    /// When the game ended, in milliseconds since the Unix epoch.
+    pub ended_at: u64,
+}
+
+/// A report waiting to be delivered.
+#[derive(Debug, Clone)]
+struct PendingReport {
+    report: FinishedMatchRequest,
+    /// Failed deliveries so far.
+    attempts: u32,
+    next_attempt_at: Instant,
+    /// Set until the sender has tried to write the report to the outbox
+    /// directory.
+    unsaved: bool,
+}
+
+/// Queue of finished-match reports not yet accepted by the stats service.
+pub struct StatsOutbox {
+    pending: Mutex<HashMap<String, PendingReport>>,
+    /// Directory pending reports are written to, if any.
+    dir: Option<PathBuf>,
+    /// Wakes the sender when a report is queued.
+    wake: Notify,
+}
+
+impl StatsOutbox {
+    /// Creates an outbox that keeps reports in memory only.
+    pub fn in_memory() -> Self {
+        Self {
+            pending: Mutex::new(HashMap::new()),
+            dir: None,
+            wake: Notify::new(),
+        }
+    }
+
+    /// Creates an outbox that writes reports to `<dir>/<game_id>.json`, and
+    /// queues the reports already stored there.
+    ///
+    /// Unreadable files are skipped with a warning.
+    pub fn persistent(dir: impl Into<PathBuf>) -> Result<Self, GameYError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| GameYError::IoError {
+            message: format!("Failed to create stats outbox directory: {}", dir.display()),
+            error: e.to_string(),
+        })?;
+        let entries = std::fs::read_dir(&dir).map_err(|e| GameYError::IoError {
+            message: format!("Failed to read stats outbox directory: {}", dir.display()),
+            error: e.to_string(),
+        })?;
+
+        let now = Instant::now();
+        let mut pending = HashMap::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path).map_err(|e| GameYError::IoError {
+                message: format!("Failed to read file: {}", path.display()),
+                error: e.to_string(),
+            })?;
+            match serde_json::from_str::<FinishedMatchRequest>(&content) {
+                Ok(report) => {
+                    pending.insert(
+                        report.game_id.clone(),
+                        PendingReport {
+                            report,
+                            attempts: 0,
+                            next_attempt_at: now,
+                            unsaved: false,
+                        },
+                    );
+                }
+                Err(error) => warn!(
+                    "Skipping unreadable stats report {}: {}",
+                    path.display(),
+                    error
+                ),
+            }
+        }
+
+        Ok(Self {
+            pending: Mutex::new(pending),
+            dir: Some(dir),
+            wake: Notify::new(),
+        })
+    }
+
+    /// Queues a report for delivery. Returns `false` if a report for the same
+    /// game is already pending.
+    ///
+    /// The sender writes the report to disk afterwards; one that cannot be
+    /// written is still kept in memory.
+    pub fn enqueue(&self, report: FinishedMatchRequest) -> bool {
+        {
+            let mut pending = self.lock();
+            if pending.contains_key(&report.game_id) {
+                return false;
+            }
+            pending.insert(
+                report.game_id.clone(),
+                PendingReport {
+                    report,
+                    attempts: 0,
+                    next_attempt_at: Instant::now(),
+                    unsaved: true,
+                },
+            );
+        }
+        self.wake.notify_one();
+        true
+    }
+
+    /// Returns the number of reports waiting to be delivered.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if no report is waiting to be delivered.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns the reports whose next attempt is due at `now`.
+    fn due(&self, now: Instant) -> Vec<FinishedMatchRequest> {
+        self.lock()
+            .values()
+            .filter(|pending| pending.next_attempt_at <= now)
+            .map(|pending| pending.report.clone())
+            .collect()
+    }
+
+    /// Returns when the next pending report is due.
+    fn next_attempt_at(&self) -> Option<Instant> {
+        self.lock()
+            .values()
+            .map(|pending| pending.next_attempt_at)
+            .min()
+    }
+
+    /// Writes the reports queued since the last call to the outbox directory,
+    /// on a blocking thread and without holding the lock.
+    async fn save_new_reports(&self) {
+        let Some(dir) = self.dir.clone() else {
+            return;
+        };
+        let unsaved: Vec<FinishedMatchRequest> = {
+            let mut pending = self.lock();
+            pending
+                .values_mut()
+                .filter(|pending| pending.unsaved)
+                .map(|pending| {
+                    pending.unsaved = false;
+                    pending.report.clone()
+                })
+                .collect()
+        };
+        if unsaved.is_empty() {
+            return;
+        }
+
+        let written = tokio::task::spawn_blocking(move || {
+            for report in unsaved {
+                if let Err(error) = write_report(&dir, &report) {
+                    warn!(
+                        "Could not store stats report for game {}: {}",
+                        report.game_id, error
+                    );
+                }
+            }
+        })
+        .await;
+        if let Err(error) = written {
+            warn!("Could not store stats reports: {}", error);
+        }
+    }
+
+    /// Removes a report that no longer needs to be sent.
+    async fn remove(&self, game_id: &str) {
+        self.lock().remove(game_id);
+        let Some(dir) = self.dir.clone() else {
+            return;
+        };
+        let file_game_id = game_id.to_string();
+        let deleted = tokio::task::spawn_blocking(move || delete_report(&dir, &file_game_id))
+            .await
+            .unwrap_or_else(|e| {
+                Err(GameYError::ServerError {
+                    message: e.to_string(),
+                })
+            });
+        if let Err(error) = deleted {
+            warn!(
+                "Could not remove stored stats report for game {}: {}",
+                game_id, error
+            );
+        }
+    }
+
+    /// Schedules the next attempt of a report whose delivery failed.
+    fn retry_later(&self, game_id: &str, now: Instant, initial: Duration, max: Duration) {
+        if let Some(pending) = self.lock().get_mut(game_id) {
+            pending.attempts = pending.attempts.saturating_add(1);
+            pending.next_attempt_at = now + retry_delay(pending.attempts, initial, max);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, PendingReport>> {
+        self.pending
+            .lock()
+            .expect("stats outbox mutex should not be poisoned")
+    }
+}
+
+/// Writes `report` to `<dir>/<game_id>.json`, replacing the file atomically.
+fn write_report(dir: &Path, report: &FinishedMatchRequest) -> Result<(), GameYError> {
+    let content = serde_json::to_string(report).map_err(|e| GameYError::SerdeError { error: e })?;
+    let path = dir.join(format!("{}.json", report.game_id));
+    let tmp_path = dir.join(format!(".{}.json.tmp", report.game_id));
+
+    std::fs::write(&tmp_path, content).map_err(|e| GameYError::IoError {
+        message: format!("Failed to write file: {}", tmp_path.display()),
+        error: e.to_string(),
+    })?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| GameYError::IoError {
+        message: format!("Failed to replace file: {}", path.display()),
+        error: e.to_string(),
+    })
+}
+
+/// Deletes the stored report of `game_id`, if there is one.
+fn delete_report(dir: &Path, game_id: &str) -> Result<(), GameYError> {
+    let path = dir.join(format!("{}.json", game_id));
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(GameYError::IoError {
+            message: format!("Failed to delete file: {}", path.display()),
+            error: e.to_string(),
+        }),
+    }
+}
+
+impl Default for StatsOutbox {
+    fn default() -> Self {
+        Self::in_memory()
+    }
+}
+
+/// What to do with a report after a delivery attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeliveryOutcome {
+    /// The stats service recorded the match.
+    Delivered,
+    /// The attempt failed and may succeed later.
+    Retry,
+    /// The stats service rejected the report as invalid.
+    Rejected,
+}
+
+/// Spawns the background task that delivers the reports queued in the
+/// outbox of `state`.
+pub fn start_stats_outbox_sender(state: AppState) {
+    tokio::spawn(async move {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        let outbox = state.stats_outbox();
+        loop {
+            outbox.save_new_reports().await;
+            deliver_due_reports(&state, &client, Instant::now()).await;
+
+            let wait = outbox
+                .next_attempt_at()
+                .map(|at| at.saturating_duration_since(Instant::now()))
+                .unwrap_or(IDLE_WAIT);
+            tokio::select! {
+                _ = outbox.wake.notified() => {}
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+    });
+}
+
+/// Sends every report that is due at `now` once.
+async fn deliver_due_reports(state: &AppState, client: &reqwest::Client, now: Instant) {
+    let config = state.config();
+    let outbox = state.stats_outbox();
+    for report in outbox.due(now) {
+        match deliver_report(state, client, &report).await {
+            DeliveryOutcome::Delivered | DeliveryOutcome::Rejected => {
+                outbox.remove(&report.game_id).await
+            }
+            DeliveryOutcome::Retry => outbox.retry_later(
+                &report.game_id,
+                Instant::now(),
+                config.stats.retry_initial_backoff,
+                config.stats.retry_max_backoff,
+            ),
+        }
+    }
+}
+
+async fn deliver_report(
+    state: &AppState,
+    client: &reqwest::Client,
+    report: &FinishedMatchRequest,
+) -> DeliveryOutcome {
+    let config = state.config();
+    let endpoint = format!(
+        "{}/internal/v1/matches/finished",
+        config.stats.url.trim_end_matches('/')
+    );
+
+    state.metrics().inc_stats_report_attempts();
+    let outcome = match client
+        .post(&endpoint)
+        .header("x-service-token", &config.stats.internal_token)
+        .json(report)
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => return DeliveryOutcome::Delivered,
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            let outcome = outcome_for_status(status);
+            warn!(
+                "Failed to report finished game {} to stats. status={} body={}{}",
+                report.game_id,
+                status,
+                body,
+                if outcome == DeliveryOutcome::Rejected {
+                    " (dropped)"
+                } else {
+                    ""
+                }
+            );
+            outcome
+        }
+        Err(error) => {
+            warn!(
+                "Could not report finished game {} to stats: {}",
+                report.game_id, error
+            );
+            DeliveryOutcome::Retry
+        }
+    };
+    state.metrics().inc_stats_report_failures();
+    outcome
+}
+
+/// Classifies an unsuccessful response. Only a malformed report is dropped:
+/// anything else, including auth errors, may be fixed on the stats side.
+fn outcome_for_status(status: StatusCode) -> DeliveryOutcome {
+    match status {
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => DeliveryOutcome::Rejected,
+        _ => DeliveryOutcome::Retry,
+    }
+}
+
+/// Delay before the next attempt after `attempts` failed ones: `initial`,
+/// doubled after every failure, and never more than `max`.
+fn retry_delay(attempts: u32, initial: Duration, max: Duration) -> Duration {
+    let doublings = attempts.saturating_sub(1).min(31);
+    initial.saturating_mul(1 << doublings).min(max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn report(game_id: &str) -> FinishedMatchRequest {
+        FinishedMatchRequest {
+            game_id: game_id.to_string(),
+            mode: Some("local_human_vs_human".to_string()),
+            bot_id: None,
+            reason: Some("win_condition".to_string()),
+            winner_id: Some("alice".to_string()),
+            final_board: None,
+            start_position: None,
+            players: vec![
+                FinishedMatchPlayer {
+                    user_id: "alice".to_string(),
+                    result: "win".to_string(),
+                },
+                FinishedMatchPlayer {
+                    user_id: "bob".to_string(),
+                    result: "loss".to_string(),
+                },
+            ],
+            ended_at: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_enqueue_ignores_games_already_pending() {
+        let outbox = StatsOutbox::in_memory();
+
+        assert!(outbox.enqueue(report("game-1")));
+        assert!(!outbox.enqueue(report("game-1")));
+        assert!(outbox.enqueue(report("game-2")));
+
+        assert_eq!(outbox.len(), 2);
+    }
+
+    #[test]
+    fn test_failed_report_is_not_due_until_its_backoff_ends() {
+        let outbox = StatsOutbox::in_memory();
+        outbox.enqueue(report("game-1"));
+        let now = Instant::now();
+
+        outbox.retry_later(
+            "game-1",
+            now,
+            Duration::from_secs(1),
+            Duration::from_secs(60),
+        );
+
+        assert!(outbox.due(now).is_empty());
+        assert_eq!(outbox.next_attempt_at(), Some(now + Duration::from_secs(1)));
+        assert_eq!(outbox.due(now + Duration::from_secs(1)).len(), 1);
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_the_maximum() {
+        let initial = Duration::from_secs(1);
+        let max = Duration::from_secs(30);
+
+        assert_eq!(retry_delay(1, initial, max), Duration::from_secs(1));
+        assert_eq!(retry_delay(2, initial, max), Duration::from_secs(2));
+        assert_eq!(retry_delay(5, initial, max), Duration::from_secs(16));
+        assert_eq!(retry_delay(6, initial, max), max);
+        assert_eq!(retry_delay(u32::MAX, initial, max), max);
+    }
+
+    #[test]
+    fn test_only_malformed_reports_are_dropped() {
+        assert_eq!(
+            outcome_for_status(StatusCode::BAD_REQUEST),
+            DeliveryOutcome::Rejected
+        );
+        assert_eq!(
+            outcome_for_status(StatusCode::UNAUTHORIZED),
+            DeliveryOutcome::Retry
+        );
+        assert_eq!(
+            outcome_for_status(StatusCode::SERVICE_UNAVAILABLE),
+            DeliveryOutcome::Retry
+        );
+    }
+
+    #[test]
+    fn test_enqueue_does_not_touch_the_disk() {
+        let dir = tempdir().unwrap();
+        let outbox = StatsOutbox::persistent(dir.path()).unwrap();
+
+        outbox.enqueue(report("game-1"));
+
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+        assert_eq!(outbox.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_outbox_reloads_pending_reports() {
+        let dir = tempdir().unwrap();
+        let outbox = StatsOutbox::persistent(dir.path()).unwrap();
+        outbox.enqueue(report("game-1"));
+        outbox.enqueue(report("game-2"));
+        outbox.save_new_reports().await;
+        outbox.remove("game-2").await;
+
+        let reloaded = StatsOutbox::persistent(dir.path()).unwrap();
+
+        let due = reloaded.due(Instant::now());
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].game_id, "game-1");
+        assert_eq!(due[0].players[0].user_id, "alice");
+    }
+
+    #[test]
+    fn test_report_serialization_uses_stats_field_names() {
+        let json = serde_json::to_value(report("game-1")).unwrap();
+
+        assert_eq!(json["gameId"], "game-1");
+        assert_eq!(json["winnerId"], "alice");
+        assert_eq!(json["endedAt"], 1_700_000_000_000_u64);
+        assert_eq!(json["players"][1]["userId"], "bob");
+    }
+}
@@ -0,0 +1,622 @@
+//! Persistent storage for game sessions.
+//!
+//! Live sessions are served from the in-memory map in [`AppState`]. When a
+//! [`GameStore`] is configured, every change to a session is queued on a
+//! [`GameStoreWriter`], which writes it through on its own thread, and the
+//! stored sessions are reloaded with [`restore_games`] when the server starts,
+//! so a restart does not forfeit running matches. Without a store nothing is
+//! persisted.
+//!
+//! Two stores are provided:
+//! - [`InMemoryGameStore`]: keeps sessions in a map; nothing survives a restart.
+//! - [`FileGameStore`]: writes one JSON file per game into a directory.
+//!
+//! A restored `human_vs_bot` game that was waiting for a bot move gets it
+//! from the online game monitor, which restarts lost bot turns.
+//!
+//! Matchmaking tickets are not persisted: players still waiting in the queue
+//! must enqueue again after a restart.
+
+use super::{
+    clock::{ClockState, GameClock},
+    exhibition::start_exhibition,
+    games::{GameMode, game_mode, register_active_game_for_session_users},
+    state::{AppState, BotTurnStatus, GameCompletionReason, GameSession},
+    version::SUPPORTED_VERSION,
+};
+use crate::{GameY, GameYError, Movement, YEN};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, mpsc},
+    time::{Duration, Instant},
+};
+use tokio::sync::oneshot;
+use tracing::warn;
+
+/// A backend able to persist game sessions across server restarts.
+pub trait GameStore: Send + Sync {
+    /// Returns every stored session together with its game id.
+    fn load_all(&self) -> Result<Vec<(String, PersistedGameSession)>, GameYError>;
+
+    /// Stores the session, replacing any previous version of the same game.
+    fn save(&self, game_id: &str, session: &PersistedGameSession) -> Result<(), GameYError>;
+
+    /// Removes the stored session. Removing an unknown game is not an error.
+    fn delete(&self, game_id: &str) -> Result<(), GameYError>;
+}
+
+/// Serializable snapshot of a [`GameSession`].
+///
+/// The game is stored as its starting position plus the move history, so the
+/// union-find groups and the undo history are rebuilt exactly on reload. Timers
+/// are stored as ages at save time, which means the time the server was down
+/// does not count against the players.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PersistedGameSession {
+    /// Position the move history starts from.
+    pub start: YEN,
+    /// Moves played from the starting position.
+    pub history: Vec<Movement>,
+    /// Current position, used to check the replayed history.
+    pub yen: YEN,
+    /// Bot playing each seat, indexed by player id.
+    #[serde(default)]
+    pub bots: [Option<String>; 2],
+    pub player_tokens: Option<HashMap<u32, String>>,
+    pub player0_user_id: Option<String>,
+    pub player1_user_id: Option<String>,
+    pub stats_reported: bool,
+    pub completion_reason: Option<GameCompletionReason>,
+    /// Milliseconds since the session was created.
+    pub created_age_ms: u64,
+    /// Milliseconds since the current online turn started.
+    pub turn_started_age_ms: Option<u64>,
+    /// Milliseconds since each online player was last seen.
+    pub last_seen_age_ms_by_player_id: Option<HashMap<u32, u64>>,
+    /// Milliseconds since the last move or action.
+    #[serde(default)]
+    pub last_activity_age_ms: u64,
+    /// Milliseconds since the game finished.
+    #[serde(default)]
+    pub finished_age_ms: Option<u64>,
+    #[serde(default = "default_allow_spectators")]
+    pub allow_spectators: bool,
+    /// Clock readings at save time. The running clock restarts on reload.
+    #[serde(default)]
+    pub clock: Option<ClockState>,
+}
+
+impl PersistedGameSession {
+    /// Takes a snapshot of the session, measuring timers against `now`.
+    pub fn from_session(session: &GameSession, now: Instant) -> Self {
+        Self {
+            start: session.game.starting_position(),
+            history: session.game.history().to_vec(),
+            yen: (&session.game).into(),
+            bots: session.bots.clone(),
+            player_tokens: session.player_tokens.clone(),
+            player0_user_id: session.player0_user_id.clone(),
+            player1_user_id: session.player1_user_id.clone(),
+            stats_reported: session.stats_reported,
+            completion_reason: session.completion_reason,
+            created_age_ms: age_ms(session.created_at, now),
+            turn_started_age_ms: session
+                .turn_started_at
+                .map(|started_at| age_ms(started_at, now)),
+            last_seen_age_ms_by_player_id: session.last_seen_at_by_player_id.as_ref().map(
+                |last_seen| {
+                    last_seen
+                        .iter()
+                        .map(|(player_id, seen_at)| (*player_id, age_ms(*seen_at, now)))
+                        .collect()
+                },
+            ),
+            last_activity_age_ms: age_ms(session.last_activity_at, now),
+            finished_age_ms: session
+                .finished_at
+                .map(|finished_at| age_ms(finished_at, now)),
+            allow_spectators: session.allow_spectators,
+            clock: session.clock.as_ref().map(|clock| clock.state(now)),
+        }
+    }
+
+    /// Rebuilds the session by replaying its history, with timers restarted from `now`.
+    ///
+    /// Returns `GameYError::StorageError` if the replayed game does not match the
+    /// stored position.
+    pub fn into_session(self, now: Instant) -> Result<GameSession, GameYError> {
+        let mut game = GameY::try_from(self.start)?;
+        for movement in self.history {
+            game.add_move(movement)?;
+        }
+
+        let replayed: YEN = (&game).into();
+        if replayed.layout() != self.yen.layout() || replayed.turn() != self.yen.turn() {
+            return Err(GameYError::StorageError {
+                message: format!(
+                    "replayed position {} does not match stored position {}",
+                    replayed.layout(),
+                    self.yen.layout()
+                ),
+            });
+        }
+
+        Ok(GameSession {
+            game,
+            bots: self.bots,
+            created_at: instant_from_age(now, self.created_age_ms),
+            turn_started_at: self
+                .turn_started_age_ms
+                .map(|age| instant_from_age(now, age)),
+            player_tokens: self.player_tokens,
+            last_seen_at_by_player_id: self.last_seen_age_ms_by_player_id.map(|last_seen| {
+                last_seen
+                    .into_iter()
+                    .map(|(player_id, age)| (player_id, instant_from_age(now, age)))
+                    .collect()
+            }),
+            player0_user_id: self.player0_user_id,
+            player1_user_id: self.player1_user_id,
+            stats_reported: self.stats_reported,
+            completion_reason: self.completion_reason,
+            last_activity_at: instant_from_age(now, self.last_activity_age_ms),
+            finished_at: self
+                .finished_age_ms
+                .map(|age| instant_from_age(now, age)),
+            allow_spectators: self.allow_spectators,
+            clock: self.clock.map(|clock| GameClock::from_state(&clock, now)),
+            bot_turn: BotTurnStatus::Idle,
+        })
+    }
+}
+
+/// Game store that keeps sessions in memory only.
+///
+/// Sessions are lost when the process exits.
+#[derive(Default)]
+pub struct InMemoryGameStore {
+    sessions: Mutex<HashMap<String, PersistedGameSession>>,
+}
+
+impl InMemoryGameStore {
+    /// Creates an empty in-memory store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl GameStore for InMemoryGameStore {
+    fn load_all(&self) -> Result<Vec<(String, PersistedGameSession)>, GameYError> {
+        let sessions = self
+            .sessions
+            .lock()
+            .expect("game store mutex should not be poisoned");
+        Ok(sessions
+            .iter()
+            .map(|(game_id, session)| (game_id.clone(), session.clone()))
+            .collect())
+    }
+
+    fn save(&self, game_id: &str, session: &PersistedGameSession) -> Result<(), GameYError> {
+        self.sessions
+            .lock()
+            .expect("game store mutex should not be poisoned")
+            .insert(game_id.to_string(), session.clone());
+        Ok(())
+    }
+
+    fn delete(&self, game_id: &str) -> Result<(), GameYError> {
+        self.sessions
+            .lock()
+            .expect("game store mutex should not be poisoned")
+            .remove(game_id);
+        Ok(())
+    }
+}
+
+/// Game store that writes each session to `<dir>/<game_id>.json`.
+///
+/// Files are written to a temporary file first and then renamed, so a crash
+/// while saving never leaves a half-written session behind.
+pub struct FileGameStore {
+    dir: PathBuf,
+}
+
+impl FileGameStore {
+    /// Creates a store in `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, GameYError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| GameYError::IoError {
+            message: format!("Failed to create storage directory: {}", dir.display()),
+            error: e.to_string(),
+        })?;
+        Ok(Self { dir })
+    }
+
+    fn session_path(&self, game_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", game_id))
+    }
+}
+
+impl GameStore for FileGameStore {
+    fn load_all(&self) -> Result<Vec<(String, PersistedGameSession)>, GameYError> {
+        let entries = std::fs::read_dir(&self.dir).map_err(|e| GameYError::IoError {
+            message: format!("Failed to read storage directory: {}", self.dir.display()),
+            error: e.to_string(),
+        })?;
+
+        let mut sessions = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(game_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let content = std::fs::read_to_string(&path).map_err(|e| GameYError::IoError {
+                message: format!("Failed to read file: {}", path.display()),
+                error: e.to_string(),
+            })?;
+            match serde_json::from_str::<PersistedGameSession>(&content) {
+                Ok(session) => sessions.push((game_id.to_string(), session)),
+                Err(error) => warn!(
+                    "Skipping unreadable stored session {}: {}",
+                    path.display(),
+                    error
+                ),
+            }
+        }
+        Ok(sessions)
+    }
+
+    fn save(&self, game_id: &str, session: &PersistedGameSession) -> Result<(), GameYError> {
+        let content =
+            serde_json::to_string(session).map_err(|e| GameYError::SerdeError { error: e })?;
+        let path = self.session_path(game_id);
+        let tmp_path = self.dir.join(format!(".{}.json.tmp", game_id));
+
+        std::fs::write(&tmp_path, content).map_err(|e| GameYError::IoError {
+            message: format!("Failed to write file: {}", tmp_path.display()),
+            error: e.to_string(),
+        })?;
+        std::fs::rename(&tmp_path, &path).map_err(|e| GameYError::IoError {
+            message: format!("Failed to replace file: {}", path.display()),
+            error: e.to_string(),
+        })
+    }
+
+    fn delete(&self, game_id: &str) -> Result<(), GameYError> {
+        let path = self.session_path(game_id);
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(GameYError::IoError {
+                message: format!("Failed to delete file: {}", path.display()),
+                error: e.to_string(),
+            }),
+        }
+    }
+}
+
+enum StoreCommand {
+    Save {
+        game_id: String,
+        session: Box<PersistedGameSession>,
+    },
+    Delete {
+        game_id: String,
+    },
+    Flush(oneshot::Sender<()>),
+}
+
+/// Writes sessions to a [`GameStore`] on a dedicated thread.
+///
+/// Handlers only queue a snapshot, so a slow disk never blocks the async
+/// workers or the session lock. Commands are applied in the order they were
+/// queued, which keeps the stored version of a game from going backwards.
+/// Storage failures are logged: the session is still served from memory.
+pub struct GameStoreWriter {
+    store: Arc<dyn GameStore>,
+    commands: mpsc::Sender<StoreCommand>,
+}
+
+impl GameStoreWriter {
+    /// Starts the writer thread for `store`. The thread exits once the writer
+    /// is dropped and the queued commands are applied.
+    pub fn new(store: Arc<dyn GameStore>) -> Self {
+        let (commands, queued) = mpsc::channel();
+        let thread_store = Arc::clone(&store);
+        std::thread::Builder::new()
+            .name("game-store-writer".to_string())
+            .spawn(move || apply_store_commands(thread_store.as_ref(), queued))
+            .expect("game store writer thread should start");
+        Self { store, commands }
+    }
+
+    /// Returns the store the sessions are written to.
+    pub fn store(&self) -> Arc<dyn GameStore> {
+        Arc::clone(&self.store)
+    }
+
+    /// Queues the session to be saved, replacing any previous version.
+    pub fn save(&self, game_id: &str, session: PersistedGameSession) {
+        self.send(StoreCommand::Save {
+            game_id: game_id.to_string(),
+            session: Box::new(session),
+        });
+    }
+
+    /// Queues the stored session to be removed.
+    pub fn delete(&self, game_id: &str) {
+        self.send(StoreCommand::Delete {
+            game_id: game_id.to_string(),
+        });
+    }
+
+    /// Waits until every command queued so far has been applied.
+    pub async fn flush(&self) {
+        let (done, applied) = oneshot::channel();
+        self.send(StoreCommand::Flush(done));
+        let _ = applied.await;
+    }
+
+    fn send(&self, command: StoreCommand) {
+        if self.commands.send(command).is_err() {
+            warn!("Game store writer has stopped; the change was not persisted");
+        }
+    }
+}
+
+fn apply_store_commands(store: &dyn GameStore, commands: mpsc::Receiver<StoreCommand>) {
+    for command in commands {
+        match command {
+            StoreCommand::Save { game_id, session } => {
+                if let Err(error) = store.save(&game_id, &session) {
+                    warn!("Could not persist game {}: {}", game_id, error);
+                }
+            }
+            StoreCommand::Delete { game_id } => {
+                if let Err(error) = store.delete(&game_id) {
+                    warn!("Could not delete game {} from storage: {}", game_id, error);
+                }
+            }
+            StoreCommand::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+/// Loads every stored session into the state and returns how many were restored.
+///
+/// Unfinished games are registered again in the active-game index, and
+/// unfinished exhibitions resume. Sessions that cannot be replayed are skipped
+/// with a warning. Nothing is restored when the state has no game store.
+pub async fn restore_games(state: &AppState) -> Result<usize, GameYError> {
+    let Some(store) = state.game_store() else {
+        return Ok(0);
+    };
+    let stored = tokio::task::spawn_blocking(move || store.load_all())
+        .await
+        .map_err(|e| GameYError::StorageError {
+            message: format!("Loading stored games failed: {}", e),
+        })??;
+    let now = Instant::now();
+    let mut restored = 0;
+
+    for (game_id, persisted) in stored {
+        let session = match persisted.into_session(now) {
+            Ok(session) => session,
+            Err(error) => {
+                warn!("Skipping stored game {}: {}", game_id, error);
+                continue;
+            }
+        };
+
+        let finished = session.game.check_game_over();
+        state.insert_game(game_id.clone(), session.clone()).await;
+        if !finished {
+            register_active_game_for_session_users(state, &game_id, &session).await;
+            if game_mode(&session) == GameMode::BotVsBot {
+                start_exhibition(
+                    state.clone(),
+                    SUPPORTED_VERSION.to_string(),
+                    game_id,
+                    state.exhibitions().take(),
+                );
+            }
+        }
+        restored += 1;
+    }
+
+    Ok(restored)
+}
+
+fn default_allow_spectators() -> bool {
+    true
+}
+
+fn age_ms(at: Instant, now: Instant) -> u64 {
+    now.saturating_duration_since(at)
+        .as_millis()
+        .min(u128::from(u64::MAX)) as u64
+}
+
+fn instant_from_age(now: Instant, age_ms: u64) -> Instant {
+    now.checked_sub(Duration::from_millis(age_ms))
+        .unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_server::clock::TimeControl;
+    use crate::{Coordinates, GameAction, PlayerId, YBotRegistry};
+
+    fn online_session() -> GameSession {
+        let mut game = GameY::new(3);
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::new(2, 0, 0),
+        })
+        .unwrap();
+        game.add_move(Movement::Action {
+            player: PlayerId::new(1),
+            action: GameAction::PassTurn,
+        })
+        .unwrap();
+
+        let now = Instant::now();
+        GameSession {
+            created_at: now - Duration::from_secs(30),
+            turn_started_at: Some(now - Duration::from_secs(5)),
+            player_tokens: Some(HashMap::from([
+                (0, "token-a".to_string()),
+                (1, "token-b".to_string()),
+            ])),
+            last_seen_at_by_player_id: Some(HashMap::from([(1, now - Duration::from_secs(10))])),
+            player0_user_id: Some("alice".to_string()),
+            player1_user_id: Some("bob".to_string()),
+            ..GameSession::for_test(game)
+        }
+    }
+
+    #[test]
+    fn test_persisted_session_roundtrip_keeps_game_and_timers() {
+        let session = online_session();
+        let now = Instant::now();
+
+        let json =
+            serde_json::to_string(&PersistedGameSession::from_session(&session, now)).unwrap();
+        let persisted: PersistedGameSession = serde_json::from_str(&json).unwrap();
+        let restored = persisted.into_session(now).unwrap();
+
+        assert_eq!(restored.game.history().len(), 2);
+        assert_eq!(restored.game.next_player(), Some(PlayerId::new(0)));
+        assert_eq!(restored.game.available_cells().len(), 5);
+        assert_eq!(restored.player_tokens, session.player_tokens);
+        assert_eq!(restored.player0_user_id.as_deref(), Some("alice"));
+        let turn_age = now.duration_since(restored.turn_started_at.unwrap());
+        assert!(turn_age >= Duration::from_secs(5) && turn_age < Duration::from_secs(6));
+        let seen_age = now.duration_since(restored.last_seen_at_by_player_id.unwrap()[&1]);
+        assert!(seen_age >= Duration::from_secs(10) && seen_age < Duration::from_secs(11));
+    }
+
+    #[test]
+    fn test_persisted_session_keeps_clock_readings() {
+        let mut session = online_session();
+        let now = Instant::now();
+        session.clock = Some(GameClock::new(
+            TimeControl::ByoYomi {
+                base_ms: 60_000,
+                period_ms: 10_000,
+                periods: 3,
+            },
+            session.game.next_player(),
+            now - Duration::from_secs(5),
+        ));
+
+        let json =
+            serde_json::to_string(&PersistedGameSession::from_session(&session, now)).unwrap();
+        let persisted: PersistedGameSession = serde_json::from_str(&json).unwrap();
+        let restored = persisted.into_session(now).unwrap();
+
+        let clock = restored.clock.unwrap().state(now);
+        assert_eq!(clock.remaining_ms, [55_000, 60_000]);
+        assert_eq!(clock.periods_left, [3, 3]);
+        assert_eq!(clock.running_player, Some(0));
+    }
+
+    #[test]
+    fn test_into_session_rejects_mismatching_position() {
+        let session = online_session();
+        let mut persisted = PersistedGameSession::from_session(&session, Instant::now());
+        persisted.yen = YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string());
+
+        let result = persisted.into_session(Instant::now());
+
+        assert!(matches!(result, Err(GameYError::StorageError { .. })));
+    }
+
+    #[test]
+    fn test_file_store_save_load_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileGameStore::new(dir.path().join("games")).unwrap();
+        let persisted = PersistedGameSession::from_session(&online_session(), Instant::now());
+
+        store.save("game-7", &persisted).unwrap();
+        store.save("game-7", &persisted).unwrap();
+        std::fs::write(dir.path().join("games").join("notes.txt"), "ignored").unwrap();
+
+        let loaded = store.load_all().unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].0, "game-7");
+        assert_eq!(loaded[0].1.history.len(), 2);
+
+        store.delete("game-7").unwrap();
+        store.delete("game-7").unwrap();
+        assert!(store.load_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_file_store_skips_corrupted_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileGameStore::new(dir.path()).unwrap();
+        std::fs::write(dir.path().join("game-1.json"), "{ not json").unwrap();
+
+        assert!(store.load_all().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_writer_applies_saves_and_deletes_in_order() {
+        let store = Arc::new(InMemoryGameStore::new());
+        let writer = GameStoreWriter::new(store.clone());
+        let persisted = PersistedGameSession::from_session(&online_session(), Instant::now());
+
+        writer.save("game-1", persisted.clone());
+        writer.save("game-2", persisted);
+        writer.delete("game-1");
+        writer.flush().await;
+
+        let stored = store.load_all().unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].0, "game-2");
+    }
+
+    #[tokio::test]
+    async fn test_state_without_store_persists_nothing() {
+        let state = AppState::new(YBotRegistry::new());
+
+        state.persist_game("game-1", &online_session());
+        state.flush_game_store().await;
+
+        assert!(state.game_store().is_none());
+        assert_eq!(restore_games(&state).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_restore_games_rebuilds_sessions_and_active_game_index() {
+        let store = Arc::new(InMemoryGameStore::new());
+        let persisted = PersistedGameSession::from_session(&online_session(), Instant::now());
+        store.save("game-41", &persisted).unwrap();
+
+        let state = AppState::new(YBotRegistry::new()).with_game_store(store);
+        let restored = restore_games(&state).await.unwrap();
+
+        assert_eq!(restored, 1);
+        assert!(state.games().read().await.contains_key("game-41"));
+        assert_eq!(
+            state
+                .active_game_id_by_user_id()
+                .read()
+                .await
+                .get("alice")
+                .map(String::as_str),
+            Some("game-41")
+        );
+    }
+}
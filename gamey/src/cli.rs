@@ -1,14 +1,15 @@
 //! Command-line interface for the Y game.
 //!
 //! This module provides the CLI application for playing Y games interactively.
-//! It supports three modes:
+//! It supports four modes:
 //! - Human vs Human: Two players take turns at the same terminal
 //! - Human vs Computer: Play against a bot
 //! - Server: Run as an HTTP server for bot API
+//! - Arena: Play bots against each other and rate them
 
 use crate::{
-    BiasedRandomBot, Coordinates, GameAction, GreedyBot, MinimaxBot, Movement, RandomBot,
-    RenderOptions, YBot, YBotRegistry, game,
+    ArenaConfig, BiasedRandomBot, Coordinates, GameAction, GreedyBot, MctsBot, MinimaxBot,
+    Movement, RandomBot, RenderOptions, YBot, YBotRegistry, game, run_arena,
 };
 use crate::{GameStatus, GameY, PlayerId};
 use anyhow::Result;
@@ -16,7 +17,10 @@ use clap::{Parser, ValueEnum};
 use rustyline::DefaultEditor;
 use rustyline::error::ReadlineError;
 use std::fmt::Display;
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Command-line arguments for the GameY application.
 #[derive(Parser, Debug)]
@@ -35,9 +39,60 @@ pub struct CliArgs {
     #[arg(short, long, default_value = "random_bot")]
     pub bot: String,
 
-    /// Port to run the server on (only used with --mode=server)
-    #[arg(short, long, default_value_t = 3000)]
-    pub port: u16,
+    /// Port to run the server on (only used with --mode=server), default =
+    /// the config file, GAMEY_PORT or 3000
+    #[arg(short, long)]
+    pub port: Option<u16>,
+
+    /// Comma-separated bots to pit against each other (only used with
+    /// --mode=arena), default = all bots
+    #[arg(long, value_delimiter = ',')]
+    pub arena_bots: Vec<String>,
+
+    /// Comma-separated board sizes for the arena, default = --size
+    #[arg(long, value_delimiter = ',')]
+    pub arena_sizes: Vec<u32>,
+
+    /// Games per pair of bots and board size in the arena
+    #[arg(long, default_value_t = 20)]
+    pub games: u32,
+
+    /// Random opening stones placed before the bots play each arena game
+    #[arg(long, default_value_t = 0)]
+    pub random_openings: u32,
+
+    /// Seed for the arena's random openings (only used with
+    /// --random-openings); the bots' own moves are not seeded
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Time each bot may spend on a move in the arena, in milliseconds
+    #[arg(long)]
+    pub time_limit_ms: Option<u64>,
+
+    /// File to write the arena report to; `.csv` files get CSV, others JSON
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    /// TOML file with the server configuration (only used with --mode=server)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// IP address the server listens on, overriding the configuration
+    #[arg(long)]
+    pub bind_address: Option<IpAddr>,
+
+    /// Directory to store game sessions in, overriding the configuration
+    #[arg(long)]
+    pub storage_dir: Option<PathBuf>,
+
+    /// Comma-separated bots the server offers, overriding the configuration
+    #[arg(long, value_delimiter = ',')]
+    pub server_bots: Vec<String>,
+
+    /// Base URL of the stats service, overriding the configuration
+    #[arg(long)]
+    pub stats_url: Option<String>,
 }
 
 /// The game mode determining how the game is played.
@@ -49,6 +104,8 @@ pub enum Mode {
     Human,
     /// Run as an HTTP server for bot API.
     Server,
+    /// Play bots against each other and report their strength.
+    Arena,
 }
 
 impl Display for Mode {
@@ -57,11 +114,69 @@ impl Display for Mode {
             Mode::Computer => "computer",
             Mode::Human => "human",
             Mode::Server => "server",
+            Mode::Arena => "arena",
         };
         write!(f, "{}", s)
     }
 }
 
+/// Returns the registry with every bot available from the command line.
+fn default_bot_registry() -> YBotRegistry {
+    YBotRegistry::new()
+        .with_bot(Arc::new(RandomBot))
+        .with_bot(Arc::new(BiasedRandomBot))
+        .with_bot(Arc::new(GreedyBot))
+        .with_bot(Arc::new(MinimaxBot::default()))
+        .with_bot(Arc::new(MctsBot::default()))
+}
+
+/// Builds the arena settings from the command-line arguments.
+pub fn arena_config_from_args(args: &CliArgs, registry: &YBotRegistry) -> ArenaConfig {
+    let bots = if args.arena_bots.is_empty() {
+        let mut names = registry.names();
+        names.sort();
+        names
+    } else {
+        args.arena_bots.clone()
+    };
+    let sizes = if args.arena_sizes.is_empty() {
+        vec![args.size]
+    } else {
+        args.arena_sizes.clone()
+    };
+
+    ArenaConfig {
+        bots,
+        games: args.games,
+        sizes,
+        random_openings: args.random_openings,
+        seed: args.seed,
+        time_limit: args.time_limit_ms.map(Duration::from_millis),
+    }
+}
+
+/// Runs the bot arena, prints a summary and writes the report if requested.
+pub fn run_arena_command(args: &CliArgs) -> Result<()> {
+    let registry = default_bot_registry();
+    let config = arena_config_from_args(args, &registry);
+    let report = run_arena(&registry, &config)?;
+    print!("{}", report.summary());
+
+    if let Some(path) = &args.report {
+        let is_csv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+        let contents = if is_csv {
+            report.to_csv()
+        } else {
+            report.to_json()?
+        };
+        std::fs::write(path, contents)?;
+        println!("Report written to {}", path.display());
+    }
+    Ok(())
+}
+
 /// Runs the interactive CLI game loop.
 ///
 /// This function parses command-line arguments, initializes the game,
@@ -70,11 +185,7 @@ pub fn run_cli_game() -> Result<()> {
     let args = CliArgs::parse();
     let mut render_options = crate::RenderOptions::default();
     let mut rl = DefaultEditor::new()?;
-    let bots_registry = YBotRegistry::new()
-        .with_bot(Arc::new(RandomBot))
-        .with_bot(Arc::new(BiasedRandomBot))
-        .with_bot(Arc::new(GreedyBot))
-        .with_bot(Arc::new(MinimaxBot::default()));
+    let bots_registry = default_bot_registry();
     let bot: Arc<dyn YBot> = match bots_registry.find(&args.bot) {
         Some(b) => b,
         None => {
@@ -150,6 +261,12 @@ fn process_input(
             };
             apply_move(game, movement, "Error adding resign move");
         }
+        Command::Undo => {
+            step_through_history(game, *player, mode, GameY::undo, "Undone");
+        }
+        Command::Redo => {
+            step_through_history(game, *player, mode, GameY::redo, "Redone");
+        }
         Command::Show3DCoords => {
             render_options.show_3d_coords = !render_options.show_3d_coords;
         }
@@ -221,6 +338,8 @@ pub fn parse_command(input: &str, bound: u32) -> Command {
             }
         }
         "resign" => Command::Resign,
+        "undo" => Command::Undo,
+        "redo" => Command::Redo,
         "help" => Command::Help,
         "exit" => Command::Exit,
         "show_colors" => Command::ShowColors,
@@ -240,6 +359,8 @@ fn print_help() {
     println!("Available commands:");
     println!("  <number>        - Place a piece at the specified index number");
     println!("  resign          - Resign from the game");
+    println!("  undo            - Take back the last move");
+    println!("  redo            - Replay the last move taken back");
     println!("  show_coords     - Toggle showing coordinates on the board");
     println!("  show_idx        - Toggle showing index numbers on the board");
     println!("  show_colors     - Toggle showing colors on the board");
@@ -256,6 +377,10 @@ pub enum Command {
     Place { idx: u32 },
     /// Resign from the game.
     Resign,
+    /// Take back the last move.
+    Undo,
+    /// Replay the last move taken back.
+    Redo,
     /// No command was entered (empty input).
     None,
     /// An error occurred while parsing the command.
@@ -324,6 +449,31 @@ fn trigger_bot_move(game: &mut GameY, bot: &dyn YBot) {
     }
 }
 
+/// Undoes or redoes moves until it is `player`'s turn again.
+///
+/// Against the computer this takes back (or replays) the bot's reply together
+/// with the human move, so the human always gets the turn back.
+fn step_through_history(
+    game: &mut GameY,
+    player: PlayerId,
+    mode: Mode,
+    step: fn(&mut GameY) -> game::Result<Movement>,
+    label: &str,
+) {
+    loop {
+        match step(game) {
+            Ok(movement) => println!("{}: {}", label, movement),
+            Err(e) => {
+                println!("{}", e);
+                break;
+            }
+        }
+        if mode != Mode::Computer || game.next_player() == Some(player) {
+            break;
+        }
+    }
+}
+
 /// Generic helper to apply a move and handle the Result printing
 /// Returns true if the move was successful
 fn apply_move(game: &mut GameY, movement: Movement, error_msg: &str) -> bool {
@@ -355,6 +505,11 @@ mod tests {
         assert_eq!(format!("{}", Mode::Server), "server");
     }
 
+    #[test]
+    fn test_mode_display_arena() {
+        assert_eq!(format!("{}", Mode::Arena), "arena");
+    }
+
     #[test]
     fn test_parse_idx_valid() {
         assert_eq!(parse_idx("5", 10), Ok(5));
@@ -394,6 +549,65 @@ mod tests {
         assert_eq!(cmd, Command::Resign);
     }
 
+    #[test]
+    fn test_parse_command_undo_and_redo() {
+        assert_eq!(parse_command("undo", 10), Command::Undo);
+        assert_eq!(parse_command("redo", 10), Command::Redo);
+    }
+
+    #[test]
+    fn test_step_through_history_in_computer_mode_returns_turn_to_human() {
+        let mut game = GameY::new(3);
+        for (player, idx) in [(0, 0), (1, 1)] {
+            game.add_move(Movement::Placement {
+                player: PlayerId::new(player),
+                coords: Coordinates::from_index(idx, 3),
+            })
+            .unwrap();
+        }
+
+        step_through_history(
+            &mut game,
+            PlayerId::new(0),
+            Mode::Computer,
+            GameY::undo,
+            "Undone",
+        );
+        assert!(game.history().is_empty());
+        assert_eq!(game.next_player(), Some(PlayerId::new(0)));
+
+        step_through_history(
+            &mut game,
+            PlayerId::new(0),
+            Mode::Computer,
+            GameY::redo,
+            "Redone",
+        );
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.next_player(), Some(PlayerId::new(0)));
+    }
+
+    #[test]
+    fn test_step_through_history_in_human_mode_undoes_one_move() {
+        let mut game = GameY::new(3);
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::from_index(0, 3),
+        })
+        .unwrap();
+
+        step_through_history(
+            &mut game,
+            PlayerId::new(1),
+            Mode::Human,
+            GameY::undo,
+            "Undone",
+        );
+
+        assert!(game.history().is_empty());
+        assert_eq!(game.next_player(), Some(PlayerId::new(0)));
+    }
+
     #[test]
     fn test_parse_command_help() {
         let cmd = parse_command("help", 10);
@@ -26,6 +26,14 @@ pub struct GameY {
     // History of moves made in the game.
     history: Vec<Movement>,
 
+    // Moves taken back with `undo`, most recent last, so they can be redone.
+    undone: Vec<Movement>,
+
+    // Position the history starts from when the game was loaded from a YEN,
+    // kept as YEN so cloning a game stays cheap. `None` means the history
+    // starts from the empty board with player 0 to move.
+    start: Option<YEN>,
+
     // Union-Find data structure to track connected components for each player
     sets: Vec<PlayerSet>,
 
@@ -49,6 +57,8 @@ impl GameY {
             board_size,
             board_map: HashMap::new(),
             history: Vec::new(),
+            undone: Vec::new(),
+            start: None,
             sets: Vec::new(),
             status: GameStatus::Ongoing {
                 next_player: PlayerId::new(0),
@@ -72,20 +82,119 @@ impl GameY {
         &self.available_cells
     }
 
+    /// Returns the moves played so far, oldest first.
+    pub fn history(&self) -> &[Movement] {
+        &self.history
+    }
+
+    /// Returns the position the move history starts from, in YEN format.
+    ///
+    /// This is the empty board unless the game was loaded from a YEN.
+    /// Replaying [`GameY::history`] on top of it reproduces the current game.
+    pub fn starting_position(&self) -> YEN {
+        match &self.start {
+            Some(start) => start.clone(),
+            None => (&GameY::new(self.board_size)).into(),
+        }
+    }
+
+    /// Returns true if the history starts from a position other than the empty
+    /// board with player 0 to move.
+    pub fn has_custom_start(&self) -> bool {
+        self.start.is_some()
+    }
+
+    /// Takes back the last move and returns it.
+    ///
+    /// The board, the connected groups and the status are restored to the
+    /// position before that move. The move can be played again with
+    /// [`GameY::redo`] until a new move is added.
+    pub fn undo(&mut self) -> Result<Movement> {
+        if self.history.is_empty() {
+            return Err(GameYError::NoMoveToUndo);
+        }
+        self.rewind_to(self.history.len() - 1)?;
+        Ok(self
+            .undone
+            .last()
+            .cloned()
+            .expect("rewinding one move records it as undone"))
+    }
+
+    /// Plays again the last move taken back with [`GameY::undo`] and returns it.
+    pub fn redo(&mut self) -> Result<Movement> {
+        let movement = self.undone.pop().ok_or(GameYError::NoMoveToRedo)?;
+        self.apply_move(movement.clone())?;
+        Ok(movement)
+    }
+
+    /// Moves the game to the position reached after the first `moves` moves.
+    ///
+    /// Both played and undone moves can be reached, so this can step through a
+    /// game backwards and forwards. Returns `GameYError::InvalidHistoryIndex`
+    /// if `moves` is beyond the recorded moves.
+    pub fn replay_to(&mut self, moves: usize) -> Result<()> {
+        let len = self.history.len() + self.undone.len();
+        if moves > len {
+            return Err(GameYError::InvalidHistoryIndex { index: moves, len });
+        }
+        if moves < self.history.len() {
+            self.rewind_to(moves)?;
+        }
+        while self.history.len() < moves {
+            self.redo()?;
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the game from the first `moves` moves of the history, keeping
+    /// the later ones available for redo.
+    ///
+    /// The union-find sets only ever merge, so they are rebuilt by replaying
+    /// the kept moves rather than by splitting groups back apart.
+    fn rewind_to(&mut self, moves: usize) -> Result<()> {
+        let mut replayed = match &self.start {
+            Some(start) => GameY::try_from(start.clone())?,
+            None => GameY::new(self.board_size),
+        };
+        for movement in &self.history[..moves] {
+            replayed.apply_move(movement.clone())?;
+        }
+        replayed.undone = std::mem::take(&mut self.undone);
+        replayed.undone.extend(self.history.drain(moves..).rev());
+        *self = replayed;
+        Ok(())
+    }
+
     /// Returns the total number of cells on the board.
     pub fn total_cells(&self) -> u32 {
         (self.board_size * (self.board_size + 1)) / 2
     }
 
+    /// Returns the player whose stone is on `coords`, or None if the cell is empty.
+    pub fn cell_owner(&self, coords: &Coordinates) -> Option<PlayerId> {
+        self.board_map.get(coords).map(|(_, player)| *player)
+    }
+
+    /// Returns the number of separate connected groups of `player`'s stones.
+    pub fn group_count(&self, player: PlayerId) -> usize {
+        let mut roots: Vec<SetIdx> = self
+            .board_map
+            .values()
+            .filter(|(_, owner)| *owner == player)
+            .map(|(set_idx, _)| self.root(*set_idx))
+            .collect();
+        roots.sort_unstable();
+        roots.dedup();
+        roots.len()
+    }
+
     /// Checks if the movement is made by the correct player.
     ///
     /// Returns an error if it's not the specified player's turn.
     pub fn check_player_turn(&self, movement: &Movement) -> Result<()> {
         if let GameStatus::Ongoing { next_player } = self.status {
-            let player = match movement {
-                Movement::Placement { player, .. } => *player,
-                Movement::Action { player, .. } => *player,
-            };
+            let player = movement.player();
             if player != next_player {
                 return Err(GameYError::InvalidPlayerTurn {
                     expected: next_player,
@@ -131,13 +240,30 @@ impl GameY {
     }
 
     /// Adds a move to the game.
+    ///
+    /// Returns `GameYError::GameOver` if the game has already finished and
+    /// `GameYError::InvalidPlayerTurn` if the movement is not made by the player
+    /// to move. Resigning is the only movement allowed out of turn.
     pub fn add_move(&mut self, movement: Movement) -> Result<()> {
+        if self.check_game_over() {
+            return Err(GameYError::GameOver { movement });
+        }
+        if !is_resignation(&movement) {
+            self.check_player_turn(&movement)?;
+        }
+        self.apply_move(movement)?;
+        self.undone.clear();
+        Ok(())
+    }
+
+    /// Applies a move without checking whose turn it is or whether the game is over.
+    fn apply_move(&mut self, movement: Movement) -> Result<()> {
         match &movement {
             Movement::Placement { player, coords } => {
                 self.handle_placement(*player, *coords)?;
             }
             Movement::Action { player, action } => {
-                self.handle_action(*player, action);
+                self.handle_action(*player, action)?;
             }
         }
         self.history.push(movement);
@@ -186,9 +312,7 @@ impl GameY {
 
     /// Updates the game status (Finished vs Ongoing)
     fn update_status_after_placement(&mut self, player: PlayerId, won: bool) {
-        if self.check_game_over() {
-            tracing::info!("Game was already over. Move ignored for status update.");
-        } else if won {
+        if won {
             tracing::debug!("Player {} wins the game!", player);
             self.finish_with_winner(player);
         } else {
@@ -197,19 +321,75 @@ impl GameY {
     }
 
     /// Handles non-placement actions (Resign, Swap, etc.)
-    fn handle_action(&mut self, player: PlayerId, action: &GameAction) {
+    fn handle_action(&mut self, player: PlayerId, action: &GameAction) -> Result<()> {
         match action {
             GameAction::Resign => self.finish_with_winner(other_player(player)),
-            GameAction::Swap | GameAction::PassTurn => self.advance_to_other_player(player),
+            GameAction::Swap => self.swap_opening_stone(player)?,
+            GameAction::PassTurn => self.advance_to_other_player(player),
         }
+        Ok(())
     }
 
-    /// Handles validation logic (Game Over checks and Occupancy)
-    fn validate_placement(&self, player: PlayerId, coords: Coordinates) -> Result<()> {
-        if self.check_game_over() {
-            tracing::info!("Game is already over. Move at {} could be ignored", coords);
+    /// Returns true if the swap (pie rule) is currently available.
+    ///
+    /// Swapping is only legal as the second player's first move, right after
+    /// the first player has placed the opening stone.
+    pub fn can_swap(&self) -> bool {
+        self.swappable_opening_stone().is_some()
+    }
+
+    /// Returns the coordinates of the opening stone if it can still be swapped.
+    fn swappable_opening_stone(&self) -> Option<Coordinates> {
+        match (&self.status, self.history.as_slice()) {
+            (GameStatus::Ongoing { next_player }, [Movement::Placement { player, coords }])
+                if *player == PlayerId::new(0) && *next_player == PlayerId::new(1) =>
+            {
+                Some(*coords)
+            }
+            _ => None,
         }
+    }
+
+    /// Applies the pie rule: the opening stone changes owner and the first
+    /// player moves again, so the swapping player effectively takes over
+    /// the opening move.
+    fn swap_opening_stone(&mut self, player: PlayerId) -> Result<()> {
+        let coords = match self.swappable_opening_stone() {
+            Some(coords) if self.next_player() == Some(player) => coords,
+            _ => return Err(GameYError::InvalidSwap { player }),
+        };
 
+        // In a game loaded from a YEN the opening stone may touch other
+        // stones, so both players' groups are rebuilt around its new owner.
+        let (set_idx, _) = self.board_map[&coords];
+        self.board_map.insert(coords, (set_idx, player));
+        let won = self.rebuild_sets(player);
+        self.update_status_after_placement(player, won);
+        Ok(())
+    }
+
+    /// Rebuilds the groups of both players from the stones on the board and
+    /// returns true if a group of `player` connects the three sides.
+    fn rebuild_sets(&mut self, player: PlayerId) -> bool {
+        let stones: Vec<(Coordinates, PlayerId)> = self
+            .board_map
+            .iter()
+            .map(|(coords, (_, owner))| (*coords, *owner))
+            .collect();
+        self.sets.clear();
+        self.board_map.clear();
+
+        let mut won = false;
+        for (coords, owner) in stones {
+            let set_idx = self.register_piece(owner, coords);
+            let connected = self.connect_neighbors_and_check_win(coords, owner, set_idx);
+            won = won || (connected && owner == player);
+        }
+        won
+    }
+
+    /// Handles validation logic (Occupancy)
+    fn validate_placement(&self, player: PlayerId, coords: Coordinates) -> Result<()> {
         if self.board_map.contains_key(&coords) {
             return Err(GameYError::Occupied {
                 coordinates: coords,
@@ -254,7 +434,7 @@ impl GameY {
     }
 
     /// Returns the neighboring coordinates for a given cell.
-    fn get_neighbors(&self, coords: &Coordinates) -> Vec<Coordinates> {
+    pub fn get_neighbors(&self, coords: &Coordinates) -> Vec<Coordinates> {
         let mut neighbors = Vec::new();
         let x = coords.x();
         let y = coords.y();
@@ -354,6 +534,14 @@ impl GameY {
         }
     }
 
+    /// Disjoint Set Union 'Find' without path compression, for read-only queries
+    fn root(&self, mut i: SetIdx) -> SetIdx {
+        while self.sets[i].parent != i {
+            i = self.sets[i].parent;
+        }
+        i
+    }
+
     /// Disjoint Set Union 'Union' operation
     fn union(&mut self, i: SetIdx, j: SetIdx) -> bool {
         let root_i = self.find(i);
@@ -380,7 +568,17 @@ fn indent(str: &mut String, level: u32) {
 impl TryFrom<YEN> for GameY {
     type Error = GameYError;
 
+    /// Builds the position described by a YEN.
+    ///
+    /// Stones are placed directly, without replaying moves, so the loaded game
+    /// starts with an empty history. The side to move comes from `turn` unless
+    /// one of the players already connects the three sides.
     fn try_from(game: YEN) -> Result<Self> {
+        let symbols = player_symbols(game.players())?;
+        if game.turn() > 1 {
+            return Err(GameYError::InvalidTurn { turn: game.turn() });
+        }
+
         let mut ygame = GameY::new(game.size());
         let rows: Vec<&str> = game.layout().split('/').collect();
         if rows.len() as u32 != game.size() {
@@ -389,6 +587,9 @@ impl TryFrom<YEN> for GameY {
                 found: rows.len() as u32,
             });
         }
+
+        let mut stone_counts = [0u32; 2];
+        let mut winners = [false; 2];
         for (row, row_str) in rows.iter().enumerate() {
             let cells: Vec<char> = row_str.chars().collect();
             if cells.len() as u32 != row as u32 + 1 {
@@ -399,21 +600,49 @@ impl TryFrom<YEN> for GameY {
                 });
             }
             for (col, cell) in cells.iter().enumerate() {
-                let x = game.size() - 1 - (row as u32);
-                let y = col as u32;
-                let z = game.size() - 1 - x - y;
-                let coords = Coordinates::new(x, y, z);
-                if let Some(player) = player_from_layout_cell(*cell) {
-                    ygame.add_move(Movement::Placement { player, coords })?;
-                } else if *cell != '.' {
+                if *cell == '.' {
+                    continue;
+                }
+                let Some(player_idx) = symbols.iter().position(|symbol| symbol == cell) else {
                     return Err(GameYError::InvalidCharInLayout {
                         char: *cell,
                         row,
                         col,
                     });
+                };
+                let x = game.size() - 1 - (row as u32);
+                let y = col as u32;
+                let z = game.size() - 1 - x - y;
+                let coords = Coordinates::new(x, y, z);
+                let player = PlayerId::new(player_idx as u32);
+
+                let set_idx = ygame.register_piece(player, coords);
+                if ygame.connect_neighbors_and_check_win(coords, player, set_idx) {
+                    winners[player_idx] = true;
                 }
+                stone_counts[player_idx] += 1;
             }
         }
+
+        if stone_counts[0].abs_diff(stone_counts[1]) > 1 {
+            return Err(GameYError::InconsistentStoneCount {
+                player0: stone_counts[0],
+                player1: stone_counts[1],
+            });
+        }
+
+        // A chain connecting the three sides cuts the board, so at most one
+        // player can have one.
+        ygame.status = match winners.iter().position(|won| *won) {
+            Some(winner) => GameStatus::Finished {
+                winner: PlayerId::new(winner as u32),
+            },
+            None => GameStatus::Ongoing {
+                next_player: PlayerId::new(game.turn()),
+            },
+        };
+        let empty_board = stone_counts == [0, 0] && game.turn() == 0;
+        ygame.start = (!empty_board).then(|| (&ygame).into());
         Ok(ygame)
     }
 }
@@ -422,7 +651,7 @@ impl From<&GameY> for YEN {
     fn from(game: &GameY) -> Self {
         let size = game.board_size;
         let turn = match game.status {
-            GameStatus::Finished { winner } => other_player(winner).id() as u32,
+            GameStatus::Finished { winner } => other_player(winner).id(),
             GameStatus::Ongoing { next_player } => next_player.id(),
         };
         let mut layout = String::new();
@@ -453,12 +682,33 @@ pub fn other_player(player: PlayerId) -> PlayerId {
     }
 }
 
-fn player_from_layout_cell(cell: char) -> Option<PlayerId> {
-    match cell {
-        'B' => Some(PlayerId::new(0)),
-        'R' => Some(PlayerId::new(1)),
-        _ => None,
+fn is_resignation(movement: &Movement) -> bool {
+    matches!(
+        movement,
+        Movement::Action {
+            action: GameAction::Resign,
+            ..
+        }
+    )
+}
+
+/// Validates the YEN player symbols, returning them in player order.
+fn player_symbols(players: &[char]) -> Result<[char; 2]> {
+    let [first, second] = players else {
+        return Err(GameYError::InvalidNumPlayers {
+            num_players: players.len() as u32,
+            expected: 2,
+        });
+    };
+    for symbol in [*first, *second] {
+        if symbol == '.' {
+            return Err(GameYError::InvalidPlayerSymbol { symbol });
+        }
+    }
+    if first == second {
+        return Err(GameYError::InvalidPlayerSymbol { symbol: *second });
     }
+    Ok([*first, *second])
 }
 
 fn apply_player_color(symbol: String, player: Option<PlayerId>) -> String {
@@ -660,7 +910,7 @@ mod tests {
     }
 
     #[test]
-    fn test_pass_turn_and_swap_advance_to_other_player() {
+    fn test_pass_turn_advances_to_other_player() {
         let mut game = GameY::new(3);
 
         game.add_move(Movement::Action {
@@ -670,12 +920,118 @@ mod tests {
         .unwrap();
         assert_next_player(&game, PlayerId::new(1));
 
+        game.add_move(Movement::Action {
+            player: PlayerId::new(1),
+            action: GameAction::PassTurn,
+        })
+        .unwrap();
+        assert_next_player(&game, PlayerId::new(0));
+    }
+
+    #[test]
+    fn test_swap_transfers_opening_stone_to_second_player() {
+        let mut game = GameY::new(3);
+        let opening = Coordinates::new(1, 1, 0);
+
+        game.add_move(placement(0, 1, 1, 0)).unwrap();
+        assert!(game.can_swap());
+
         game.add_move(Movement::Action {
             player: PlayerId::new(1),
             action: GameAction::Swap,
         })
         .unwrap();
+
         assert_next_player(&game, PlayerId::new(0));
+        assert!(!game.can_swap());
+        assert_eq!(game.board_map[&opening].1, PlayerId::new(1));
+        assert_eq!(game.history.len(), 2);
+
+        let yen: YEN = (&game).into();
+        assert_eq!(yen.layout(), "./.R/...");
+        assert_eq!(yen.turn(), 0);
+    }
+
+    #[test]
+    fn test_swapped_stone_connects_with_new_owner_stones() {
+        let mut game = GameY::new(3);
+
+        apply_moves(
+            &mut game,
+            [
+                placement(0, 0, 1, 1),
+                Movement::Action {
+                    player: PlayerId::new(1),
+                    action: GameAction::Swap,
+                },
+                placement(0, 2, 0, 0),
+                placement(1, 0, 2, 0),
+                placement(0, 1, 1, 0),
+                placement(1, 0, 0, 2),
+            ],
+        );
+
+        assert_winner(&game, PlayerId::new(1));
+    }
+
+    #[test]
+    fn test_swap_in_custom_start_joins_the_new_owner_group() {
+        let mut game =
+            GameY::try_from(YEN::new(3, 0, vec!['B', 'R'], "./B./.R.".to_string())).unwrap();
+
+        apply_moves(
+            &mut game,
+            [
+                placement(0, 0, 0, 2),
+                Movement::Action {
+                    player: PlayerId::new(1),
+                    action: GameAction::Swap,
+                },
+            ],
+        );
+
+        assert_eq!(YEN::from(&game).layout(), "./B./RR.");
+        assert_eq!(game.group_count(PlayerId::new(0)), 1);
+        assert_eq!(game.group_count(PlayerId::new(1)), 1);
+
+        apply_moves(&mut game, [placement(0, 2, 0, 0), placement(1, 0, 2, 0)]);
+        assert_winner(&game, PlayerId::new(1));
+    }
+
+    #[test]
+    fn test_swap_rejected_outside_second_players_first_move() {
+        let swap = |player: u32| Movement::Action {
+            player: PlayerId::new(player),
+            action: GameAction::Swap,
+        };
+
+        let mut empty_board = GameY::new(3);
+        assert!(matches!(
+            empty_board.add_move(swap(0)),
+            Err(GameYError::InvalidSwap { .. })
+        ));
+
+        let mut after_opening = GameY::new(3);
+        after_opening.add_move(placement(0, 2, 0, 0)).unwrap();
+        assert!(matches!(
+            after_opening.add_move(swap(0)),
+            Err(GameYError::InvalidPlayerTurn { .. })
+        ));
+
+        let mut later = GameY::new(3);
+        apply_moves(
+            &mut later,
+            [
+                placement(0, 2, 0, 0),
+                placement(1, 0, 2, 0),
+                placement(0, 0, 0, 2),
+            ],
+        );
+        assert!(matches!(
+            later.add_move(swap(1)),
+            Err(GameYError::InvalidSwap { .. })
+        ));
+        assert_next_player(&later, PlayerId::new(1));
     }
 
     #[test]
@@ -712,6 +1068,221 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_add_move_rejects_placement_after_win() {
+        let mut game = GameY::new(1);
+        game.add_move(placement(0, 0, 0, 0)).unwrap();
+        assert_winner(&game, PlayerId::new(0));
+
+        let error = game
+            .add_move(Movement::Action {
+                player: PlayerId::new(1),
+                action: GameAction::PassTurn,
+            })
+            .unwrap_err();
+
+        assert!(matches!(error, GameYError::GameOver { .. }));
+        assert_eq!(game.history.len(), 1);
+    }
+
+    #[test]
+    fn test_add_move_rejects_moves_after_resignation() {
+        let mut game = GameY::new(3);
+        game.add_move(Movement::Action {
+            player: PlayerId::new(0),
+            action: GameAction::Resign,
+        })
+        .unwrap();
+
+        let error = game.add_move(placement(1, 2, 0, 0)).unwrap_err();
+        match error {
+            GameYError::GameOver { movement } => {
+                assert!(matches!(movement, Movement::Placement { .. }));
+            }
+            other => panic!("Expected GameOver, found {:?}", other),
+        }
+        assert!(game.available_cells().contains(&0));
+        assert_winner(&game, PlayerId::new(1));
+    }
+
+    #[test]
+    fn test_add_move_rejects_placement_by_wrong_player() {
+        let mut game = GameY::new(3);
+
+        let error = game.add_move(placement(1, 2, 0, 0)).unwrap_err();
+
+        assert!(matches!(
+            error,
+            GameYError::InvalidPlayerTurn { expected, found }
+                if expected == PlayerId::new(0) && found == PlayerId::new(1)
+        ));
+        assert_eq!(game.available_cells().len(), 6);
+        assert_next_player(&game, PlayerId::new(0));
+    }
+
+    #[test]
+    fn test_add_move_allows_resignation_out_of_turn() {
+        let mut game = GameY::new(3);
+
+        game.add_move(Movement::Action {
+            player: PlayerId::new(1),
+            action: GameAction::Resign,
+        })
+        .unwrap();
+
+        assert_winner(&game, PlayerId::new(0));
+    }
+
+    #[test]
+    fn test_history_returns_moves_in_order() {
+        let mut game = GameY::new(3);
+        apply_moves(&mut game, [placement(0, 2, 0, 0), placement(1, 0, 2, 0)]);
+
+        let history = game.history();
+        assert_eq!(history.len(), 2);
+        assert!(matches!(
+            history[0],
+            Movement::Placement { player, coords }
+                if player == PlayerId::new(0) && coords == Coordinates::new(2, 0, 0)
+        ));
+        assert!(matches!(
+            history[1],
+            Movement::Placement { player, .. } if player == PlayerId::new(1)
+        ));
+    }
+
+    #[test]
+    fn test_undo_restores_board_and_turn() {
+        let mut game = GameY::new(3);
+        apply_moves(&mut game, [placement(0, 2, 0, 0), placement(1, 0, 2, 0)]);
+
+        let undone = game.undo().unwrap();
+
+        assert!(matches!(
+            undone,
+            Movement::Placement { player, .. } if player == PlayerId::new(1)
+        ));
+        assert_next_player(&game, PlayerId::new(1));
+        assert_eq!(game.history().len(), 1);
+        assert_eq!(game.available_cells().len(), 5);
+        assert!(!game.board_map.contains_key(&Coordinates::new(0, 2, 0)));
+        assert_eq!(game.sets.len(), 1);
+    }
+
+    #[test]
+    fn test_undo_on_empty_history_returns_error() {
+        let mut game = GameY::new(3);
+
+        assert!(matches!(game.undo(), Err(GameYError::NoMoveToUndo)));
+        assert!(matches!(game.redo(), Err(GameYError::NoMoveToRedo)));
+    }
+
+    #[test]
+    fn test_undo_winning_move_splits_groups_and_reopens_game() {
+        let mut game = GameY::new(3);
+        apply_moves(
+            &mut game,
+            [
+                placement(0, 0, 2, 0),
+                placement(1, 2, 0, 0),
+                placement(0, 0, 0, 2),
+                placement(1, 1, 1, 0),
+                placement(0, 0, 1, 1),
+            ],
+        );
+        assert_winner(&game, PlayerId::new(0));
+
+        game.undo().unwrap();
+
+        assert_next_player(&game, PlayerId::new(0));
+        assert_eq!(game.sets.len(), 4);
+        let (left, _) = game.board_map[&Coordinates::new(0, 2, 0)];
+        let (right, _) = game.board_map[&Coordinates::new(0, 0, 2)];
+        assert_ne!(game.find(left), game.find(right));
+
+        game.redo().unwrap();
+        assert_winner(&game, PlayerId::new(0));
+    }
+
+    #[test]
+    fn test_undo_resignation_and_swap() {
+        let mut game = GameY::new(3);
+        apply_moves(
+            &mut game,
+            [
+                placement(0, 1, 1, 0),
+                Movement::Action {
+                    player: PlayerId::new(1),
+                    action: GameAction::Swap,
+                },
+                Movement::Action {
+                    player: PlayerId::new(0),
+                    action: GameAction::Resign,
+                },
+            ],
+        );
+        assert_winner(&game, PlayerId::new(1));
+
+        game.undo().unwrap();
+        assert_next_player(&game, PlayerId::new(0));
+        assert_eq!(
+            game.board_map[&Coordinates::new(1, 1, 0)].1,
+            PlayerId::new(1)
+        );
+
+        game.undo().unwrap();
+        assert_next_player(&game, PlayerId::new(1));
+        assert_eq!(
+            game.board_map[&Coordinates::new(1, 1, 0)].1,
+            PlayerId::new(0)
+        );
+        assert!(game.can_swap());
+    }
+
+    #[test]
+    fn test_add_move_discards_undone_moves() {
+        let mut game = GameY::new(3);
+        apply_moves(&mut game, [placement(0, 2, 0, 0), placement(1, 0, 2, 0)]);
+        game.undo().unwrap();
+
+        game.add_move(placement(1, 0, 0, 2)).unwrap();
+
+        assert!(matches!(game.redo(), Err(GameYError::NoMoveToRedo)));
+        assert_eq!(game.history().len(), 2);
+    }
+
+    #[test]
+    fn test_replay_to_steps_backwards_and_forwards() {
+        let mut game = GameY::new(3);
+        apply_moves(
+            &mut game,
+            [
+                placement(0, 2, 0, 0),
+                placement(1, 0, 2, 0),
+                placement(0, 0, 0, 2),
+            ],
+        );
+
+        game.replay_to(0).unwrap();
+        assert_eq!(game.history().len(), 0);
+        assert_eq!(game.available_cells().len(), 6);
+        assert_next_player(&game, PlayerId::new(0));
+
+        game.replay_to(2).unwrap();
+        assert_eq!(game.history().len(), 2);
+        assert_next_player(&game, PlayerId::new(0));
+
+        game.replay_to(3).unwrap();
+        assert_eq!(game.available_cells().len(), 3);
+        assert_next_player(&game, PlayerId::new(1));
+
+        assert!(matches!(
+            game.replay_to(4),
+            Err(GameYError::InvalidHistoryIndex { index: 4, len: 3 })
+        ));
+        assert_eq!(game.history().len(), 3);
+    }
+
     #[test]
     fn test_yen_conversion() {
         let mut game = GameY::new(3);
@@ -807,6 +1378,39 @@ mod tests {
         assert!(!rendered.contains("\x1b["));
     }
 
+    #[test]
+    fn test_cell_owner_and_group_count() {
+        let mut game = GameY::new(4);
+        apply_moves(
+            &mut game,
+            [
+                placement(0, 3, 0, 0),
+                placement(1, 0, 3, 0),
+                placement(0, 0, 0, 3),
+            ],
+        );
+
+        assert_eq!(
+            game.cell_owner(&Coordinates::new(3, 0, 0)),
+            Some(PlayerId::new(0))
+        );
+        assert_eq!(game.cell_owner(&Coordinates::new(1, 1, 1)), None);
+        assert_eq!(game.group_count(PlayerId::new(0)), 2);
+        assert_eq!(game.group_count(PlayerId::new(1)), 1);
+
+        // Joining both corners along the edge merges the groups.
+        apply_moves(
+            &mut game,
+            [
+                placement(1, 0, 2, 1),
+                placement(0, 2, 0, 1),
+                placement(1, 1, 2, 0),
+                placement(0, 1, 0, 2),
+            ],
+        );
+        assert_eq!(game.group_count(PlayerId::new(0)), 1);
+    }
+
     #[test]
     fn test_load_yen_status_cases() {
         let cases = [
@@ -815,7 +1419,7 @@ mod tests {
                     "size": 2,
                     "turn": 0,
                     "players": ["B","R"],
-                    "layout": "B/BB"
+                    "layout": "B/BR"
                 }"#,
                 Some(PlayerId::new(0)),
                 None,
@@ -825,7 +1429,7 @@ mod tests {
                     "size": 3,
                     "turn": 0,
                     "players": ["B","R"],
-                    "layout": "B/BB/BBR"
+                    "layout": "B/BR/BRR"
                 }"#,
                 Some(PlayerId::new(0)),
                 None,
@@ -850,6 +1454,36 @@ mod tests {
                 None,
                 Some(PlayerId::new(0)),
             ),
+            (
+                r#"{
+                    "size": 3,
+                    "turn": 1,
+                    "players": ["B","R"],
+                    "layout": "./B./..."
+                }"#,
+                None,
+                Some(PlayerId::new(1)),
+            ),
+            (
+                r#"{
+                    "size": 3,
+                    "turn": 0,
+                    "players": ["B","R"],
+                    "layout": "./BR/..."
+                }"#,
+                None,
+                Some(PlayerId::new(0)),
+            ),
+            (
+                r#"{
+                    "size": 3,
+                    "turn": 1,
+                    "players": ["X","O"],
+                    "layout": "O/OX/O.X"
+                }"#,
+                Some(PlayerId::new(1)),
+                None,
+            ),
         ];
 
         for (yen_str, winner, next_player) in cases {
@@ -863,6 +1497,92 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_try_from_rejects_inconsistent_players_turn_and_stones() {
+        let yen = |turn: u32, players: Vec<char>, layout: &str| {
+            GameY::try_from(YEN::new(3, turn, players, layout.to_string()))
+        };
+
+        assert!(matches!(
+            yen(0, vec!['B'], "./../..."),
+            Err(GameYError::InvalidNumPlayers {
+                num_players: 1,
+                expected: 2
+            })
+        ));
+        assert!(matches!(
+            yen(0, vec!['B', '.'], "./../..."),
+            Err(GameYError::InvalidPlayerSymbol { symbol: '.' })
+        ));
+        assert!(matches!(
+            yen(0, vec!['B', 'B'], "./../..."),
+            Err(GameYError::InvalidPlayerSymbol { symbol: 'B' })
+        ));
+        assert!(matches!(
+            yen(2, vec!['B', 'R'], "./../..."),
+            Err(GameYError::InvalidTurn { turn: 2 })
+        ));
+        assert!(matches!(
+            yen(1, vec!['B', 'R'], "B/B./..."),
+            Err(GameYError::InconsistentStoneCount {
+                player0: 2,
+                player1: 0
+            })
+        ));
+        assert!(matches!(
+            yen(0, vec!['X', 'O'], "./B./..."),
+            Err(GameYError::InvalidCharInLayout { char: 'B', .. })
+        ));
+    }
+
+    #[test]
+    fn test_loaded_position_has_empty_history_and_undo_keeps_stones() {
+        let yen = YEN::new(3, 1, vec!['B', 'R'], "./B./...".to_string());
+        let mut game = GameY::try_from(yen).unwrap();
+        assert!(game.history().is_empty());
+
+        game.add_move(placement(1, 0, 2, 0)).unwrap();
+        game.undo().unwrap();
+
+        assert_next_player(&game, PlayerId::new(1));
+        assert_eq!(game.available_cells().len(), 5);
+        assert_eq!(
+            game.board_map[&Coordinates::new(1, 0, 1)].1,
+            PlayerId::new(0)
+        );
+        assert!(matches!(game.undo(), Err(GameYError::NoMoveToUndo)));
+    }
+
+    #[test]
+    fn test_starting_position_replays_to_current_game() {
+        let yen = YEN::new(3, 1, vec!['B', 'R'], "./B./...".to_string());
+        let mut game = GameY::try_from(yen).unwrap();
+        apply_moves(&mut game, [placement(1, 0, 2, 0), placement(0, 2, 0, 0)]);
+
+        let start = game.starting_position();
+        assert_eq!(start.layout(), "./B./...");
+        assert_eq!(start.turn(), 1);
+
+        let mut replayed = GameY::try_from(start).unwrap();
+        apply_moves(&mut replayed, game.history().to_vec());
+        let current: YEN = (&game).into();
+        let replayed: YEN = (&replayed).into();
+        assert_eq!(replayed.layout(), current.layout());
+        assert_eq!(GameY::new(2).starting_position().layout(), "./..");
+    }
+
+    #[test]
+    fn test_has_custom_start_ignores_the_empty_board() {
+        let empty = YEN::new(3, 0, vec!['B', 'R'], "./../...".to_string());
+        assert!(!GameY::try_from(empty).unwrap().has_custom_start());
+        assert!(!GameY::new(3).has_custom_start());
+
+        let stone = YEN::new(3, 1, vec!['B', 'R'], "./B./...".to_string());
+        assert!(GameY::try_from(stone).unwrap().has_custom_start());
+        let red_to_move = YEN::new(3, 1, vec!['B', 'R'], "./../...".to_string());
+        assert!(GameY::try_from(red_to_move).unwrap().has_custom_start());
+    }
+
     #[test]
     fn test_try_from_rejects_invalid_yen_cases() {
         let cases = [
@@ -1,11 +1,13 @@
 use crate::{Coordinates, GameAction, PlayerId};
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 /// Represents a move that a player can make during the game.
 ///
 /// A movement can either be placing a piece on the board at specific coordinates,
 /// or performing a special game action like swapping or resigning.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Movement {
     /// A piece placement on the board.
     Placement {
@@ -23,6 +25,15 @@ pub enum Movement {
     },
 }
 
+impl Movement {
+    /// Returns the player making this movement.
+    pub fn player(&self) -> PlayerId {
+        match self {
+            Movement::Placement { player, .. } | Movement::Action { player, .. } => *player,
+        }
+    }
+}
+
 impl Display for Movement {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -67,6 +78,40 @@ mod tests {
         assert_eq!(format!("{}", movement), "Player 0 performs action Resign");
     }
 
+    #[test]
+    fn test_player_returns_moving_player() {
+        let placement = Movement::Placement {
+            player: PlayerId::new(0),
+            coords: Coordinates::new(1, 2, 3),
+        };
+        let action = Movement::Action {
+            player: PlayerId::new(1),
+            action: GameAction::PassTurn,
+        };
+        assert_eq!(placement.player(), PlayerId::new(0));
+        assert_eq!(action.player(), PlayerId::new(1));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let movement = Movement::Action {
+            player: PlayerId::new(1),
+            action: GameAction::PassTurn,
+        };
+        let json = serde_json::to_string(&movement).unwrap();
+        assert_eq!(json, r#"{"action":{"player":1,"action":"pass_turn"}}"#);
+
+        let parsed: Movement = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.player(), PlayerId::new(1));
+        assert!(matches!(
+            parsed,
+            Movement::Action {
+                action: GameAction::PassTurn,
+                ..
+            }
+        ));
+    }
+
     #[test]
     fn test_clone() {
         let movement = Movement::Placement {
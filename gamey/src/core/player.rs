@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 /// Represents a player in the game with an identifier and a name.
@@ -34,7 +35,7 @@ impl Display for Player {
 ///
 /// This is a lightweight wrapper around a `u32` that provides type safety
 /// for player identification throughout the game.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PlayerId(u32);
 
 impl PlayerId {
@@ -87,6 +87,30 @@ pub enum GameYError {
         found: PlayerId,
     },
 
+    /// A swap was attempted when the pie rule does not allow it.
+    #[error("Player {player} cannot swap: swap is only allowed as the second player's first move")]
+    InvalidSwap {
+        /// The player who attempted the swap.
+        player: PlayerId,
+    },
+
+    /// Attempted to undo a move when the history is empty.
+    #[error("There is no move to undo")]
+    NoMoveToUndo,
+
+    /// Attempted to redo a move when no move has been undone.
+    #[error("There is no move to redo")]
+    NoMoveToRedo,
+
+    /// Attempted to replay the game to a position outside its move history.
+    #[error("Invalid history index {index}: the game has {len} recorded moves")]
+    InvalidHistoryIndex {
+        /// The requested number of moves to replay.
+        index: usize,
+        /// The number of recorded moves, including undone ones.
+        len: usize,
+    },
+
     /// Invalid number of players specified.
     #[error("Invalid number of players: {num_players}, expected {expected}")]
     InvalidNumPlayers {
@@ -116,6 +140,52 @@ pub enum GameYError {
         line: u32,
     },
 
+    /// The YEN player symbols cannot be told apart on the board.
+    #[error("Invalid player symbol '{symbol}': symbols must be distinct and different from '.'")]
+    InvalidPlayerSymbol {
+        /// The offending symbol.
+        symbol: char,
+    },
+
+    /// The YEN turn does not name one of the two players.
+    #[error("Invalid turn {turn}: expected 0 or 1")]
+    InvalidTurn {
+        /// The turn found in the YEN.
+        turn: u32,
+    },
+
+    /// The number of stones of each player cannot come from alternating turns.
+    #[error(
+        "Inconsistent stone count: player 0 has {player0} stones and player 1 has {player1} stones"
+    )]
+    InconsistentStoneCount {
+        /// Stones owned by player 0.
+        player0: u32,
+        /// Stones owned by player 1.
+        player1: u32,
+    },
+
+    /// Reading or writing persisted game sessions failed.
+    #[error("Storage error: {message}")]
+    StorageError {
+        /// Description of what went wrong.
+        message: String,
+    },
+
+    /// A bot arena run could not be set up.
+    #[error("Arena error: {message}")]
+    ArenaError {
+        /// Description of what went wrong.
+        message: String,
+    },
+
+    /// The server configuration is missing, malformed or invalid.
+    #[error("Configuration error: {message}")]
+    ConfigError {
+        /// Description of what went wrong.
+        message: String,
+    },
+
     /// Server operation failed.
     #[error("Server error: {message}")]
     ServerError {
@@ -198,6 +268,84 @@ mod tests {
         assert!(msg.contains("found player 1"));
     }
 
+    #[test]
+    fn test_invalid_swap_display() {
+        let err = GameYError::InvalidSwap {
+            player: PlayerId::new(0),
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Player 0 cannot swap"));
+        assert!(msg.contains("second player's first move"));
+    }
+
+    #[test]
+    fn test_no_move_to_undo_display() {
+        let err = GameYError::NoMoveToUndo;
+        assert_eq!(format!("{}", err), "There is no move to undo");
+    }
+
+    #[test]
+    fn test_no_move_to_redo_display() {
+        let err = GameYError::NoMoveToRedo;
+        assert_eq!(format!("{}", err), "There is no move to redo");
+    }
+
+    #[test]
+    fn test_invalid_history_index_display() {
+        let err = GameYError::InvalidHistoryIndex { index: 7, len: 3 };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid history index 7"));
+        assert!(msg.contains("3 recorded moves"));
+    }
+
+    #[test]
+    fn test_invalid_player_symbol_display() {
+        let err = GameYError::InvalidPlayerSymbol { symbol: '.' };
+        let msg = format!("{}", err);
+        assert!(msg.contains("Invalid player symbol '.'"));
+    }
+
+    #[test]
+    fn test_invalid_turn_display() {
+        let err = GameYError::InvalidTurn { turn: 2 };
+        assert_eq!(format!("{}", err), "Invalid turn 2: expected 0 or 1");
+    }
+
+    #[test]
+    fn test_inconsistent_stone_count_display() {
+        let err = GameYError::InconsistentStoneCount {
+            player0: 3,
+            player1: 0,
+        };
+        let msg = format!("{}", err);
+        assert!(msg.contains("player 0 has 3 stones"));
+        assert!(msg.contains("player 1 has 0 stones"));
+    }
+
+    #[test]
+    fn test_storage_error_display() {
+        let err = GameYError::StorageError {
+            message: "corrupted session".to_string(),
+        };
+        assert_eq!(format!("{}", err), "Storage error: corrupted session");
+    }
+
+    #[test]
+    fn test_arena_error_display() {
+        let err = GameYError::ArenaError {
+            message: "unknown bot 'x'".to_string(),
+        };
+        assert_eq!(format!("{}", err), "Arena error: unknown bot 'x'");
+    }
+
+    #[test]
+    fn test_config_error_display() {
+        let err = GameYError::ConfigError {
+            message: "unknown bot 'x'".to_string(),
+        };
+        assert_eq!(format!("{}", err), "Configuration error: unknown bot 'x'");
+    }
+
     #[test]
     fn test_invalid_num_players_display() {
         let err = GameYError::InvalidNumPlayers {
@@ -1,10 +1,11 @@
 //! GameY binary entry point.
 //!
-//! This is the main executable for the GameY application. It supports three modes:
+//! This is the main executable for the GameY application. It supports four modes:
 //!
 //! - **Human mode** (default): Two players take turns at the terminal
 //! - **Computer mode**: Play against a bot
 //! - **Server mode**: Run as an HTTP server exposing the bot API
+//! - **Arena mode**: Play bots against each other and rate them
 //!
 //! # Usage
 //!
@@ -17,10 +18,18 @@
 //!
 //! # Start the bot server on port 3000
 //! gamey --mode server --port 3000
+//!
+//! # Start the bot server with settings from a TOML file
+//! gamey --mode server --config gamey.toml
+//!
+//! # Rate two bots over 50 games on sizes 5 and 7, each game starting from
+//! # two random stones; the seed repeats the same openings on every run
+//! gamey --mode arena --arena-bots greedy_bot,minimax_bot --arena-sizes 5,7 \
+//!     --games 50 --random-openings 2 --seed 1 --report arena.json
 //! ```
 
 use clap::Parser;
-use gamey::{self, CliArgs, Mode, run_bot_server, run_cli_game};
+use gamey::{self, CliArgs, Mode, ServerConfig, run_arena_command, run_bot_server, run_cli_game};
 use tracing_subscriber::prelude::*;
 
 /// Main entry point for the GameY application.
@@ -33,7 +42,16 @@ async fn main() {
     let args = CliArgs::parse();
 
     if args.mode == Mode::Server {
-        if let Err(e) = run_bot_server(args.port).await {
+        let result = match ServerConfig::load(&args) {
+            Ok(config) => run_bot_server(config).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args.mode == Mode::Arena {
+        if let Err(e) = run_arena_command(&args) {
             eprintln!("Error: {}", e);
             std::process::exit(1);
         }
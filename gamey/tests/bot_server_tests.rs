@@ -3,11 +3,12 @@ use axum::{
     http::{Request, StatusCode},
 };
 use gamey::{
-    ErrorResponse, MoveResponse, RandomBot, YBotRegistry, YEN, create_default_state, create_router,
-    state::AppState,
+    AnalysisResponse, Coordinates, ErrorResponse, GameY, MoveResponse, Movement, PlayerId,
+    RandomBot, YBotRegistry, YEN, create_default_state, create_router, state::AppState,
 };
 use http_body_util::BodyExt;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tower::ServiceExt;
 
 /// Helper to create a test app with the default state
@@ -141,7 +142,7 @@ async fn test_choose_endpoint_with_partially_filled_board() {
     let app = test_app();
 
     // Board with some cells already filled: B in first cell, R in second
-    let yen = YEN::new(3, 2, vec!['B', 'R'], "B/R./.B.".to_string());
+    let yen = YEN::new(3, 1, vec!['B', 'R'], "B/R./.B.".to_string());
 
     let response = app
         .oneshot(
@@ -164,6 +165,32 @@ async fn test_choose_endpoint_with_partially_filled_board() {
     assert_eq!(move_response.bot_id, "random_bot");
 }
 
+#[tokio::test]
+async fn test_choose_endpoint_honours_time_limit_for_minimax_bot() {
+    let app = test_app();
+    let yen: YEN = (&GameY::new(11)).into();
+
+    let started = Instant::now();
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/choose/minimax_bot?time_limit_ms=100")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&yen).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(started.elapsed() < Duration::from_secs(3));
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let move_response: MoveResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(move_response.bot_id, "minimax_bot");
+}
+
 // ============================================================================
 // Choose endpoint tests - Error cases
 // ============================================================================
@@ -223,6 +250,54 @@ async fn test_choose_endpoint_with_unknown_bot() {
     assert_eq!(error_response.bot_id, Some("unknown_bot".to_string()));
 }
 
+#[tokio::test]
+async fn test_choose_endpoint_rejects_inconsistent_stone_counts() {
+    let app = test_app();
+
+    let yen = YEN::new(3, 1, vec!['B', 'R'], "B/B./B..".to_string());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/choose/random_bot")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&yen).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(error_response.message.contains("Invalid YEN format"));
+    assert!(error_response.message.contains("Inconsistent stone count"));
+}
+
+#[tokio::test]
+async fn test_choose_endpoint_accepts_custom_player_symbols() {
+    let app = test_app();
+
+    let yen = YEN::new(3, 1, vec!['X', 'O'], "X/../...".to_string());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/ybot/choose/random_bot")
+                .header("content-type", "application/json")
+                .body(Body::from(serde_json::to_string(&yen).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
 #[tokio::test]
 async fn test_choose_endpoint_with_invalid_json() {
     let app = test_app();
@@ -322,6 +397,126 @@ async fn test_choose_with_empty_bot_registry() {
     assert!(error_response.message.contains("Bot not found"));
 }
 
+// ============================================================================
+// Analysis endpoint tests
+// ============================================================================
+
+/// Position where player 0 wins by playing (0, 2, 0).
+fn yen_with_winning_move() -> YEN {
+    let mut game = GameY::new(3);
+    for (player, coords) in [
+        (0, Coordinates::new(0, 0, 2)),
+        (1, Coordinates::new(2, 0, 0)),
+        (0, Coordinates::new(0, 1, 1)),
+        (1, Coordinates::new(1, 1, 0)),
+    ] {
+        game.add_move(Movement::Placement {
+            player: PlayerId::new(player),
+            coords,
+        })
+        .unwrap();
+    }
+    (&game).into()
+}
+
+async fn post_analysis(app: axum::Router, uri: &str, yen: &YEN) -> axum::response::Response {
+    app.oneshot(
+        Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_string(yen).unwrap()))
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_analyze_endpoint_ranks_candidates_and_reports_connections() {
+    let response = post_analysis(
+        test_app(),
+        "/v1/ybot/analyze?top_k=2&time_limit_ms=2000",
+        &yen_with_winning_move(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let analysis: AnalysisResponse = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(analysis.api_version, "v1");
+    assert_eq!(analysis.analysis.player, PlayerId::new(0));
+    assert_eq!(analysis.analysis.candidates.len(), 2);
+    assert_eq!(
+        analysis.analysis.candidates[0].coords,
+        Coordinates::new(0, 2, 0)
+    );
+    assert_eq!(
+        analysis.analysis.principal_variation.first(),
+        Some(&Coordinates::new(0, 2, 0))
+    );
+    assert_eq!(analysis.analysis.connection_costs.len(), 2);
+    assert_eq!(
+        analysis.analysis.connection_costs[0]
+            .side_a_to_c
+            .as_ref()
+            .map(|connection| connection.cost),
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn test_analyze_endpoint_rejects_finished_games() {
+    let mut game = GameY::try_from(yen_with_winning_move()).unwrap();
+    game.add_move(Movement::Placement {
+        player: PlayerId::new(0),
+        coords: Coordinates::new(0, 2, 0),
+    })
+    .unwrap();
+
+    let response = post_analysis(test_app(), "/v1/ybot/analyze", &(&game).into()).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error_response.message.contains("already finished"));
+}
+
+#[tokio::test]
+async fn test_analyze_endpoint_rejects_invalid_yen() {
+    let yen = YEN::new(3, 1, vec!['B', 'R'], "B/B./B..".to_string());
+
+    let response = post_analysis(test_app(), "/v1/ybot/analyze", &yen).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(error_response.message.contains("Invalid YEN format"));
+}
+
+#[tokio::test]
+async fn test_analyze_endpoint_requires_minimax_bot() {
+    let bots = YBotRegistry::new().with_bot(Arc::new(RandomBot));
+    let app = test_app_with_state(AppState::new(bots));
+
+    let response = post_analysis(app, "/v1/ybot/analyze", &yen_with_winning_move()).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error_response: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert!(
+        error_response
+            .message
+            .contains("Bot not found: minimax_bot")
+    );
+    assert_eq!(error_response.bot_id.as_deref(), Some("minimax_bot"));
+}
+
 // ============================================================================
 // Route not found tests
 // ============================================================================
@@ -430,3 +625,306 @@ async fn test_matchmaking_enqueue_rejects_duplicate_waiting_ticket_for_same_iden
             .contains("User already has an active matchmaking ticket")
     );
 }
+
+// ============================================================================
+// Event stream tests
+// ============================================================================
+
+/// Reads the next server-sent event from a streaming response body.
+async fn next_sse_event(body: &mut Body) -> Option<String> {
+    let frame = tokio::time::timeout(Duration::from_secs(5), body.frame())
+        .await
+        .expect("timed out waiting for an event")?
+        .unwrap();
+    let data = frame.into_data().unwrap();
+    Some(String::from_utf8(data.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_game_events_stream_pushes_state_after_each_move() {
+    let app = test_app();
+
+    let create_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/games")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"size":3,"mode":"human_vs_human"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = create_response
+        .into_body()
+        .collect()
+        .await
+        .unwrap()
+        .to_bytes();
+    let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let game_id = created["game_id"].as_str().unwrap().to_string();
+
+    let events_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/v1/games/{}/events", game_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(events_response.status(), StatusCode::OK);
+    assert_eq!(
+        events_response.headers()["content-type"],
+        "text/event-stream"
+    );
+    let mut events = events_response.into_body();
+
+    let initial = next_sse_event(&mut events).await.unwrap();
+    assert!(initial.starts_with("event: state\n"));
+    assert!(initial.contains(r#""next_player":0"#));
+
+    let move_response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/v1/games/{}/moves", game_id))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"coords":{"x":2,"y":0,"z":0}}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(move_response.status(), StatusCode::OK);
+
+    let after_move = next_sse_event(&mut events).await.unwrap();
+    assert!(after_move.contains(r#""next_player":1"#));
+}
+
+#[tokio::test]
+async fn test_ticket_events_stream_ends_when_ticket_is_cancelled() {
+    let app = test_app();
+
+    let enqueue_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/matchmaking/enqueue")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"size":7}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = enqueue_response
+        .into_body()
+        .collect()
+        .await
+        .unwrap()
+        .to_bytes();
+    let ticket: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let ticket_id = ticket["ticket_id"].as_str().unwrap().to_string();
+
+    let events_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/v1/matchmaking/tickets/{}/events", ticket_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(events_response.status(), StatusCode::OK);
+    let mut events = events_response.into_body();
+
+    let initial = next_sse_event(&mut events).await.unwrap();
+    assert!(initial.starts_with("event: ticket\n"));
+    assert!(initial.contains(r#""status":"waiting""#));
+
+    let cancel_response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/v1/matchmaking/tickets/{}/cancel", ticket_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(cancel_response.status(), StatusCode::OK);
+
+    let cancelled = next_sse_event(&mut events).await.unwrap();
+    assert!(cancelled.contains(r#""status":"cancelled""#));
+    assert!(next_sse_event(&mut events).await.is_none());
+}
+
+#[tokio::test]
+async fn test_ticket_events_for_unknown_ticket_returns_error() {
+    let response = test_app()
+        .oneshot(
+            Request::builder()
+                .uri("/v1/matchmaking/tickets/ticket-missing/events")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+// ============================================================================
+// Spectator tests
+// ============================================================================
+
+async fn create_game_with_body(app: &axum::Router, body: &str) -> String {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/games")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    created["game_id"].as_str().unwrap().to_string()
+}
+
+async fn list_live_games(app: &axum::Router) -> serde_json::Value {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/v1/spectate/games")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_spectators_can_list_and_watch_public_games_only() {
+    let app = test_app();
+    let public_game_id = create_game_with_body(&app, r#"{"size":3,"mode":"human_vs_human"}"#).await;
+    let private_game_id = create_game_with_body(
+        &app,
+        r#"{"size":3,"mode":"human_vs_human","allow_spectators":false}"#,
+    )
+    .await;
+
+    let live = list_live_games(&app).await;
+    let games = live["games"].as_array().unwrap();
+    assert_eq!(games.len(), 1);
+    assert_eq!(games[0]["game_id"], public_game_id.as_str());
+    assert_eq!(games[0]["spectator_count"], 0);
+
+    let private_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/v1/spectate/games/{}/events", private_game_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(private_response.status(), StatusCode::BAD_REQUEST);
+
+    let spectate_response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/v1/spectate/games/{}/events", public_game_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(spectate_response.status(), StatusCode::OK);
+    let mut events = spectate_response.into_body();
+
+    let initial = next_sse_event(&mut events).await.unwrap();
+    assert!(initial.contains(r#""spectator_count":1"#));
+    assert_eq!(
+        list_live_games(&app).await["games"][0]["spectator_count"],
+        1
+    );
+
+    drop(events);
+    assert_eq!(
+        list_live_games(&app).await["games"][0]["spectator_count"],
+        0
+    );
+}
+
+// ============================================================================
+// Game clock tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_clocked_game_reports_remaining_time_and_adds_increment() {
+    let app = test_app();
+    let game_id = create_game_with_body(
+        &app,
+        r#"{"size":3,"mode":"human_vs_human","time_control":{"kind":"fischer","base_ms":60000,"increment_ms":5000}}"#,
+    )
+    .await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/v1/games/{}/moves", game_id))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"coords":{"x":2,"y":0,"z":0}}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let state: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let clock = &state["clock"];
+    assert_eq!(clock["time_control"]["kind"], "fischer");
+    assert_eq!(clock["running_player"], 1);
+    assert!(clock["remaining_ms"][0].as_u64().unwrap() > 60_000);
+    assert!(clock["remaining_ms"][1].as_u64().unwrap() <= 60_000);
+    assert!(state["turn_timeout_remaining_ms"].is_null());
+}
+
+#[tokio::test]
+async fn test_create_game_rejects_invalid_time_control() {
+    let app = test_app();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/v1/games")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"size":3,"mode":"human_vs_human","time_control":{"kind":"byo_yomi","base_ms":60000,"period_ms":0,"periods":3}}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
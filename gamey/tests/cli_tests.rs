@@ -278,7 +278,7 @@ fn test_cli_args_default_values() {
     assert_eq!(args.size, 7);
     assert_eq!(args.mode, Mode::Human);
     assert_eq!(args.bot, "random_bot");
-    assert_eq!(args.port, 3000);
+    assert_eq!(args.port, None);
 }
 
 #[test]
@@ -332,13 +332,13 @@ fn test_cli_args_custom_bot_short() {
 #[test]
 fn test_cli_args_custom_port() {
     let args = CliArgs::try_parse_from(["gamey", "--port", "8080"]).unwrap();
-    assert_eq!(args.port, 8080);
+    assert_eq!(args.port, Some(8080));
 }
 
 #[test]
 fn test_cli_args_custom_port_short() {
     let args = CliArgs::try_parse_from(["gamey", "-p", "9000"]).unwrap();
-    assert_eq!(args.port, 9000);
+    assert_eq!(args.port, Some(9000));
 }
 
 #[test]
@@ -358,7 +358,7 @@ fn test_cli_args_combined_options() {
     assert_eq!(args.size, 9);
     assert_eq!(args.mode, Mode::Computer);
     assert_eq!(args.bot, "advanced_bot");
-    assert_eq!(args.port, 5000);
+    assert_eq!(args.port, Some(5000));
 }
 
 #[test]
@@ -390,3 +390,52 @@ fn test_cli_args_version_flag() {
     let result = CliArgs::try_parse_from(["gamey", "--version"]);
     assert!(result.is_err()); // --version causes an error (but it's intentional)
 }
+
+#[test]
+fn test_cli_args_arena_options() {
+    let args = CliArgs::try_parse_from([
+        "gamey",
+        "--mode",
+        "arena",
+        "--arena-bots",
+        "random_bot,greedy_bot",
+        "--arena-sizes",
+        "5,7",
+        "--games",
+        "10",
+        "--seed",
+        "42",
+        "--time-limit-ms",
+        "250",
+        "--report",
+        "arena.csv",
+    ])
+    .unwrap();
+    assert_eq!(args.mode, Mode::Arena);
+
+    let config = gamey::arena_config_from_args(&args, &gamey::YBotRegistry::new());
+    assert_eq!(config.bots, vec!["random_bot", "greedy_bot"]);
+    assert_eq!(config.sizes, vec![5, 7]);
+    assert_eq!(config.games, 10);
+    assert_eq!(config.seed, Some(42));
+    assert_eq!(
+        config.time_limit,
+        Some(std::time::Duration::from_millis(250))
+    );
+    assert_eq!(args.report, Some(std::path::PathBuf::from("arena.csv")));
+}
+
+#[test]
+fn test_arena_config_defaults_to_all_bots_and_board_size() {
+    let args = CliArgs::try_parse_from(["gamey", "--mode", "arena", "-s", "9"]).unwrap();
+    let registry = gamey::YBotRegistry::new()
+        .with_bot(std::sync::Arc::new(gamey::RandomBot))
+        .with_bot(std::sync::Arc::new(gamey::GreedyBot));
+
+    let config = gamey::arena_config_from_args(&args, &registry);
+
+    assert_eq!(config.bots, vec!["greedy_bot", "random_bot"]);
+    assert_eq!(config.sizes, vec![9]);
+    assert_eq!(config.games, 20);
+    assert_eq!(config.seed, None);
+}
@@ -408,17 +408,17 @@ fn test_player_1_resign_makes_player_0_win() {
 }
 
 #[test]
-fn test_swap_changes_next_player() {
+fn test_swap_on_empty_board_is_rejected() {
     let mut game = GameY::new(5);
 
-    game.add_move(Movement::Action {
+    let result = game.add_move(Movement::Action {
         player: PlayerId::new(0),
         action: GameAction::Swap,
-    })
-    .unwrap();
+    });
 
+    assert!(matches!(result, Err(GameYError::InvalidSwap { .. })));
     assert!(!game.check_game_over());
-    assert_eq!(game.next_player(), Some(PlayerId::new(1)));
+    assert_eq!(game.next_player(), Some(PlayerId::new(0)));
 }
 
 #[test]
@@ -439,9 +439,11 @@ fn test_swap_after_opening_move() {
     })
     .unwrap();
 
-    // Now it's player 0's turn again
+    // Now it's player 0's turn again and the opening stone belongs to player 1
     assert_eq!(game.next_player(), Some(PlayerId::new(0)));
     assert!(!game.check_game_over());
+    let yen: YEN = (&game).into();
+    assert_eq!(yen.layout(), "./../.R./..../.....");
 }
 
 #[test]
@@ -848,9 +850,7 @@ fn test_full_game_on_size_4_board() {
         (1, Coordinates::new(1, 2, 0)),
         (0, Coordinates::new(1, 0, 2)),
         (1, Coordinates::new(0, 3, 0)),
-        (0, Coordinates::new(0, 0, 3)), // Player 0 now touches side A and B
-        (1, Coordinates::new(0, 2, 1)),
-        (0, Coordinates::new(1, 1, 1)), // Interior cell - connects pieces
+        (0, Coordinates::new(0, 0, 3)), // Player 0 now connects all three sides
     ];
 
     for (player_id, coords) in &moves {
@@ -861,9 +861,19 @@ fn test_full_game_on_size_4_board() {
         .unwrap();
     }
 
-    // Game might or might not be over depending on the board state
-    // The important thing is all moves executed successfully
-    assert!(game.available_cells().len() < 10);
+    assert_eq!(game.available_cells().len(), 3);
+    assert!(matches!(
+        game.status(),
+        GameStatus::Finished { winner } if *winner == PlayerId::new(0)
+    ));
+
+    // The finished game no longer accepts moves
+    let result = game.add_move(Movement::Placement {
+        player: PlayerId::new(1),
+        coords: Coordinates::new(0, 2, 1),
+    });
+    assert!(matches!(result, Err(GameYError::GameOver { .. })));
+    assert_eq!(game.available_cells().len(), 3);
 }
 
 #[test]
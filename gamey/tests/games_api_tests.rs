@@ -2,57 +2,65 @@ use axum::{
     body::Body,
     http::{Method, Request, StatusCode},
 };
-use gamey::{create_default_state, create_router};
+use gamey::{
+    ServerConfig, create_default_state, create_router,
+    stats_outbox::start_stats_outbox_sender,
+    storage::{FileGameStore, restore_games},
+};
+use std::{sync::Arc, time::Duration};
 use http_body_util::BodyExt;
 use serde_json::{Value, json};
-use std::sync::Mutex;
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
-    net::TcpListener,
+    net::{TcpListener, TcpStream},
 };
 use tower::ServiceExt;
 
-static STATS_ENV_MUTEX: Mutex<()> = Mutex::new(());
-
 fn test_app() -> axum::Router {
     create_router(create_default_state())
 }
 
-async fn capture_next_http_body(listener: TcpListener) -> String {
-    for _ in 0..5 {
-        let (mut stream, _) = listener.accept().await.unwrap();
-        let mut buffer = Vec::new();
-        let header_end;
-
-        loop {
-            let mut chunk = [0_u8; 1024];
-            let read = stream.read(&mut chunk).await.unwrap();
-            assert!(read > 0, "stats request connection closed before headers");
-            buffer.extend_from_slice(&chunk[..read]);
-
-            if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
-                header_end = position + 4;
-                break;
-            }
-        }
+async fn read_http_body(stream: &mut TcpStream) -> String {
+    let mut buffer = Vec::new();
+    let header_end;
+
+    loop {
+        let mut chunk = [0_u8; 1024];
+        let read = stream.read(&mut chunk).await.unwrap();
+        assert!(read > 0, "stats request connection closed before headers");
+        buffer.extend_from_slice(&chunk[..read]);
 
-        let headers = String::from_utf8_lossy(&buffer[..header_end]);
-        let content_length = headers
-            .lines()
-            .find_map(|line| {
-                let (name, value) = line.split_once(':')?;
-                name.eq_ignore_ascii_case("content-length")
-                    .then(|| value.trim().parse::<usize>().ok())
-                    .flatten()
-            })
-            .unwrap_or(0);
-
-        while buffer.len() < header_end + content_length {
-            let mut chunk = [0_u8; 1024];
-            let read = stream.read(&mut chunk).await.unwrap();
-            assert!(read > 0, "stats request connection closed before body");
-            buffer.extend_from_slice(&chunk[..read]);
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            header_end = position + 4;
+            break;
         }
+    }
+
+    let headers = String::from_utf8_lossy(&buffer[..header_end]);
+    let content_length = headers
+        .lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("content-length")
+                .then(|| value.trim().parse::<usize>().ok())
+                .flatten()
+        })
+        .unwrap_or(0);
+
+    while buffer.len() < header_end + content_length {
+        let mut chunk = [0_u8; 1024];
+        let read = stream.read(&mut chunk).await.unwrap();
+        assert!(read > 0, "stats request connection closed before body");
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    String::from_utf8(buffer[header_end..header_end + content_length].to_vec()).unwrap()
+}
+
+async fn capture_next_http_body(listener: TcpListener, mode: &str) -> String {
+    for _ in 0..5 {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let body = read_http_body(&mut stream).await;
 
         stream
             .write_all(
@@ -61,13 +69,50 @@ async fn capture_next_http_body(listener: TcpListener) -> String {
             .await
             .unwrap();
 
-        let body = String::from_utf8(buffer[header_end..header_end + content_length].to_vec()).unwrap();
-        if body.contains(r#""mode":"local_human_vs_human""#) {
+        if body.contains(&format!(r#""mode":"{mode}""#)) {
             return body;
         }
     }
 
-    panic!("did not receive a local human-vs-human stats report");
+    panic!("did not receive a {mode} stats report");
+}
+
+async fn reject_next_http_request(listener: &TcpListener) {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    read_http_body(&mut stream).await;
+    stream
+        .write_all(b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\n\r\n")
+        .await
+        .unwrap();
+}
+
+async fn finish_local_game(app: &axum::Router) -> String {
+    let (_, created) = request_json_with_headers(
+        app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 1,
+            "mode": "human_vs_human"
+        })),
+        &[("x-user-id", "fernando")],
+    )
+    .await;
+
+    let game_id = created["game_id"].as_str().unwrap().to_string();
+    let (move_status, moved) = request_json(
+        app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/moves"),
+        Some(json!({
+            "coords": { "x": 0, "y": 0, "z": 0 }
+        })),
+    )
+    .await;
+
+    assert_eq!(move_status, StatusCode::OK);
+    assert_eq!(moved["game_over"], true);
+    game_id
 }
 
 async fn request_json(
@@ -267,6 +312,307 @@ async fn create_human_vs_human_rejects_bot_id() {
     );
 }
 
+#[tokio::test]
+async fn create_human_vs_human_rejects_color() {
+    let app = test_app();
+
+    let (status, body) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 3,
+            "mode": "human_vs_human",
+            "color": "second"
+        })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["message"]
+            .as_str()
+            .unwrap()
+            .contains("color is only valid in human_vs_bot mode")
+    );
+}
+
+#[tokio::test]
+async fn bot_opens_the_game_when_the_human_plays_second() {
+    let app = test_app();
+
+    let (create_status, created) = request_json_with_headers(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 3,
+            "mode": "human_vs_bot",
+            "bot_id": "random_bot",
+            "color": "second"
+        })),
+        &[("x-user-id", "alice")],
+    )
+    .await;
+
+    assert_eq!(create_status, StatusCode::OK);
+    assert_eq!(created["human_player"], 1);
+    assert_eq!(created["next_player"], 1);
+    assert_eq!(created["player0_user_id"], Value::Null);
+    assert_eq!(created["player1_user_id"], "alice");
+    let stones = |state: &Value| {
+        state["yen"]["layout"]
+            .as_str()
+            .unwrap()
+            .chars()
+            .filter(|cell| !matches!(cell, '.' | '/'))
+            .count()
+    };
+    assert_eq!(stones(&created), 1);
+    let game_id = created["game_id"].as_str().unwrap();
+
+    let (pass_status, passed) = request_json(
+        &app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/pass"),
+        None,
+    )
+    .await;
+    assert_eq!(pass_status, StatusCode::OK);
+    assert_eq!(passed["next_player"], 1);
+    assert_eq!(stones(&passed), 2);
+
+    let (resign_status, resigned) = request_json(
+        &app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/resign"),
+        None,
+    )
+    .await;
+    assert_eq!(resign_status, StatusCode::OK);
+    assert_eq!(resigned["winner"], 0);
+}
+
+#[tokio::test]
+async fn create_bot_vs_bot_requires_two_bots() {
+    let app = test_app();
+
+    let (status, body) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 3,
+            "mode": "bot_vs_bot",
+            "bot_id": "random_bot"
+        })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["message"]
+            .as_str()
+            .unwrap()
+            .contains("opponent_bot_id is required")
+    );
+}
+
+#[tokio::test]
+async fn players_cannot_move_in_bot_vs_bot_games() {
+    let app = test_app();
+
+    let (create_status, created) = request_json_with_headers(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 5,
+            "mode": "bot_vs_bot",
+            "bot_id": "random_bot",
+            "opponent_bot_id": "greedy_bot"
+        })),
+        &[("x-user-id", "alice")],
+    )
+    .await;
+    assert_eq!(create_status, StatusCode::OK);
+    assert_eq!(created["mode"], "bot_vs_bot");
+    assert_eq!(created["bot_id"], "random_bot");
+    assert_eq!(created["opponent_bot_id"], "greedy_bot");
+    assert_eq!(created["human_player"], Value::Null);
+    assert_eq!(created["player0_user_id"], Value::Null);
+    let game_id = created["game_id"].as_str().unwrap();
+
+    let (move_status, body) = request_json(
+        &app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/moves"),
+        Some(json!({ "coords": { "x": 4, "y": 0, "z": 0 } })),
+    )
+    .await;
+    assert_eq!(move_status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains("bot_vs_bot"));
+
+    // An exhibition is nobody's active game.
+    let (other_status, _) = request_json_with_headers(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({ "size": 3, "mode": "human_vs_bot" })),
+        &[("x-user-id", "alice")],
+    )
+    .await;
+    assert_eq!(other_status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn create_game_from_initial_position_keeps_side_to_move() {
+    let app = test_app();
+    let position = json!({
+        "size": 3,
+        "turn": 1,
+        "players": ["B", "R"],
+        "layout": "./B./..."
+    });
+
+    let (status, created) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "mode": "human_vs_human",
+            "initial_position": position
+        })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(created["next_player"], 1);
+    assert_eq!(created["yen"]["size"], 3);
+    assert_eq!(created["yen"]["layout"], "./B./...");
+    assert_eq!(created["start_position"], position);
+}
+
+#[tokio::test]
+async fn create_game_rejects_invalid_initial_position() {
+    let app = test_app();
+
+    let (status, body) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "mode": "human_vs_human",
+            "initial_position": {
+                "size": 3,
+                "turn": 0,
+                "players": ["B", "R"],
+                "layout": "B/BB/..."
+            }
+        })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let message = body["message"].as_str().unwrap();
+    assert!(message.contains("Invalid initial_position"));
+    assert!(message.contains("Inconsistent stone count"));
+}
+
+#[tokio::test]
+async fn create_game_rejects_size_that_does_not_match_initial_position() {
+    let app = test_app();
+
+    let (status, body) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 5,
+            "mode": "human_vs_human",
+            "initial_position": {
+                "size": 3,
+                "turn": 1,
+                "players": ["B", "R"],
+                "layout": "./B./..."
+            }
+        })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["message"]
+            .as_str()
+            .unwrap()
+            .contains("size 5 does not match the initial_position size 3")
+    );
+}
+
+#[tokio::test]
+async fn moves_given_at_creation_are_part_of_the_starting_position() {
+    let app = test_app();
+
+    let (status, created) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 4,
+            "mode": "human_vs_bot",
+            "bot_id": "random_bot",
+            "moves": [{ "x": 3, "y": 0, "z": 0 }]
+        })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    // The bot is to move after the given placement and replies at once.
+    assert_eq!(created["next_player"], 0);
+    assert_eq!(created["start_position"]["turn"], 1);
+    let game_id = created["game_id"].as_str().unwrap();
+
+    let (undo_status, undone) = request_json(
+        &app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/undo"),
+        None,
+    )
+    .await;
+    assert_eq!(undo_status, StatusCode::BAD_REQUEST);
+    assert!(
+        undone["message"]
+            .as_str()
+            .unwrap()
+            .contains("no human move")
+    );
+}
+
+#[tokio::test]
+async fn create_game_rejects_invalid_moves() {
+    let app = test_app();
+
+    let (status, body) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 3,
+            "mode": "human_vs_human",
+            "moves": [{ "x": 2, "y": 0, "z": 0 }, { "x": 2, "y": 0, "z": 0 }]
+        })),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        body["message"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid move 1 in moves")
+    );
+}
+
 // Test: play move updates turn in human vs human game.
 #[tokio::test]
 async fn play_move_updates_turn_in_human_vs_human_game() {
@@ -492,46 +838,168 @@ async fn pass_turn_clears_finished_local_bot_game_from_active_game_index() {
     assert_eq!(next_game["game_over"], false);
 }
 
-#[tokio::test(flavor = "current_thread")]
-async fn finished_local_human_vs_human_game_is_reported_to_stats() {
-    let _env_guard = STATS_ENV_MUTEX.lock().unwrap();
-    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
-    let stats_url = format!("http://{}", listener.local_addr().unwrap());
-    let previous_stats_url = std::env::var("STATS_SERVICE_URL").ok();
-    let previous_stats_token = std::env::var("STATS_INTERNAL_TOKEN").ok();
-    unsafe {
-        std::env::set_var("STATS_SERVICE_URL", &stats_url);
-        std::env::set_var("STATS_INTERNAL_TOKEN", "stats-internal-token");
-    }
+#[tokio::test]
+async fn undo_takes_back_human_move_and_bot_reply() {
+    let app = test_app();
 
-    let capture_task = tokio::spawn(capture_next_http_body(listener));
+    let (_, created) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 4,
+            "mode": "human_vs_bot",
+            "bot_id": "random_bot"
+        })),
+    )
+    .await;
+    let game_id = created["game_id"].as_str().unwrap();
+    let empty_layout = created["yen"]["layout"].clone();
+
+    let (move_status, moved) = request_json(
+        &app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/moves"),
+        Some(json!({ "coords": { "x": 3, "y": 0, "z": 0 } })),
+    )
+    .await;
+    assert_eq!(move_status, StatusCode::OK);
+    assert_ne!(moved["yen"]["layout"], empty_layout);
+
+    let (undo_status, undone) = request_json(
+        &app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/undo"),
+        None,
+    )
+    .await;
+
+    assert_eq!(undo_status, StatusCode::OK);
+    assert_eq!(undone["game_over"], false);
+    assert_eq!(undone["next_player"], 0);
+    assert_eq!(undone["yen"]["layout"], empty_layout);
+
+    let (second_status, second) = request_json(
+        &app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/undo"),
+        None,
+    )
+    .await;
+
+    assert_eq!(second_status, StatusCode::BAD_REQUEST);
+    assert!(second["message"].as_str().unwrap().contains("no human move"));
+}
+
+#[tokio::test]
+async fn undo_is_rejected_in_human_vs_human_game() {
     let app = test_app();
 
+    let (_, created) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 3,
+            "mode": "human_vs_human"
+        })),
+    )
+    .await;
+    let game_id = created["game_id"].as_str().unwrap();
+
+    request_json(
+        &app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/moves"),
+        Some(json!({ "coords": { "x": 2, "y": 0, "z": 0 } })),
+    )
+    .await;
+
+    let (status, body) = request_json(
+        &app,
+        Method::POST,
+        &format!("/v1/games/{game_id}/undo"),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains("human_vs_bot"));
+}
+
+#[tokio::test]
+async fn games_survive_restart_with_file_store() {
+    let storage_dir = tempfile::tempdir().unwrap();
+    let state = create_default_state()
+        .with_game_store(Arc::new(FileGameStore::new(storage_dir.path()).unwrap()));
+    let app = create_router(state.clone());
+
     let (_, created) = request_json_with_headers(
         &app,
         Method::POST,
         "/v1/games",
         Some(json!({
-            "size": 1,
+            "size": 3,
             "mode": "human_vs_human"
         })),
         &[("x-user-id", "fernando")],
     )
     .await;
+    let game_id = created["game_id"].as_str().unwrap().to_string();
 
-    let game_id = created["game_id"].as_str().unwrap();
-    let (move_status, moved) = request_json(
+    let (_, moved) = request_json(
         &app,
         Method::POST,
         &format!("/v1/games/{game_id}/moves"),
+        Some(json!({ "coords": { "x": 2, "y": 0, "z": 0 } })),
+    )
+    .await;
+    state.flush_game_store().await;
+
+    let restarted_state = create_default_state()
+        .with_game_store(Arc::new(FileGameStore::new(storage_dir.path()).unwrap()));
+    assert_eq!(restore_games(&restarted_state).await.unwrap(), 1);
+    let restarted_app = create_router(restarted_state);
+
+    let (status, fetched) = request_json(
+        &restarted_app,
+        Method::GET,
+        &format!("/v1/games/{game_id}"),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched["yen"], moved["yen"]);
+    assert_eq!(fetched["next_player"], 1);
+    assert_eq!(fetched["player0_user_id"], "fernando");
+
+    let (second_status, _) = request_json_with_headers(
+        &restarted_app,
+        Method::POST,
+        "/v1/games",
         Some(json!({
-            "coords": { "x": 0, "y": 0, "z": 0 }
+            "size": 3,
+            "mode": "human_vs_human"
         })),
+        &[("x-user-id", "fernando")],
     )
     .await;
+    assert_eq!(second_status, StatusCode::BAD_REQUEST);
+}
 
-    assert_eq!(move_status, StatusCode::OK);
-    assert_eq!(moved["game_over"], true);
+#[tokio::test(flavor = "current_thread")]
+async fn finished_local_human_vs_human_game_is_reported_to_stats() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut config = ServerConfig::default();
+    config.stats.url = format!("http://{}", listener.local_addr().unwrap());
+
+    let capture_task = tokio::spawn(capture_next_http_body(listener, "local_human_vs_human"));
+    let state = create_default_state().with_config(config);
+    start_stats_outbox_sender(state.clone());
+    let app = create_router(state);
+
+    let game_id = finish_local_game(&app).await;
 
     let body = capture_task.await.unwrap();
     let payload: Value = serde_json::from_str(&body).unwrap();
@@ -544,18 +1012,104 @@ async fn finished_local_human_vs_human_game_is_reported_to_stats() {
     assert_eq!(payload["players"][0]["result"], "win");
     assert_eq!(payload["players"][1]["userId"], "player-1");
     assert_eq!(payload["players"][1]["result"], "loss");
+    assert!(payload["endedAt"].as_u64().unwrap() > 0);
+}
 
-    unsafe {
-        if let Some(previous_stats_url) = previous_stats_url {
-            std::env::set_var("STATS_SERVICE_URL", previous_stats_url);
-        } else {
-            std::env::remove_var("STATS_SERVICE_URL");
-        }
+#[tokio::test]
+async fn stats_report_is_retried_after_the_stats_service_fails() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut config = ServerConfig::default();
+    config.stats.url = format!("http://{}", listener.local_addr().unwrap());
+    config.stats.retry_initial_backoff = Duration::from_millis(10);
+
+    let capture_task = tokio::spawn(async move {
+        reject_next_http_request(&listener).await;
+        capture_next_http_body(listener, "local_human_vs_human").await
+    });
+    let state = create_default_state().with_config(config);
+    start_stats_outbox_sender(state.clone());
+    let app = create_router(state.clone());
+
+    let game_id = finish_local_game(&app).await;
+
+    let body = tokio::time::timeout(Duration::from_secs(5), capture_task)
+        .await
+        .expect("report should be retried")
+        .unwrap();
+    let payload: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload["gameId"], game_id);
 
-        if let Some(previous_stats_token) = previous_stats_token {
-            std::env::set_var("STATS_INTERNAL_TOKEN", previous_stats_token);
-        } else {
-            std::env::remove_var("STATS_INTERNAL_TOKEN");
+    for _ in 0..50 {
+        if state.stats_outbox().is_empty() {
+            return;
         }
+        tokio::time::sleep(Duration::from_millis(10)).await;
     }
+    panic!("delivered report should leave the outbox");
+}
+
+#[tokio::test]
+async fn bot_vs_bot_game_is_played_by_the_server_and_reported_to_stats() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mut config = ServerConfig::default();
+    config.stats.url = format!("http://{}", listener.local_addr().unwrap());
+    config.exhibition.move_delay = Duration::ZERO;
+
+    let capture_task = tokio::spawn(capture_next_http_body(listener, "bot_vs_bot"));
+    let state = create_default_state().with_config(config);
+    start_stats_outbox_sender(state.clone());
+    let app = create_router(state);
+
+    let (create_status, created) = request_json(
+        &app,
+        Method::POST,
+        "/v1/games",
+        Some(json!({
+            "size": 3,
+            "mode": "bot_vs_bot",
+            "bot_id": "random_bot",
+            "opponent_bot_id": "greedy_bot"
+        })),
+    )
+    .await;
+    assert_eq!(create_status, StatusCode::OK);
+    let game_id = created["game_id"].as_str().unwrap();
+
+    let body = tokio::time::timeout(Duration::from_secs(5), capture_task)
+        .await
+        .expect("exhibition should finish")
+        .unwrap();
+    let payload: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(payload["gameId"], game_id);
+    assert_eq!(payload["mode"], "bot_vs_bot");
+    assert_eq!(payload["players"][0]["userId"], "bot:random_bot");
+    assert_eq!(payload["players"][1]["userId"], "bot:greedy_bot");
+
+    let (get_status, fetched) =
+        request_json(&app, Method::GET, &format!("/v1/games/{game_id}"), None).await;
+    assert_eq!(get_status, StatusCode::OK);
+    assert_eq!(fetched["game_over"], true);
+}
+
+#[tokio::test]
+async fn bot_vs_bot_games_beyond_the_limit_are_rejected() {
+    let mut config = ServerConfig::default();
+    config.exhibition.max_concurrent = 1;
+    config.exhibition.move_delay = Duration::from_secs(60);
+    let app = create_router(create_default_state().with_config(config));
+    let exhibition = json!({
+        "size": 3,
+        "mode": "bot_vs_bot",
+        "bot_id": "random_bot",
+        "opponent_bot_id": "greedy_bot"
+    });
+
+    let (first_status, _) =
+        request_json(&app, Method::POST, "/v1/games", Some(exhibition.clone())).await;
+    let (second_status, body) =
+        request_json(&app, Method::POST, "/v1/games", Some(exhibition)).await;
+
+    assert_eq!(first_status, StatusCode::OK);
+    assert_eq!(second_status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(body["message"].as_str().unwrap().contains("Too many"));
 }
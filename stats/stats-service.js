@@ -7,7 +7,7 @@ const DEFAULT_PORT = Number(process.env.PORT ?? 3001);
 const MONGO_URL = process.env.MONGO_URL ?? 'mongodb://localhost:27017';
 const MONGO_DB_NAME = process.env.MONGO_DB_NAME ?? 'yovi_stats';
 const INTERNAL_TOKEN = process.env.STATS_INTERNAL_TOKEN ?? 'stats-internal-token';
-const HISTORY_MODE_VALUES = ['human_vs_bot', 'local_human_vs_human', 'human_vs_human', 'online'];
+const HISTORY_MODE_VALUES = ['human_vs_bot', 'bot_vs_bot', 'local_human_vs_human', 'human_vs_human', 'online'];
 
 // --- Helpers de validacion y normalizacion ---
 
@@ -240,6 +240,12 @@ function validateFinishedMatchPayload(payload) {
   const finalBoardError = validateOptionalObject(payload.finalBoard, 'finalBoard must be an object');
   if (finalBoardError) return finalBoardError;
 
+  const startPositionError = validateOptionalObject(
+    payload.startPosition,
+    'startPosition must be an object',
+  );
+  if (startPositionError) return startPositionError;
+
   const botIdError = validateOptionalString(payload.botId, 'botId must be a string');
   if (botIdError) return botIdError;
 
@@ -425,6 +431,7 @@ function createApp({ internalToken = INTERNAL_TOKEN, playerStatsCollection, play
     const winnerId = toOptionalString(req.body.winnerId);
     const botId = toOptionalString(req.body.botId);
     const finalBoard = toOptionalObject(req.body.finalBoard);
+    const startPosition = toOptionalObject(req.body.startPosition);
 
     if (Number.isNaN(endedAt.getTime())) {
       return res.status(400).json({ message: 'endedAt must be a valid date' });
@@ -450,6 +457,7 @@ function createApp({ internalToken = INTERNAL_TOKEN, playerStatsCollection, play
           reason,
           winnerId,
           finalBoard,
+          startPosition,
           endedAt,
           createdAt: new Date(),
         };
@@ -308,6 +308,14 @@ test('validateFinishedMatchPayload checks required fields', () => {
     }),
     'finalBoard must be an object',
   );
+  assert.equal(
+    validateFinishedMatchPayload({
+      gameId: 'g1',
+      players: [{ userId: 'u1', result: 'win' }],
+      startPosition: 'B/..',
+    }),
+    'startPosition must be an object',
+  );
   assert.equal(
     validateFinishedMatchPayload({
       gameId: 'g1',
@@ -1,4 +1,6 @@
-export type GameMode = 'human_vs_human' | 'human_vs_bot';
+export type GameMode = 'human_vs_human' | 'human_vs_bot' | 'bot_vs_bot';
+
+export type ColorChoice = 'first' | 'second' | 'random';
 
 export interface Coordinates {
   x: number;
@@ -15,11 +17,27 @@ export interface YEN {
 
 export interface CreateGameRequest {
   size?: number;
+  initial_position?: YEN;
+  moves?: Coordinates[];
   mode?: GameMode;
   bot_id?: string;
+  opponent_bot_id?: string;
+  color?: ColorChoice;
+  time_control?: TimeControl;
+}
+
+export type TimeControl =
+  | { kind: 'fischer'; base_ms: number; increment_ms: number }
+  | { kind: 'byo_yomi'; base_ms: number; period_ms: number; periods: number };
+
+export interface ClockState {
+  time_control: TimeControl;
+  remaining_ms: [number, number];
+  periods_left: [number, number];
+  running_player: number | null;
 }
 
-export type GameCompletionReason = 'win_condition' | 'resignation' | 'disconnect_timeout';
+export type GameCompletionReason = 'win_condition' | 'resignation' | 'disconnect_timeout' | 'timeout' | 'bot_failure';
 
 export interface MoveRequest {
   coords: Coordinates;
@@ -31,7 +49,10 @@ export interface GameStateResponse {
   game_id: string;
   mode: GameMode;
   bot_id: string | null;
+  opponent_bot_id?: string | null;
+  human_player?: number | null;
   yen: YEN;
+  start_position?: YEN | null;
   game_over: boolean;
   next_player: number | null;
   winner: number | null;
@@ -40,13 +61,14 @@ export interface GameStateResponse {
   player1_user_id?: string | null;
   opponent_inactivity_timeout_remaining_ms?: number | null;
   turn_timeout_remaining_ms?: number | null;
+  clock?: ClockState | null;
 }
 
 interface ApiErrorResponse {
   message: string;
 }
 
-export type MatchmakingStatus = 'waiting' | 'matched' | 'cancelled';
+export type MatchmakingStatus = 'waiting' | 'matched' | 'cancelled' | 'expired';
 
 export interface MatchmakingTicketResponse {
   api_version: string;
@@ -57,6 +79,7 @@ export interface MatchmakingTicketResponse {
   game_id: string | null;
   player_id: number | null;
   player_token: string | null;
+  rating_gap?: number | null;
 }
 
 const GAMEY_API_URL = import.meta.env.VITE_GAMEY_API_URL ?? '/api';
@@ -264,6 +287,51 @@ export async function getBotHint(
   });
 }
 
+export interface CandidateMove {
+  coords: Coordinates;
+  score: number;
+}
+
+export interface SideConnection {
+  cost: number;
+  cells: Coordinates[];
+}
+
+export interface ConnectionCosts {
+  player: number;
+  side_a_to_b: SideConnection | null;
+  side_b_to_c: SideConnection | null;
+  side_a_to_c: SideConnection | null;
+  score: number;
+}
+
+export interface PositionAnalysisResponse {
+  api_version: string;
+  player: number;
+  depth: number;
+  candidates: CandidateMove[];
+  connection_costs: ConnectionCosts[];
+  principal_variation: Coordinates[];
+}
+
+export async function analyzePosition(
+  yen: YEN,
+  topK = 5,
+  timeLimitMs?: number,
+): Promise<PositionAnalysisResponse> {
+  const params = new URLSearchParams({ top_k: String(topK) });
+  if (timeLimitMs !== undefined) {
+    params.set('time_limit_ms', String(timeLimitMs));
+  }
+  return requestJson<PositionAnalysisResponse>(buildApiUrl(`/v1/ybot/analyze?${params}`), {
+    method: 'POST',
+    headers: {
+      'Content-Type': 'application/json',
+    },
+    body: JSON.stringify(yen),
+  });
+}
+
 export async function enqueueMatchmaking(
   size = 7,
   userId?: string,